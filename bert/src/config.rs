@@ -12,7 +12,13 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::{marker::PhantomData, path::PathBuf};
+use std::{
+    io::Read,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    str,
+    sync::Arc,
+};
 
 use cfg_if::cfg_if;
 use figment::{
@@ -21,12 +27,13 @@ use figment::{
     Figment,
 };
 use serde::Deserialize;
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 use crate::{
     model::Model,
     pipeline::{Pipeline, PipelineError},
     pooler::NonePooler,
-    tokenizer::Tokenizer,
+    tokenizer::{default_token_size, Tokenizer, MAX_TOKEN_SIZE, MIN_TOKEN_SIZE},
 };
 
 /// A pipeline configuration.
@@ -50,13 +57,31 @@ use crate::{
 /// ```
 #[must_use]
 pub struct Config<P> {
-    pub(crate) dir: PathBuf,
-    toml: Figment,
+    assets: Assets,
+    pub(crate) toml: Figment,
     pub(crate) token_size: usize,
+    pub(crate) token_buckets: Vec<usize>,
     pub(crate) runtime: PathBuf,
     pooler: PhantomData<P>,
 }
 
+/// The tokenizer and model files.
+enum Assets {
+    /// The files are in a directory.
+    Dir(PathBuf),
+    /// The contents of the files are in memory.
+    Bytes {
+        tokenizer: Vec<u8>,
+        model: Arc<[u8]>,
+    },
+}
+
+/// A single tokenizer or model file.
+pub(crate) enum Asset<B> {
+    File(PathBuf),
+    Bytes(B),
+}
+
 impl Config<NonePooler> {
     /// Creates a pipeline configuration.
     pub fn new(dir: impl Into<PathBuf>, runtime: impl Into<PathBuf>) -> Result<Self, Error> {
@@ -74,23 +99,111 @@ impl Config<NonePooler> {
                 toml.display(),
             ))));
         }
+
+        Self::with_assets(Assets::Dir(dir), Figment::from(Toml::file(toml)), runtime)
+    }
+
+    /// Creates a pipeline configuration from in-memory files.
+    ///
+    /// The files are the contents of the `config.toml`, `tokenizer.json` and `model.onnx`, e.g.
+    /// from an asset bundle or a download. The runtime is a dynamic library and still loaded from
+    /// the file system.
+    pub fn from_bytes(
+        config: impl AsRef<[u8]>,
+        tokenizer: impl Into<Vec<u8>>,
+        model: impl Into<Arc<[u8]>>,
+        runtime: impl Into<PathBuf>,
+    ) -> Result<Self, Error> {
+        let toml = str::from_utf8(config.as_ref()).map_err(|error| {
+            Error::from(Kind::Message(format!(
+                "embedder config isn't valid utf-8: {error}"
+            )))
+        })?;
+        let assets = Assets::Bytes {
+            tokenizer: tokenizer.into(),
+            model: model.into(),
+        };
+
+        Self::with_assets(assets, Figment::from(Toml::string(toml)), runtime)
+    }
+
+    /// Creates a pipeline configuration from readers of the files.
+    ///
+    /// See [`Config::from_bytes()`] for details.
+    pub fn from_readers(
+        config: impl Read,
+        tokenizer: impl Read,
+        model: impl Read,
+        runtime: impl Into<PathBuf>,
+    ) -> Result<Self, Error> {
+        fn read(mut reader: impl Read, file: &str) -> Result<Vec<u8>, Error> {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).map_err(|error| {
+                Error::from(Kind::Message(format!(
+                    "failed to read embedder {file}: {error}"
+                )))
+            })?;
+            Ok(bytes)
+        }
+
+        Self::from_bytes(
+            read(config, "config")?,
+            read(tokenizer, "tokenizer")?,
+            read(model, "model")?,
+            runtime,
+        )
+    }
+
+    /// Creates a pipeline configuration from asynchronous readers of the files.
+    ///
+    /// See [`Config::from_bytes()`] for details.
+    pub async fn from_async_readers(
+        config: impl AsyncRead + Send + Unpin,
+        tokenizer: impl AsyncRead + Send + Unpin,
+        model: impl AsyncRead + Send + Unpin,
+        runtime: impl Into<PathBuf> + Send,
+    ) -> Result<Self, Error> {
+        async fn read(
+            mut reader: impl AsyncRead + Send + Unpin,
+            file: &str,
+        ) -> Result<Vec<u8>, Error> {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await.map_err(|error| {
+                Error::from(Kind::Message(format!(
+                    "failed to read embedder {file}: {error}"
+                )))
+            })?;
+            Ok(bytes)
+        }
+
+        Self::from_bytes(
+            read(config, "config").await?,
+            read(tokenizer, "tokenizer").await?,
+            read(model, "model").await?,
+            runtime,
+        )
+    }
+
+    fn with_assets(
+        assets: Assets,
+        toml: Figment,
+        runtime: impl Into<PathBuf>,
+    ) -> Result<Self, Error> {
         let runtime = runtime.into();
         if !runtime.exists() {
             return Err(Error::from(Kind::Message(format!(
                 "embedder runtime directory '{}' doesn't exist",
-                dir.display(),
+                runtime.display(),
             ))));
         }
 
-        let toml = Figment::from(Toml::file(toml));
-        let token_size = (toml.extract_inner::<usize>(Self::MIN_TOKEN_SIZE)?
-            + toml.extract_inner::<usize>(Self::MAX_TOKEN_SIZE)?)
-            / 2;
+        let token_size = default_token_size(&toml)?;
 
         Ok(Self {
-            dir,
+            assets,
             toml,
             token_size,
+            token_buckets: Vec::new(),
             runtime,
             pooler: PhantomData,
         })
@@ -98,9 +211,6 @@ impl Config<NonePooler> {
 }
 
 impl<P> Config<P> {
-    const MIN_TOKEN_SIZE: &str = "tokenizer.min_size";
-    const MAX_TOKEN_SIZE: &str = "tokenizer.max_size";
-
     pub(crate) fn extract<'b, V>(&self, key: &str) -> Result<V, Error>
     where
         V: Deserialize<'b>,
@@ -109,14 +219,25 @@ impl<P> Config<P> {
     }
 
     pub fn validate(&self) -> Result<(), Error> {
-        let min = self.extract::<usize>(Self::MIN_TOKEN_SIZE)?;
-        let max = self.extract::<usize>(Self::MAX_TOKEN_SIZE)?;
+        let min = self.extract::<usize>(MIN_TOKEN_SIZE)?;
+        let max = self.extract::<usize>(MAX_TOKEN_SIZE)?;
         if !(min..=max).contains(&self.token_size) {
             return Err(Error::from(Kind::InvalidValue(
                 Actual::Unsigned(self.token_size as u128),
                 format!("token_size in {min}..={max}"),
             )));
         }
+        // Hint: larger buckets would pad the sequences beyond the token size
+        if let Some(&bucket) = self
+            .token_buckets
+            .iter()
+            .find(|bucket| !(min..=self.token_size).contains(*bucket))
+        {
+            return Err(Error::from(Kind::InvalidValue(
+                Actual::Unsigned(bucket as u128),
+                format!("token_buckets in {min}..={}", self.token_size),
+            )));
+        }
 
         Ok(())
     }
@@ -135,32 +256,69 @@ impl<P> Config<P> {
         Ok(self)
     }
 
+    /// Sets the token size buckets for the tokenizer.
+    ///
+    /// Each tokenized sequence is padded to the smallest bucket which fits it. This keeps the number
+    /// of distinct input shapes of the model small, if the largest bucket is the token size. Longer
+    /// sequences are still only truncated to the token size. Defaults to no buckets, ie sequences
+    /// are not padded.
+    ///
+    /// # Errors
+    /// Fails if any bucket is not within the token size range or is larger than the token size.
+    pub fn with_token_buckets(
+        mut self,
+        buckets: impl IntoIterator<Item = usize>,
+    ) -> Result<Self, Error> {
+        let mut buckets = buckets.into_iter().collect::<Vec<_>>();
+        buckets.sort_unstable();
+        buckets.dedup();
+        self.token_buckets = buckets;
+        self.validate()?;
+
+        Ok(self)
+    }
+
     /// Sets the pooler for the model.
     ///
     /// Defaults to `NonePooler`.
     pub fn with_pooler<Q>(self) -> Config<Q> {
         Config {
-            dir: self.dir,
+            assets: self.assets,
             toml: self.toml,
             token_size: self.token_size,
+            token_buckets: self.token_buckets,
             runtime: self.runtime,
             pooler: PhantomData,
         }
     }
 
-    pub(crate) fn model(&self) -> Result<PathBuf, Error> {
-        let model = self.dir.join("model.onnx");
+    fn file<B>(dir: &Path, file: &str, name: &str) -> Result<Asset<B>, Error> {
+        let path = dir.join(file);
 
-        if model.exists() {
-            Ok(model)
+        if path.exists() {
+            Ok(Asset::File(path))
         } else {
             Err(Error::from(Kind::Message(format!(
-                "embedder model '{}' doesn't exist",
-                model.display(),
+                "embedder {name} '{}' doesn't exist",
+                path.display(),
             ))))
         }
     }
 
+    pub(crate) fn tokenizer(&self) -> Result<Asset<&[u8]>, Error> {
+        match &self.assets {
+            Assets::Dir(dir) => Self::file(dir, "tokenizer.json", "tokenizer"),
+            Assets::Bytes { tokenizer, .. } => Ok(Asset::Bytes(tokenizer)),
+        }
+    }
+
+    pub(crate) fn model(&self) -> Result<Asset<Arc<[u8]>>, Error> {
+        match &self.assets {
+            Assets::Dir(dir) => Self::file(dir, "model.onnx", "model"),
+            Assets::Bytes { model, .. } => Ok(Asset::Bytes(model.clone())),
+        }
+    }
+
     pub(crate) fn runtime(&self) -> Result<PathBuf, Error> {
         cfg_if! {
             if #[cfg(target_os = "linux")] {
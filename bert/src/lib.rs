@@ -36,26 +36,41 @@
     clippy::must_use_candidate
 )]
 
+#[cfg(feature = "pipeline")]
 mod config;
+#[cfg(feature = "pipeline")]
 mod model;
+#[cfg(feature = "pipeline")]
 mod pipeline;
 mod pooler;
+#[cfg(feature = "tokenizer")]
 mod tokenizer;
 
+#[cfg(feature = "tokenizer")]
+pub use tokenizers::Encoding;
+
+pub use crate::pooler::{
+    AveragePooler,
+    Embedding,
+    Embedding1,
+    Embedding2,
+    FirstPooler,
+    InvalidEmbedding,
+    NonePooler,
+    NormalizedEmbedding,
+};
+#[cfg(feature = "tokenizer")]
+pub use crate::tokenizer::Tokenizer;
+#[cfg(feature = "pipeline")]
 pub use crate::{
     config::Config,
     pipeline::{Pipeline, PipelineError},
-    pooler::{
-        AveragePooler,
-        Embedding,
-        Embedding1,
-        Embedding2,
-        FirstPooler,
-        InvalidEmbedding,
-        NonePooler,
-        NormalizedEmbedding,
-    },
 };
 
 /// A Transformer pipeline with an average pooler.
+#[cfg(feature = "pipeline")]
 pub type AvgEmbedder = Pipeline<AveragePooler>;
+
+/// A Transformer pipeline with a first token pooler.
+#[cfg(feature = "pipeline")]
+pub type FirstEmbedder = Pipeline<FirstPooler>;
@@ -25,7 +25,7 @@ use ort::{
         ExecutionProvider,
         TensorRTExecutionProviderOptions,
     },
-    session::{Session, SessionBuilder},
+    session::SessionBuilder,
     tensor::OrtOwnedTensor,
     value::Value,
     GraphOptimizationLevel,
@@ -33,12 +33,13 @@ use ort::{
 };
 use tokenizers::Encoding;
 
-use crate::config::Config;
+use self::runtime::Runtime;
+use crate::config::{Asset, Config};
 
 /// A Bert onnx model.
 #[derive(Debug)]
 pub(crate) struct Model {
-    runtime: Session,
+    runtime: Runtime,
     use_type_ids: bool,
     pub(crate) embedding_size: usize,
     // we drop env last. This as been fixed upstream but not yet released.
@@ -47,6 +48,69 @@ pub(crate) struct Model {
 
 pub(crate) struct Embedding(Value<'static>);
 
+// the async builders which are generated for the self-referencing runtime aren't used
+#[allow(clippy::future_not_send)]
+mod runtime {
+    use std::{fmt, ops::Deref, path::Path, sync::Arc};
+
+    use ort::{InMemorySession, OrtResult, Session, SessionBuilder};
+    use ouroboros::self_referencing;
+
+    /// A runtime session of a model file or of model bytes.
+    #[derive(Debug)]
+    pub(super) enum Runtime {
+        File(Session),
+        Memory(MemoryRuntime),
+    }
+
+    /// A runtime session which borrows the model bytes for its whole lifetime.
+    #[self_referencing]
+    pub(super) struct MemoryRuntime {
+        model: Arc<[u8]>,
+        #[borrows(model)]
+        #[covariant]
+        session: InMemorySession<'this>,
+    }
+
+    impl Runtime {
+        pub(super) fn from_file(
+            builder: SessionBuilder,
+            model: impl AsRef<Path>,
+        ) -> OrtResult<Self> {
+            builder.with_model_from_file(model).map(Self::File)
+        }
+
+        pub(super) fn from_memory(builder: SessionBuilder, model: Arc<[u8]>) -> OrtResult<Self> {
+            MemoryRuntimeTryBuilder {
+                model,
+                session_builder: |model| builder.with_model_from_memory(model),
+            }
+            .try_build()
+            .map(Self::Memory)
+        }
+    }
+
+    impl Deref for Runtime {
+        type Target = Session;
+
+        fn deref(&self) -> &Self::Target {
+            match self {
+                Self::File(session) => session,
+                Self::Memory(runtime) => runtime.borrow_session(),
+            }
+        }
+    }
+
+    impl fmt::Debug for MemoryRuntime {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("MemoryRuntime")
+                .field("model", &self.borrow_model().len())
+                .field("session", &**self.borrow_session())
+                .finish()
+        }
+    }
+}
+
 impl Model {
     /// Creates a model from a configuration.
     pub(crate) fn new<P>(config: &Config<P>) -> Result<Self, Error> {
@@ -66,21 +130,21 @@ impl Model {
         let session = SessionBuilder::new(&environment)?
             // TODO: this is the default, we could run the optimizations once offline and then
             // always load the optimized model from disk with GraphOptimizationLevel::Disable
-            .with_optimization_level(GraphOptimizationLevel::Level3)?
-            .with_model_from_file(config.model()?)?;
+            .with_optimization_level(GraphOptimizationLevel::Level3)?;
+        let runtime = match config.model()? {
+            Asset::File(model) => Runtime::from_file(session, model)?,
+            Asset::Bytes(model) => Runtime::from_memory(session, model)?,
+        };
 
-        let use_type_ids = session.inputs.len() > 2;
+        let use_type_ids = runtime.inputs.len() > 2;
         let Some(embedding_size) =
-            session.outputs[0].dimensions[2].or_else(|| session.outputs[1].dimensions[1])
+            runtime.outputs[0].dimensions[2].or_else(|| runtime.outputs[1].dimensions[1])
         else {
-            bail!(format!(
-                "embedder model '{}' has unspecified embedding size",
-                config.model()?.display(),
-            ));
+            bail!("embedder model has unspecified embedding size");
         };
 
         Ok(Model {
-            runtime: session,
+            runtime,
             use_type_ids,
             embedding_size: embedding_size as usize,
             _env: environment,
@@ -89,9 +89,12 @@ impl<P> Pipeline<P> {
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::{fs, path::PathBuf};
 
-    use xayn_test_utils::asset::{e5_mocked, ort, smbert_mocked};
+    use xayn_test_utils::{
+        assert_approx_eq,
+        asset::{e5_mocked, ort, smbert_mocked},
+    };
 
     use super::*;
     use crate::{
@@ -140,6 +143,30 @@ mod tests {
         assert_eq!(embeddings.shape(), [pipeline.embedding_size()]);
     }
 
+    #[test]
+    fn test_pipeline_from_bytes() {
+        let dir = smbert_mocked().unwrap();
+        let read = |file| fs::read(dir.join(file)).unwrap();
+        let from_bytes = Config::from_bytes(
+            read("config.toml"),
+            read("tokenizer.json"),
+            read("model.onnx"),
+            ort().unwrap(),
+        )
+        .unwrap()
+        .with_pooler::<AveragePooler>()
+        .build()
+        .unwrap();
+        let from_dir = pipeline::<AveragePooler>(dir);
+
+        assert_eq!(from_bytes.embedding_size(), from_dir.embedding_size());
+        assert_approx_eq!(
+            f32,
+            from_bytes.run("This is a sequence.").unwrap(),
+            from_dir.run("This is a sequence.").unwrap(),
+        );
+    }
+
     #[test]
     fn test_e5_pipeline() {
         let pipeline = pipeline::<AveragePooler>(e5_mocked().unwrap());
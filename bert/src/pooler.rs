@@ -16,7 +16,9 @@ use std::ops::{Add, Mul};
 
 use derive_more::{Deref, From};
 use displaydoc::Display;
-use ndarray::{s, Array, Array1, ArrayView, Dimension, Ix, Ix1, Ix2, IxDyn};
+#[cfg(feature = "pipeline")]
+use ndarray::{s, ArrayView, IxDyn};
+use ndarray::{Array, Array1, Dimension, Ix, Ix1, Ix2};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 #[cfg(feature = "sqlx")]
 use sqlx::{
@@ -31,6 +33,7 @@ use sqlx::{
     Type,
 };
 use thiserror::Error;
+#[cfg(feature = "pipeline")]
 use tokenizers::Encoding;
 use xayn_test_utils::ApproxEqIter;
 
@@ -153,6 +156,8 @@ impl<'r> Decode<'r, Postgres> for Embedding1 {
 
 impl NormalizedEmbedding {
     /// The value is bounded in `[-1, 1]`.
+    ///
+    /// The product is computed with blas if the `blas` feature is enabled.
     pub fn dot_product(&self, other: &Self) -> f32 {
         self.dot(&other.0 .0).clamp(-1., 1.)
     }
@@ -201,6 +206,7 @@ pub type Embedding2 = Embedding<Ix2>;
 /// The embedding is just passed through.
 pub struct NonePooler;
 
+#[cfg(feature = "pipeline")]
 impl NonePooler {
     /// Passes through the embedding.
     pub(crate) fn pool(embedding: &ArrayView<'_, f32, IxDyn>) -> Embedding2 {
@@ -213,6 +219,7 @@ impl NonePooler {
 /// The embedding is pooled over its first token.
 pub struct FirstPooler;
 
+#[cfg(feature = "pipeline")]
 impl FirstPooler {
     /// Pools the embedding over its first token.
     pub(crate) fn pool(embedding: &ArrayView<'_, f32, IxDyn>) -> Embedding1 {
@@ -225,6 +232,7 @@ impl FirstPooler {
 /// The embedding is pooled over its averaged tokens.
 pub struct AveragePooler;
 
+#[cfg(feature = "pipeline")]
 impl AveragePooler {
     /// Pools the embedding over its averaged, active tokens.
     pub(crate) fn pool(embedding: &ArrayView<'_, f32, IxDyn>, encoding: &Encoding) -> Embedding1 {
@@ -248,8 +256,11 @@ impl AveragePooler {
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, f32::consts::SQRT_2};
+    #[cfg(feature = "pipeline")]
+    use std::collections::HashMap;
+    use std::f32::consts::SQRT_2;
 
+    #[cfg(feature = "pipeline")]
     use ndarray::arr3;
     use xayn_test_utils::assert_approx_eq;
 
@@ -273,6 +284,44 @@ mod tests {
         assert_approx_eq!(f32, embedding, [-0.5, 0.5, -0.5, 0.5]);
     }
 
+    #[test]
+    fn test_dot_product() {
+        for size in [1, 7, 128, 384, 1000] {
+            #[allow(clippy::cast_precision_loss)]
+            let embedding1 = Embedding1::from(
+                (0..size)
+                    .map(|i| ((i + 1) as f32 * 0.37).sin())
+                    .collect::<Vec<_>>(),
+            )
+            .normalize()
+            .unwrap();
+            #[allow(clippy::cast_precision_loss)]
+            let embedding2 = Embedding1::from(
+                (0..size)
+                    .map(|i| (i as f32 * 0.73).cos())
+                    .collect::<Vec<_>>(),
+            )
+            .normalize()
+            .unwrap();
+            let expected = embedding1
+                .iter()
+                .zip(embedding2.iter())
+                .map(|(value1, value2)| f64::from(*value1) * f64::from(*value2))
+                .sum::<f64>();
+
+            #[allow(clippy::cast_possible_truncation)]
+            let expected = expected as f32;
+            assert_approx_eq!(
+                f32,
+                embedding1.dot_product(&embedding2),
+                expected,
+                epsilon = 1e-5
+            );
+            assert_approx_eq!(f32, embedding1.dot_product(&embedding1), 1., epsilon = 1e-5);
+        }
+    }
+
+    #[cfg(feature = "pipeline")]
     #[test]
     fn test_none() {
         let embedding = arr3(&[[[1_f32, 2., 3.], [4., 5., 6.]]]).into_dyn();
@@ -280,6 +329,7 @@ mod tests {
         assert_approx_eq!(f32, embedding, [[1., 2., 3.], [4., 5., 6.]]);
     }
 
+    #[cfg(feature = "pipeline")]
     #[test]
     fn test_first() {
         let embedding = arr3(&[[[1., 2., 3.], [4., 5., 6.]]]).into_dyn();
@@ -287,6 +337,7 @@ mod tests {
         assert_approx_eq!(f32, embedding, [1., 2., 3.]);
     }
 
+    #[cfg(feature = "pipeline")]
     #[test]
     fn test_average() {
         let embedding = arr3(&[[[1., 2., 3.], [4., 5., 6.]]]).into_dyn();
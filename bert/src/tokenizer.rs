@@ -12,7 +12,12 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use anyhow::anyhow;
+use std::str;
+
+use figment::{
+    providers::{Format, Toml},
+    Figment,
+};
 use tokenizers::{
     tokenizer::Tokenizer as HfTokenizer,
     utils::{
@@ -23,24 +28,60 @@ use tokenizers::{
     Error,
 };
 
-use crate::config::Config;
+#[cfg(feature = "pipeline")]
+use crate::config::{Asset, Config};
+
+pub(crate) const MIN_TOKEN_SIZE: &str = "tokenizer.min_size";
+pub(crate) const MAX_TOKEN_SIZE: &str = "tokenizer.max_size";
 
 /// A pre-configured huggingface tokenizer.
-pub(crate) struct Tokenizer {
+///
+/// This is the tokenizer of a pipeline, it can be used on its own to run the model elsewhere, e.g.
+/// with `onnxruntime-web` in the browser.
+pub struct Tokenizer {
     tokenizer: HfTokenizer,
     add_special_tokens: bool,
+    buckets: Vec<usize>,
 }
 
 impl Tokenizer {
+    #[cfg(feature = "pipeline")]
     pub(crate) fn new<P>(config: &Config<P>) -> Result<Self, Error> {
-        let tokenizer = config.dir.join("tokenizer.json");
-        if !tokenizer.exists() {
-            return Err(
-                anyhow!("embedder tokenizer '{}' doesn't exist", tokenizer.display()).into(),
-            );
-        }
-        let mut tokenizer = HfTokenizer::from_file(tokenizer)?;
-        let padding_token = config.extract::<String>("tokenizer.padding")?;
+        let tokenizer = match config.tokenizer()? {
+            Asset::File(tokenizer) => HfTokenizer::from_file(tokenizer)?,
+            Asset::Bytes(tokenizer) => HfTokenizer::from_bytes(tokenizer)?,
+        };
+
+        Self::configure(
+            tokenizer,
+            &config.toml,
+            config.token_size,
+            config.token_buckets.clone(),
+        )
+    }
+
+    /// Creates a tokenizer from the contents of the `config.toml` and `tokenizer.json`.
+    ///
+    /// The tokenizer is the same as the one of a pipeline with the default token size and without
+    /// token size buckets.
+    pub fn from_bytes(
+        config: impl AsRef<[u8]>,
+        tokenizer: impl AsRef<[u8]>,
+    ) -> Result<Self, Error> {
+        let config = Figment::from(Toml::string(str::from_utf8(config.as_ref())?));
+        let token_size = default_token_size(&config)?;
+        let tokenizer = HfTokenizer::from_bytes(tokenizer)?;
+
+        Self::configure(tokenizer, &config, token_size, Vec::new())
+    }
+
+    fn configure(
+        mut tokenizer: HfTokenizer,
+        config: &Figment,
+        token_size: usize,
+        buckets: Vec<usize>,
+    ) -> Result<Self, Error> {
+        let padding_token = config.extract_inner::<String>("tokenizer.padding")?;
         let padding = PaddingParams {
             strategy: PaddingStrategy::BatchLongest,
             direction: PaddingDirection::Right,
@@ -53,28 +94,56 @@ impl Tokenizer {
         };
         let truncation = TruncationParams {
             direction: TruncationDirection::Right,
-            max_length: config.token_size,
+            max_length: token_size,
             strategy: TruncationStrategy::LongestFirst,
             stride: 0,
         };
         tokenizer.with_padding(Some(padding));
         tokenizer.with_truncation(Some(truncation));
-        let add_special_tokens = config.extract::<bool>("tokenizer.add_special_tokens")?;
+        let add_special_tokens = config.extract_inner::<bool>("tokenizer.add_special_tokens")?;
 
         Ok(Tokenizer {
             tokenizer,
             add_special_tokens,
+            buckets,
         })
     }
 
-    pub(crate) fn encode(&self, sequence: impl AsRef<str>) -> Result<Encoding, Error> {
-        self.tokenizer
-            .encode(sequence.as_ref(), self.add_special_tokens)
+    /// Encodes the sequence into the token ids, attention mask and type ids for the model.
+    pub fn encode(&self, sequence: impl AsRef<str>) -> Result<Encoding, Error> {
+        let mut encoding = self
+            .tokenizer
+            .encode(sequence.as_ref(), self.add_special_tokens)?;
+        if let (Some(&bucket), Some(padding)) = (
+            self.buckets
+                .iter()
+                .find(|&&bucket| bucket >= encoding.len()),
+            self.tokenizer.get_padding(),
+        ) {
+            encoding.pad(
+                bucket,
+                padding.pad_id,
+                padding.pad_type_id,
+                &padding.pad_token,
+                padding.direction,
+            );
+        }
+
+        Ok(encoding)
     }
 }
 
+/// The default token size is the midpoint of the token size range.
+pub(crate) fn default_token_size(config: &Figment) -> Result<usize, figment::Error> {
+    Ok((config.extract_inner::<usize>(MIN_TOKEN_SIZE)?
+        + config.extract_inner::<usize>(MAX_TOKEN_SIZE)?)
+        / 2)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::fs;
+
     use xayn_test_utils::asset::{e5_mocked, ort, smbert_mocked};
 
     use super::*;
@@ -95,6 +164,24 @@ mod tests {
         assert!(encoding.get_type_ids().iter().all(|v| *v == 0));
     }
 
+    #[test]
+    fn test_smbert_from_bytes() {
+        let dir = smbert_mocked().unwrap();
+        let tokenizer = Tokenizer::from_bytes(
+            fs::read(dir.join("config.toml")).unwrap(),
+            fs::read(dir.join("tokenizer.json")).unwrap(),
+        )
+        .unwrap();
+        let encoding = tokenizer
+            .encode("These are normal, common EMBEDDINGS.")
+            .unwrap();
+        assert_eq!(
+            encoding.get_ids(),
+            [2, 4538, 2128, 8561, 1, 6541, 69469, 2762, 5, 3],
+        );
+        assert!(encoding.get_attention_mask().iter().all(|v| *v == 1));
+    }
+
     #[test]
     fn test_smbert_truncation() {
         let token_size = 5;
@@ -112,6 +199,38 @@ mod tests {
         assert!(encoding.get_type_ids().iter().all(|v| *v == 0));
     }
 
+    #[test]
+    fn test_smbert_buckets() {
+        let config = Config::new(smbert_mocked().unwrap(), ort().unwrap())
+            .unwrap()
+            .with_token_size(16)
+            .unwrap()
+            .with_token_buckets([5, 16])
+            .unwrap();
+        let tokenizer = Tokenizer::new(&config).unwrap();
+
+        let encoding = tokenizer.encode("These are normal").unwrap();
+        assert_eq!(encoding.get_ids(), [2, 4538, 2128, 8561, 3]);
+        assert!(encoding.get_attention_mask().iter().all(|v| *v == 1));
+
+        let encoding = tokenizer
+            .encode("These are normal, common EMBEDDINGS.")
+            .unwrap();
+        assert_eq!(encoding.get_ids().len(), 16);
+        assert_eq!(
+            encoding.get_ids()[..10],
+            [2, 4538, 2128, 8561, 1, 6541, 69469, 2762, 5, 3],
+        );
+        assert_eq!(encoding.get_attention_mask()[..10], [1; 10]);
+        assert_eq!(encoding.get_attention_mask()[10..], [0; 6]);
+
+        let encoding = tokenizer
+            .encode("These are normal, common EMBEDDINGS. ".repeat(3))
+            .unwrap();
+        assert_eq!(encoding.get_ids().len(), 16);
+        assert!(encoding.get_attention_mask().iter().all(|v| *v == 1));
+    }
+
     #[test]
     fn test_smbert_troublemakers() {
         let config = Config::new(smbert_mocked().unwrap(), ort().unwrap()).unwrap();
@@ -0,0 +1,251 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! JS bindings of the center of interest system.
+//!
+//! The sequences are tokenized within the bindings, but the embeddings must be computed outside of
+//! them from the encodings, e.g. with `onnxruntime-web`, because the onnx runtime of the pipeline of
+//! `xayn-ai-bert` doesn't support wasm.
+
+#![forbid(unsafe_op_in_unsafe_fn)]
+#![deny(
+    clippy::pedantic,
+    noop_method_call,
+    rust_2018_idioms,
+    unsafe_code,
+    unused_qualifications
+)]
+#![warn(unreachable_pub, rustdoc::missing_crate_level_docs)]
+#![allow(
+    clippy::items_after_statements,
+    clippy::missing_errors_doc,
+    clippy::module_name_repetitions,
+    clippy::must_use_candidate
+)]
+
+use chrono::{DateTime, TimeZone, Utc};
+use wasm_bindgen::prelude::{wasm_bindgen, JsError};
+use xayn_ai_bert::NormalizedEmbedding;
+use xayn_ai_coi::{Coi, CoiConfig, CoiSystem, Document};
+
+/// The tokenizer of an embedding model.
+#[wasm_bindgen]
+pub struct Tokenizer(xayn_ai_bert::Tokenizer);
+
+#[wasm_bindgen]
+impl Tokenizer {
+    /// Creates the tokenizer from the contents of the `config.toml` and `tokenizer.json` of the
+    /// model.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &[u8], tokenizer: &[u8]) -> Result<Tokenizer, JsError> {
+        xayn_ai_bert::Tokenizer::from_bytes(config, tokenizer)
+            .map(Self)
+            .map_err(|error| JsError::new(&error.to_string()))
+    }
+
+    /// Encodes the sequence into the inputs of the model.
+    pub fn encode(&self, sequence: &str) -> Result<Encoding, JsError> {
+        let encoding = self
+            .0
+            .encode(sequence)
+            .map_err(|error| JsError::new(&error.to_string()))?;
+
+        Ok(Encoding {
+            ids: encoding.get_ids().to_vec(),
+            attention_mask: encoding.get_attention_mask().to_vec(),
+            type_ids: encoding.get_type_ids().to_vec(),
+        })
+    }
+}
+
+/// The inputs of the model for a sequence.
+#[wasm_bindgen]
+pub struct Encoding {
+    ids: Vec<u32>,
+    attention_mask: Vec<u32>,
+    type_ids: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl Encoding {
+    /// The token ids.
+    #[wasm_bindgen(getter)]
+    pub fn ids(&self) -> Vec<u32> {
+        self.ids.clone()
+    }
+
+    /// The attention mask.
+    #[wasm_bindgen(getter, js_name = attentionMask)]
+    pub fn attention_mask(&self) -> Vec<u32> {
+        self.attention_mask.clone()
+    }
+
+    /// The token type ids.
+    #[wasm_bindgen(getter, js_name = typeIds)]
+    pub fn type_ids(&self) -> Vec<u32> {
+        self.type_ids.clone()
+    }
+}
+
+/// The interests of a user.
+#[wasm_bindgen]
+pub struct Interests {
+    system: CoiSystem,
+    cois: Vec<Coi>,
+}
+
+#[wasm_bindgen]
+impl Interests {
+    /// Creates empty interests with the default configuration.
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            system: CoiConfig::default().build(),
+            cois: Vec::new(),
+        }
+    }
+
+    /// Restores the interests from [`Self::to_json()`].
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<Interests, JsError> {
+        Ok(Self {
+            system: CoiConfig::default().build(),
+            cois: serde_json::from_str(json)?,
+        })
+    }
+
+    /// Serializes the interests to persist them.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(&self.cois)?)
+    }
+
+    /// Logs a positive reaction of the user to a document.
+    ///
+    /// The timestamp is in milliseconds since the epoch, like `Date.now()`.
+    #[wasm_bindgen(js_name = logPositiveReaction)]
+    pub fn log_positive_reaction(
+        &mut self,
+        embedding: Vec<f32>,
+        timestamp: f64,
+    ) -> Result<(), JsError> {
+        let embedding = NormalizedEmbedding::try_from(embedding)?;
+        self.system
+            .log_user_reaction(&mut self.cois, &embedding, to_time(timestamp)?);
+
+        Ok(())
+    }
+
+    /// Scores the documents wrt the interests.
+    ///
+    /// The embeddings of the documents are concatenated and must all be of the same size. Returns
+    /// `undefined` if there are not enough interests yet.
+    pub fn score(
+        &self,
+        embeddings: &[f32],
+        embedding_size: usize,
+        timestamp: f64,
+    ) -> Result<Option<Vec<f32>>, JsError> {
+        if embedding_size == 0 || embeddings.len() % embedding_size != 0 {
+            return Err(JsError::new("embeddings don't match the embedding size"));
+        }
+        if self.cois.len() < self.system.config().min_cois() {
+            return Ok(None);
+        }
+
+        let documents = embeddings
+            .chunks(embedding_size)
+            .enumerate()
+            .map(|(id, embedding)| {
+                Ok(ScoredDocument {
+                    id,
+                    embedding: embedding.to_vec().try_into()?,
+                })
+            })
+            .collect::<Result<Vec<_>, JsError>>()?;
+
+        Ok(self
+            .system
+            .score(&documents, &self.cois, to_time(timestamp)?))
+    }
+}
+
+struct ScoredDocument {
+    id: usize,
+    embedding: NormalizedEmbedding,
+}
+
+impl Document for ScoredDocument {
+    type Id = usize;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn embedding(&self) -> &NormalizedEmbedding {
+        &self.embedding
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)] // js timestamps are integral milliseconds
+fn to_time(timestamp: f64) -> Result<DateTime<Utc>, JsError> {
+    Utc.timestamp_millis_opt(timestamp as i64)
+        .single()
+        .ok_or_else(|| JsError::new("invalid timestamp"))
+}
+
+#[cfg(test)]
+mod tests {
+    // Hint: `JsError` doesn't implement `Debug`, hence the results are unwrapped as options
+    use std::fs;
+
+    use xayn_test_utils::asset::smbert_mocked;
+
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let dir = smbert_mocked().unwrap();
+        let tokenizer = Tokenizer::new(
+            &fs::read(dir.join("config.toml")).unwrap(),
+            &fs::read(dir.join("tokenizer.json")).unwrap(),
+        )
+        .ok()
+        .unwrap();
+        let encoding = tokenizer.encode("These are normal").ok().unwrap();
+        assert_eq!(encoding.ids, [2, 4538, 2128, 8561, 3]);
+        assert_eq!(encoding.attention_mask, [1; 5]);
+        assert_eq!(encoding.type_ids, [0; 5]);
+    }
+
+    #[test]
+    fn test_score() {
+        let mut interests = Interests::new();
+        assert!(matches!(interests.score(&[1., 0.], 2, 0.), Ok(None)));
+
+        assert!(interests.log_positive_reaction(vec![1., 0.], 0.).is_ok());
+        let scores = interests
+            .score(&[1., 0., 0., 1.], 2, 0.)
+            .ok()
+            .flatten()
+            .unwrap();
+        assert_eq!(scores.len(), 2);
+        assert!(scores[0] > scores[1]);
+
+        let json = interests.to_json().ok().unwrap();
+        let interests = Interests::from_json(&json).ok().unwrap();
+        assert_eq!(interests.cois.len(), 1);
+    }
+}
@@ -19,7 +19,15 @@ use criterion::{criterion_group, BatchSize, Criterion};
 use itertools::Itertools;
 use rand::Rng;
 use rand_distr::Uniform;
-use xayn_ai_coi::{compute_coi_decay_factor, compute_coi_relevances, Coi, CoiId};
+use xayn_ai_bert::NormalizedEmbedding;
+use xayn_ai_coi::{
+    compute_coi_decay_factor,
+    compute_coi_relevances,
+    Coi,
+    CoiConfig,
+    CoiId,
+    Document,
+};
 
 fn create_cois(n: usize, embedding_size: usize) -> Vec<Coi> {
     let range = Uniform::new(-1., 1.);
@@ -38,6 +46,34 @@ fn create_cois(n: usize, embedding_size: usize) -> Vec<Coi> {
         .collect()
 }
 
+struct BenchDocument {
+    id: usize,
+    embedding: NormalizedEmbedding,
+}
+
+impl Document for BenchDocument {
+    type Id = usize;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn embedding(&self) -> &NormalizedEmbedding {
+        &self.embedding
+    }
+}
+
+fn create_documents(n: usize, embedding_size: usize) -> Vec<BenchDocument> {
+    create_cois(n, embedding_size)
+        .into_iter()
+        .enumerate()
+        .map(|(id, coi)| BenchDocument {
+            id,
+            embedding: coi.point,
+        })
+        .collect()
+}
+
 fn bench_compute_coi_decay_factor(c: &mut Criterion) {
     let horizon = Duration::new(60 * 60 * 24 * 30, 0); // 30 days
     let now = Utc::now();
@@ -84,8 +120,35 @@ fn bench_compute_coi_relevance(c: &mut Criterion) {
     });
 }
 
+fn bench_score(c: &mut Criterion) {
+    let count = [100, 500, 1000];
+    let cois_count = [10, 50];
+    let embedding_size = 128;
+    let system = CoiConfig::default().build();
+    let now = Utc::now();
+
+    let count_max: usize = *count.iter().max().unwrap();
+    let documents = create_documents(count_max, embedding_size);
+    let cois_count_max: usize = *cois_count.iter().max().unwrap();
+    let cois = create_cois(cois_count_max, embedding_size);
+
+    for &n in &count {
+        for &m in &cois_count {
+            let documents = &documents[..n];
+            let cois = &cois[..m];
+
+            c.bench_function(&format!("score_n{n}_c{m}_s{embedding_size}"), |b| {
+                b.iter(|| {
+                    black_box(system.score(black_box(documents), black_box(cois), black_box(now)))
+                })
+            });
+        }
+    }
+}
+
 criterion_group!(b_compute_coi_decay_factor, bench_compute_coi_decay_factor);
 criterion_group!(b_compute_coi_relevance, bench_compute_coi_relevance);
+criterion_group!(b_score, bench_score);
 
 fn main() {
     criterion::Criterion::default()
@@ -94,4 +157,5 @@ fn main() {
 
     b_compute_coi_decay_factor();
     b_compute_coi_relevance();
+    b_score();
 }
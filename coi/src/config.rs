@@ -34,6 +34,8 @@ pub struct Config {
     min_cois: usize,
     #[serde(with = "serde_duration_as_days")]
     horizon: Duration,
+    #[serde(with = "serde_duration_as_days")]
+    impression_aging: Duration,
 }
 
 // the f32 fields are never NaN by construction
@@ -46,6 +48,7 @@ impl Default for Config {
             threshold: 0.67,
             min_cois: 1,
             horizon: Duration::from_secs(30 * SECONDS_PER_DAY),
+            impression_aging: Duration::from_secs(SECONDS_PER_DAY),
         }
     }
 }
@@ -135,6 +138,17 @@ impl Config {
         self
     }
 
+    /// The time by which a coi is aged for an impression without interaction.
+    pub fn impression_aging(&self) -> Duration {
+        self.impression_aging
+    }
+
+    /// Sets the impression aging.
+    pub fn with_impression_aging(mut self, impression_aging: Duration) -> Self {
+        self.impression_aging = impression_aging;
+        self
+    }
+
     /// Creates a coi system.
     pub fn build(self) -> System {
         System { config: self }
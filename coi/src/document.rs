@@ -12,8 +12,13 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::time::Duration;
+
 use xayn_ai_bert::NormalizedEmbedding;
 
+/// The average reading speed in words per minute.
+const WORDS_PER_MINUTE: f32 = 200.;
+
 /// Common document properties.
 pub trait Document {
     type Id: std::fmt::Debug + Eq + std::hash::Hash + Clone;
@@ -25,6 +30,12 @@ pub trait Document {
     fn embedding(&self) -> &NormalizedEmbedding;
 }
 
+/// Estimates the reading time of a document from the number of words of its text.
+#[allow(clippy::cast_precision_loss)]
+pub fn estimate_reading_time(text: &str) -> Duration {
+    Duration::from_secs_f32(text.split_whitespace().count() as f32 / WORDS_PER_MINUTE * 60.)
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use derive_more::Display;
@@ -69,4 +80,17 @@ pub(crate) mod tests {
             &self.embedding
         }
     }
+
+    #[test]
+    fn test_estimate_reading_time() {
+        assert_eq!(estimate_reading_time(""), Duration::ZERO);
+        assert_eq!(
+            estimate_reading_time(&" a  b\n".repeat(50)),
+            Duration::from_secs(30),
+        );
+        assert_eq!(
+            estimate_reading_time(&"word ".repeat(400)),
+            Duration::from_secs(120),
+        );
+    }
 }
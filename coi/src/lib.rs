@@ -40,7 +40,7 @@ mod utils;
 
 pub use crate::{
     config::{Config as CoiConfig, Error as CoiConfigError},
-    document::Document,
+    document::{estimate_reading_time, Document},
     point::{Coi, Id as CoiId},
     stats::{
         compute_coi_decay_factor,
@@ -79,15 +79,6 @@ pub(super) fn find_closest_coi_index(
     similarities.first().copied()
 }
 
-/// Finds the most similar [`Coi`] for the given embedding.
-pub(super) fn find_closest_coi_mut<'a>(
-    cois: &'a mut [Coi],
-    embedding: &NormalizedEmbedding,
-) -> Option<(&'a mut Coi, f32)> {
-    find_closest_coi_index(cois, embedding)
-        .map(move |(index, similarity)| (&mut cois[index], similarity))
-}
-
 #[cfg(test)]
 pub(crate) mod tests {
     use xayn_test_utils::{assert_approx_eq, uuid::mock_uuid};
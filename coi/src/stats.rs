@@ -44,6 +44,15 @@ impl Stats {
         self.view_count += 1;
         self.last_view = time;
     }
+
+    pub(super) fn log_impression(&mut self, aging: Duration) {
+        if let Some(last_view) = chrono::Duration::from_std(aging)
+            .ok()
+            .and_then(|aging| self.last_view.checked_sub_signed(aging))
+        {
+            self.last_view = last_view;
+        }
+    }
 }
 
 impl Coi {
@@ -56,6 +65,11 @@ impl Coi {
         self.stats.log_reaction(time);
         self
     }
+
+    pub(super) fn log_impression(&mut self, aging: Duration) -> &mut Self {
+        self.stats.log_impression(aging);
+        self
+    }
 }
 
 /// Computes the relevances of the [`Coi`]s.
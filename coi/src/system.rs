@@ -12,8 +12,6 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::time::Duration;
-
 use chrono::{DateTime, Utc};
 use xayn_ai_bert::NormalizedEmbedding;
 
@@ -21,7 +19,7 @@ use crate::{
     compute_coi_relevances,
     config::Config,
     document::Document,
-    point::{find_closest_coi_index, find_closest_coi_mut, Coi, Id},
+    point::{find_closest_coi_index, Coi, Id},
     stats::compute_coi_decay_factor,
 };
 
@@ -35,17 +33,6 @@ impl System {
         &self.config
     }
 
-    /// Updates the view time of the [`Coi`] closest to the embedding.
-    pub fn log_document_view_time(
-        cois: &mut [Coi],
-        embedding: &NormalizedEmbedding,
-        viewed: Duration,
-    ) {
-        if let Some((coi, _)) = find_closest_coi_mut(cois, embedding) {
-            coi.log_time(viewed);
-        }
-    }
-
     /// Updates the [`Coi`] closest to the embedding or creates a new one if it's too far away.
     pub fn log_user_reaction<'a>(
         &self,
@@ -70,6 +57,23 @@ impl System {
         &cois[cois.len() - 1]
     }
 
+    /// Ages the [`Coi`] closest to the embedding if it's similar enough.
+    ///
+    /// An impression of a document without an interaction is a mild negative signal, such that
+    /// repeatedly ignored interests gradually fade.
+    pub fn log_user_impression<'a>(
+        &self,
+        cois: &'a mut [Coi],
+        embedding: &NormalizedEmbedding,
+    ) -> Option<&'a Coi> {
+        let (index, similarity) = find_closest_coi_index(cois, embedding)?;
+        if similarity >= self.config.threshold() {
+            Some(cois[index].log_impression(self.config.impression_aging()))
+        } else {
+            None
+        }
+    }
+
     /// Computes the scores for all [`Document`]s wrt the [`Coi`]s.
     ///
     /// Each score ranges in the interval `[0., 1.]` if a [`Coi`] exists. The [coi weighting]
@@ -141,22 +145,24 @@ mod tests {
     }
 
     #[test]
-    fn test_log_document_view_time() {
-        let mut cois = create_cois([[1., 2., 3.]], Utc::now());
-
-        System::log_document_view_time(
-            &mut cois,
-            &[1., 2., 4.].try_into().unwrap(),
-            Duration::from_secs(10),
-        );
-        assert_eq!(Duration::from_secs(10), cois[0].stats.view_time);
+    fn test_log_user_impression() {
+        let now = Utc::now();
+        let mut cois = create_cois([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]], now);
+        let system = Config::default().build();
 
-        System::log_document_view_time(
-            &mut cois,
-            &[1., 2., 4.].try_into().unwrap(),
-            Duration::from_secs(10),
-        );
-        assert_eq!(Duration::from_secs(20), cois[0].stats.view_time);
+        let id = system
+            .log_user_impression(&mut cois, &[1., 0.1, 0.].try_into().unwrap())
+            .unwrap()
+            .id;
+        assert_eq!(id, cois[0].id);
+        assert_eq!(cois[0].stats.last_view, now - chrono::Duration::days(1));
+        assert_eq!(cois[1].stats.last_view, now);
+        assert_eq!(cois[2].stats.last_view, now);
+
+        assert!(system
+            .log_user_impression(&mut cois, &[-1., 0., 0.].try_into().unwrap())
+            .is_none());
+        assert_eq!(cois[0].stats.last_view, now - chrono::Duration::days(1));
     }
 
     #[test]
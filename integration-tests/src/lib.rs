@@ -27,6 +27,7 @@ use std::{
     fs::{create_dir_all, remove_dir_all, OpenOptions},
     future::Future,
     io::{self, Write},
+    net::SocketAddr,
     path::{Path, PathBuf},
     process::{abort, Command, Output, Stdio},
     sync::{Arc, Once},
@@ -499,6 +500,48 @@ pub fn test_app<A, F>(
 ) where
     F: Future<Output = Result<(), Error>>,
     A: Application + 'static,
+{
+    test_app_with_handle::<A, _>(configure, |client, handle, services| {
+        test(client, Arc::new(handle.url()), services)
+    });
+}
+
+/// Like [`test_app`] but with the grpc interface enabled and its address passed to the test.
+///
+/// Requests to the grpc interface have to set the `x-xayn-tenant-id` metadata to the `test_id`.
+pub fn test_grpc_app<A, F>(
+    configure: Option<Table>,
+    test: impl FnOnce(Arc<Client>, Arc<Url>, SocketAddr, Services) -> F,
+) where
+    F: Future<Output = Result<(), Error>>,
+    A: Application + 'static,
+{
+    let mut configure = configure.unwrap_or_default();
+    extend_config(
+        &mut configure,
+        toml! {
+            [net.grpc]
+            enabled = true
+            bind_to = "127.0.0.1:0"
+        },
+    );
+
+    test_app_with_handle::<A, _>(Some(configure), |client, handle, services| {
+        test(
+            client,
+            Arc::new(handle.url()),
+            handle.grpc_address().unwrap(),
+            services,
+        )
+    });
+}
+
+fn test_app_with_handle<A, F>(
+    configure: Option<Table>,
+    test: impl FnOnce(Arc<Client>, &AppHandle, Services) -> F,
+) where
+    F: Future<Output = Result<(), Error>>,
+    A: Application + 'static,
 {
     run_async_test(|test_id| async move {
         let (configure, enable_legacy_tenant) =
@@ -508,13 +551,9 @@ pub fn test_app<A, F>(
 
         let handle = start_test_application::<A>(&services, configure).await;
 
-        test(
-            build_client(&services),
-            Arc::new(handle.url()),
-            services.clone(),
-        )
-        .instrument(info_span!("call_test"))
-        .await?;
+        test(build_client(&services), &handle, services.clone())
+            .instrument(info_span!("call_test"))
+            .await?;
 
         handle
             .stop_and_wait()
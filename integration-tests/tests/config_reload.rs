@@ -0,0 +1,39 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use reqwest::StatusCode;
+use xayn_integration_tests::{send_assert, test_app, UNCHANGED_CONFIG};
+use xayn_web_api::WebApi;
+
+#[test]
+fn test_reload_unchanged_config() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        send_assert(
+            &client,
+            client.post(url.join("/_ops/config/reload")?).build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        send_assert(
+            &client,
+            client.get(url.join("/users/u1/interests")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+
+        Ok(())
+    });
+}
@@ -17,6 +17,7 @@ use std::collections::HashMap;
 use reqwest::StatusCode;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use toml::toml;
 use xayn_integration_tests::{send_assert, send_assert_json, test_app, UNCHANGED_CONFIG};
 use xayn_web_api::WebApi;
 
@@ -27,9 +28,11 @@ struct DocumentPropertiesResponse {
 
 #[derive(Debug, Deserialize, PartialEq)]
 #[serde(tag = "kind")]
+#[allow(clippy::enum_variant_names)]
 enum Error {
     DocumentNotFound,
     DocumentPropertyNotFound,
+    DocumentRejected,
 }
 
 fn document_properties(is_candidate: bool) {
@@ -262,3 +265,97 @@ fn test_document_property_candidate() {
 fn test_document_property_noncandidate() {
     document_property(false);
 }
+
+#[test]
+fn test_document_properties_moderation() {
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [[ingestion.moderation]]
+            type = "language_allow_list"
+            allowed = ["en"]
+            reject_missing = true
+
+            [[ingestion.moderation]]
+            type = "banned_domains"
+            domains = ["spam.example"]
+        }),
+        |client, url, _| async move {
+            send_assert(
+                &client,
+                client
+                    .post(url.join("/documents")?)
+                    .json(&json!({
+                        "documents": [
+                            { "id": "d1", "snippet": "snippet one", "properties": { "language": "en" } }
+                        ]
+                    }))
+                    .build()?,
+                StatusCode::CREATED,
+                false,
+            )
+            .await;
+
+            for request in [
+                client
+                    .put(url.join("/documents/d1/properties")?)
+                    .json(&json!({ "properties": { "language": "de" } }))
+                    .build()?,
+                client
+                    .put(url.join("/documents/d1/properties")?)
+                    .json(&json!({
+                        "properties": { "language": "en", "link": "https://www.spam.example/a" }
+                    }))
+                    .build()?,
+                client
+                    .delete(url.join("/documents/d1/properties")?)
+                    .build()?,
+                client
+                    .put(url.join("/documents/d1/properties/language")?)
+                    .json(&json!({ "property": "de" }))
+                    .build()?,
+                client
+                    .put(url.join("/documents/d1/properties/link")?)
+                    .json(&json!({ "property": "https://spam.example" }))
+                    .build()?,
+                client
+                    .delete(url.join("/documents/d1/properties/language")?)
+                    .build()?,
+            ] {
+                let error =
+                    send_assert_json::<Error>(&client, request, StatusCode::BAD_REQUEST, false)
+                        .await;
+                assert_eq!(error, Error::DocumentRejected);
+            }
+            let DocumentPropertiesResponse { properties } = send_assert_json(
+                &client,
+                client.get(url.join("/documents/d1/properties")?).build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+            assert_eq!(properties, [("language".to_string(), json!("en"))].into());
+
+            send_assert(
+                &client,
+                client
+                    .put(url.join("/documents/d1/properties/link")?)
+                    .json(&json!({ "property": "https://news.example" }))
+                    .build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+            send_assert(
+                &client,
+                client
+                    .delete(url.join("/documents/d1/properties/link")?)
+                    .build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+
+            Ok(())
+        },
+    );
+}
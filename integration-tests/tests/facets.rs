@@ -0,0 +1,110 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use xayn_integration_tests::{send_assert, send_assert_json, test_app, UNCHANGED_CONFIG};
+use xayn_web_api::WebApi;
+
+#[test]
+fn test_facets() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        send_assert(
+            &client,
+            client
+                .post(url.join("/documents/_indexed_properties")?)
+                .json(&json!({
+                    "properties": {
+                        "category": { "type": "keyword" },
+                        "labels": { "type": "keyword[]" }
+                    }
+                }))
+                .build()?,
+            StatusCode::ACCEPTED,
+            false,
+        )
+        .await;
+        send_assert(
+            &client,
+            client
+                .post(url.join("/documents")?)
+                .json(&json!({
+                    "documents": [
+                        { "id": "d1", "snippet": "a", "properties": { "category": "sports", "labels": ["x", "y"] } },
+                        { "id": "d2", "snippet": "b", "properties": { "category": "sports", "labels": ["x"] } },
+                        { "id": "d3", "snippet": "c", "properties": { "category": "politics" } },
+                        { "id": "d4", "snippet": "d" }
+                    ]
+                }))
+                .build()?,
+            StatusCode::CREATED,
+            false,
+        )
+        .await;
+
+        let facets = send_assert_json::<Value>(
+            &client,
+            client
+                .get(url.join("/documents/_facets?by=category")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(
+            facets,
+            json!({
+                "facets": [
+                    { "value": "sports", "count": 2 },
+                    { "value": "politics", "count": 1 }
+                ]
+            }),
+        );
+
+        let facets = send_assert_json::<Value>(
+            &client,
+            client
+                .get(url.join("/documents/_facets?by=labels&count=1")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(
+            facets,
+            json!({ "facets": [ { "value": "x", "count": 2 } ] }),
+        );
+
+        send_assert(
+            &client,
+            client
+                .get(url.join("/documents/_facets?by=publication_date")?)
+                .build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+        send_assert(
+            &client,
+            client
+                .get(url.join("/documents/_facets?by=unindexed")?)
+                .build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+
+        Ok(())
+    });
+}
@@ -0,0 +1,197 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::net::SocketAddr;
+
+use futures_util::stream;
+use pbjson_types::{value::Kind, Struct, Value};
+use reqwest::StatusCode;
+use serde_json::json;
+use tonic::{transport::Channel, Code, Request};
+use xayn_integration_tests::{send_assert, test_grpc_app, Services, UNCHANGED_CONFIG};
+use xayn_web_api::{
+    grpc::proto::{
+        document::Data,
+        ingestion_client::IngestionClient,
+        personalization_client::PersonalizationClient,
+        Document,
+        RecommendationRequest,
+        UpsertDocumentsRequest,
+        UserRecommendationsRequest,
+    },
+    WebApi,
+};
+
+async fn channel(address: SocketAddr) -> Result<Channel, anyhow::Error> {
+    Ok(Channel::from_shared(format!("http://{address}"))?
+        .connect()
+        .await?)
+}
+
+fn request<T>(services: &Services, message: T) -> Request<T> {
+    let mut request = Request::new(message);
+    request.metadata_mut().insert(
+        "x-xayn-tenant-id",
+        services.test_id.as_str().parse().unwrap(),
+    );
+    request
+}
+
+fn documents(ids: &[&str]) -> UpsertDocumentsRequest {
+    UpsertDocumentsRequest {
+        documents: ids
+            .iter()
+            .map(|id| Document {
+                id: (*id).to_string(),
+                data: Some(Data::Snippet(format!("snippet of document {id}"))),
+                ..Document::default()
+            })
+            .collect(),
+    }
+}
+
+fn recommendations(
+    user_id: &str,
+    request: Option<RecommendationRequest>,
+) -> UserRecommendationsRequest {
+    UserRecommendationsRequest {
+        user_id: user_id.to_string(),
+        request,
+    }
+}
+
+#[test]
+fn test_grpc_ingestion() {
+    test_grpc_app::<WebApi, _>(
+        UNCHANGED_CONFIG,
+        |client, url, address, services| async move {
+            let mut ingestion = IngestionClient::new(channel(address).await?);
+            ingestion
+                .upsert_documents(request(&services, documents(&["d1", "d2"])))
+                .await?;
+            ingestion
+                .upsert_documents_stream(request(
+                    &services,
+                    stream::iter([documents(&["d3"]), documents(&["d4", "d5"])]),
+                ))
+                .await?;
+
+            for id in ["d1", "d2", "d3", "d4", "d5"] {
+                send_assert(
+                    &client,
+                    client
+                        .get(url.join(&format!("/documents/{id}/properties"))?)
+                        .build()?,
+                    StatusCode::OK,
+                    false,
+                )
+                .await;
+            }
+
+            let status = ingestion
+                .upsert_documents(request(&services, documents(&["invalid id"])))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+
+            let status = ingestion
+                .upsert_documents(documents(&["d6"]))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn test_grpc_personalization() {
+    test_grpc_app::<WebApi, _>(
+        UNCHANGED_CONFIG,
+        |client, url, address, services| async move {
+            let channel = channel(address).await?;
+            IngestionClient::new(channel.clone())
+                .upsert_documents(request(&services, documents(&["d1", "d2", "d3"])))
+                .await?;
+            let mut personalization = PersonalizationClient::new(channel);
+
+            let status = personalization
+                .user_recommendations(request(&services, recommendations("u1", None)))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::FailedPrecondition);
+
+            send_assert(
+                &client,
+                client
+                    .patch(url.join("/users/u1/interactions")?)
+                    .json(&json!({ "documents": [ { "id": "d1" } ] }))
+                    .build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+
+            let documents = personalization
+                .user_recommendations(request(&services, recommendations("u1", None)))
+                .await?
+                .into_inner()
+                .documents;
+            assert!(!documents.is_empty());
+            assert!(documents.iter().all(|document| document.id != "d1"));
+
+            let documents = personalization
+                .user_recommendations(request(
+                    &services,
+                    recommendations(
+                        "u1",
+                        Some(RecommendationRequest {
+                            count: Some(1),
+                            include_snippet: true,
+                            ..RecommendationRequest::default()
+                        }),
+                    ),
+                ))
+                .await?
+                .into_inner()
+                .documents;
+            assert_eq!(documents.len(), 1);
+            assert!(documents[0].snippet.is_some());
+
+            let status = personalization
+                .user_recommendations(request(
+                    &services,
+                    recommendations(
+                        "u1",
+                        Some(RecommendationRequest {
+                            filter: Some(Struct {
+                                fields: [(
+                                    "unknown".to_string(),
+                                    Value {
+                                        kind: Some(Kind::BoolValue(true)),
+                                    },
+                                )]
+                                .into(),
+                            }),
+                            ..RecommendationRequest::default()
+                        }),
+                    ),
+                ))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+            Ok(())
+        },
+    );
+}
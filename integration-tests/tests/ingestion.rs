@@ -15,7 +15,7 @@
 use std::collections::{HashMap, HashSet};
 
 use base64::{engine::general_purpose, Engine as _};
-use reqwest::{Client, StatusCode, Url};
+use reqwest::{Client, Method, StatusCode, Url};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use toml::toml;
@@ -24,6 +24,7 @@ use xayn_integration_tests::{
     send_assert_json,
     test_app,
     with_text_extractor_options,
+    Services,
     UNCHANGED_CONFIG,
 };
 use xayn_web_api::WebApi;
@@ -405,6 +406,224 @@ fn test_deletion() {
     });
 }
 
+#[test]
+fn test_list_documents() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        send_assert(
+            &client,
+            client
+                .post(url.join("/documents")?)
+                .json(&json!({
+                    "documents": [
+                        { "id": "d1", "snippet": "once in a spring", "properties": { "title": "spring" } },
+                        { "id": "d2", "snippet": "there was a fall", "is_candidate": false }
+                    ]
+                }))
+                .build()?,
+            StatusCode::CREATED,
+            false,
+        )
+        .await;
+
+        let documents = send_assert_json::<Value>(
+            &client,
+            client
+                .get(url.join("/documents?count=1&include_properties=true")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(
+            documents,
+            json!({
+                "documents": [ { "id": "d1", "properties": { "title": "spring" } } ],
+                "next": "d1",
+            }),
+        );
+        let documents = send_assert_json::<Value>(
+            &client,
+            client.get(url.join("/documents?after=d1")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(documents, json!({ "documents": [] }));
+        let documents = send_assert_json::<Value>(
+            &client,
+            client.get(url.join("/documents")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(documents, json!({ "documents": [ { "id": "d1" } ] }));
+
+        send_assert(
+            &client,
+            client.get(url.join("/documents?count=0")?).build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_validate_documents() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        ingest(&client, &url).await?;
+        let report = send_assert_json::<Value>(
+            &client,
+            client
+                .post(url.join("/documents/_validate")?)
+                .json(&json!({ "documents": ["d1", "d2", "d3"] }))
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(
+            report,
+            json!({
+                "missing": ["d3"],
+                "pending": [],
+                "not_indexed": [],
+                "invalid_properties": [],
+            }),
+        );
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_consistency() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        ingest(&client, &url).await?;
+        let consistent = json!({
+            "pending": [],
+            "missing": [],
+            "unexpected": [],
+            "last": "d2",
+            "candidates": 2,
+            "indexed": 2
+        });
+        let report = send_assert_json::<Value>(
+            &client,
+            client.get(url.join("/documents/_consistency")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(report, consistent);
+        let report = send_assert_json::<Value>(
+            &client,
+            client.post(url.join("/documents/_consistency")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(report, consistent);
+        let report = send_assert_json::<Value>(
+            &client,
+            client
+                .get(url.join("/documents/_consistency?after=d1&count=1")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(report, consistent);
+
+        Ok(())
+    });
+}
+
+async fn block_writes(services: &Services, blocked: bool) -> Result<(), anyhow::Error> {
+    services
+        .silo
+        .elastic_client()
+        .with_index(&services.tenant.es_index_name)
+        .request(Method::PUT, ["_settings"], [])
+        .json(&json!({ "index.blocks.write": blocked }))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[test]
+fn test_reconcile_after_elastic_failure() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, services| async move {
+        ingest(&client, &url).await?;
+
+        block_writes(&services, true).await?;
+        let response = client
+            .put(url.join("/documents/d1/properties")?)
+            .json(&json!({ "properties": { "title": "spring" } }))
+            .send()
+            .await?;
+        assert!(!response.status().is_success());
+        let response = client
+            .post(url.join("/documents")?)
+            .json(&json!({ "documents": [ { "id": "d3", "snippet": "once in a fall" } ] }))
+            .send()
+            .await?;
+        assert!(!response.status().is_success());
+
+        let report = send_assert_json::<Value>(
+            &client,
+            client.get(url.join("/documents/_consistency")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(
+            report,
+            json!({
+                "pending": ["d1", "d3"],
+                "missing": ["d3"],
+                "unexpected": [],
+                "last": "d3",
+                "candidates": 3,
+                "indexed": 2
+            }),
+        );
+
+        block_writes(&services, false).await?;
+        let report = send_assert_json::<Value>(
+            &client,
+            client.post(url.join("/documents/_consistency")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(
+            report,
+            json!({
+                "pending": [],
+                "missing": [],
+                "unexpected": [],
+                "last": "d3",
+                "candidates": 3,
+                "indexed": 3
+            }),
+        );
+        let properties = send_assert_json::<Value>(
+            &client,
+            client.get(url.join("/documents/d1/properties")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(properties, json!({ "properties": { "title": "spring" } }));
+
+        Ok(())
+    });
+}
+
 #[derive(Deserialize)]
 struct PersonalizedDocumentData {
     id: String,
@@ -591,6 +810,101 @@ fn test_ingestion_same_id() {
     });
 }
 
+#[test]
+fn test_update_documents() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        send_assert(
+            &client,
+            client
+                .post(url.join("/documents")?)
+                .json(&json!({
+                    "documents": [
+                        { "id": "d1", "snippet": "snippet 1", "properties": { "order": 1 } },
+                        { "id": "d2", "snippet": "snippet 2", "properties": { "order": 2 } }
+                    ]
+                }))
+                .build()?,
+            StatusCode::CREATED,
+            false,
+        )
+        .await;
+        send_assert(
+            &client,
+            client
+                .put(url.join("/documents/d1")?)
+                .json(&json!({ "snippet": "snippet X" }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        let OrderPropertyResponse { property } = send_assert_json(
+            &client,
+            client
+                .get(url.join("/documents/d1/properties/order")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(property, 1);
+
+        send_assert(
+            &client,
+            client
+                .put(url.join("/documents")?)
+                .json(&json!({
+                    "documents": [
+                        { "id": "d2", "snippet": "snippet Y", "properties": { "order": 3 } }
+                    ]
+                }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        let OrderPropertyResponse { property } = send_assert_json(
+            &client,
+            client
+                .get(url.join("/documents/d2/properties/order")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(property, 3);
+
+        send_assert(
+            &client,
+            client
+                .put(url.join("/documents")?)
+                .json(&json!({
+                    "documents": [
+                        { "id": "d2", "snippet": "snippet Y", "unknown": true }
+                    ]
+                }))
+                .build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+
+        let error = send_assert_json::<Error>(
+            &client,
+            client
+                .put(url.join("/documents/d3")?)
+                .json(&json!({ "snippet": "snippet Z" }))
+                .build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+        assert_eq!(error.kind, Kind::DocumentNotFound);
+
+        Ok(())
+    });
+}
+
 #[test]
 fn test_ingestion_validation() {
     test_app::<WebApi, _>(
@@ -694,3 +1008,201 @@ fn test_ingestion_validation() {
         },
     );
 }
+
+#[test]
+fn test_ingestion_reject_duplicates() {
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [ingestion.duplicates]
+            policy = "reject"
+        }),
+        |client, url, _| async move {
+            send_assert(
+                &client,
+                client
+                    .post(url.join("/documents")?)
+                    .json(&json!({
+                        "documents": [ { "id": "d1", "snippet": "once in a spring there was a fall" } ]
+                    }))
+                    .build()?,
+                StatusCode::CREATED,
+                false,
+            )
+            .await;
+
+            let error = send_assert_json::<Error>(
+                &client,
+                client
+                    .post(url.join("/documents")?)
+                    .json(&json!({
+                        "documents": [
+                            { "id": "d2", "snippet": "once in a spring there was a fall" },
+                            { "id": "d3", "snippet": "the recipe needs flour and sugar" },
+                            { "id": "d4", "snippet": "the recipe needs flour and sugar" }
+                        ]
+                    }))
+                    .build()?,
+                StatusCode::BAD_REQUEST,
+                false,
+            )
+            .await;
+            assert_eq!(error.kind, Kind::FailedToValidateDocuments);
+            assert_eq!(
+                error.details.unwrap(),
+                Details::Ingest(vec![
+                    json!({
+                        "id": "d2",
+                        "kind": "DuplicateDocument",
+                        "details": { "duplicate_of": "d1" }
+                    }),
+                    json!({
+                        "id": "d4",
+                        "kind": "DuplicateDocument",
+                        "details": { "duplicate_of": "d3" }
+                    }),
+                ]),
+            );
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn test_ingestion_tag_duplicates() {
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [ingestion.duplicates]
+            policy = "tag"
+        }),
+        |client, url, _| async move {
+            for id in ["d1", "d2"] {
+                send_assert(
+                    &client,
+                    client
+                        .post(url.join("/documents")?)
+                        .json(&json!({
+                            "documents": [ { "id": id, "snippet": "once in a spring there was a fall" } ]
+                        }))
+                        .build()?,
+                    StatusCode::CREATED,
+                    false,
+                )
+                .await;
+            }
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn test_ingestion_with_idempotency_key() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        let ingest = || async {
+            send_assert(
+                &client,
+                client
+                    .post(url.join("/documents")?)
+                    .header("X-Idempotency-Key", "request-1")
+                    .json(&json!({
+                        "documents": [ { "id": "d1", "snippet": "once in a spring" } ]
+                    }))
+                    .build()?,
+                StatusCode::CREATED,
+                false,
+            )
+            .await;
+            Ok::<_, anyhow::Error>(())
+        };
+
+        ingest().await?;
+        send_assert(
+            &client,
+            client.delete(url.join("/documents/d1")?).build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        // the retried request is not processed again
+        ingest().await?;
+        let error = send_assert_json::<Error>(
+            &client,
+            client.get(url.join("/documents/d1/properties")?).build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+        assert_eq!(error.kind, Kind::DocumentNotFound);
+
+        // the key can't be reused for a different request
+        send_assert(
+            &client,
+            client
+                .post(url.join("/documents")?)
+                .header("X-Idempotency-Key", "request-1")
+                .json(&json!({ "documents": [ { "id": "d2", "snippet": "once in a spring" } ] }))
+                .build()?,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            false,
+        )
+        .await;
+
+        // client errors are replayed as well
+        for _ in 0..2 {
+            let error = send_assert_json::<Error>(
+                &client,
+                client
+                    .post(url.join("/documents")?)
+                    .header("X-Idempotency-Key", "request-2")
+                    .json(
+                        &json!({ "documents": [ { "id": "d!", "snippet": "once in a spring" } ] }),
+                    )
+                    .build()?,
+                StatusCode::BAD_REQUEST,
+                false,
+            )
+            .await;
+            assert_eq!(error.kind, Kind::FailedToValidateDocuments);
+        }
+
+        send_assert(
+            &client,
+            client
+                .post(url.join("/documents")?)
+                .header("X-Idempotency-Key", "invalid key")
+                .json(&json!({ "documents": [ { "id": "d1", "snippet": "once in a spring" } ] }))
+                .build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_ingestion_payload_too_large() {
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [net]
+            max_body_size = 1024
+        }),
+        |client, url, _| async move {
+            send_assert(
+                &client,
+                client
+                    .post(url.join("/documents")?)
+                    .json(&json!({ "documents": [ { "id": "d1", "snippet": "a".repeat(1024) } ] }))
+                    .build()?,
+                StatusCode::PAYLOAD_TOO_LARGE,
+                false,
+            )
+            .await;
+            ingest(&client, &url).await?;
+
+            Ok(())
+        },
+    );
+}
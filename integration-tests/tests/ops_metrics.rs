@@ -0,0 +1,36 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use reqwest::StatusCode;
+use serde_json::Value;
+use xayn_integration_tests::{send_assert_json, test_app, UNCHANGED_CONFIG};
+use xayn_web_api::WebApi;
+
+#[test]
+fn test_postgres_pool_metrics() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        let metrics = send_assert_json::<Value>(
+            &client,
+            client.get(url.join("/_ops/metrics")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        let pool = &metrics["postgres_pool"];
+        assert!(pool["size"].as_u64().unwrap() <= pool["max_size"].as_u64().unwrap());
+        assert!(pool["idle"].as_u64().unwrap() <= pool["size"].as_u64().unwrap());
+
+        Ok(())
+    });
+}
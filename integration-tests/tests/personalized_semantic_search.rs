@@ -203,6 +203,52 @@ fn test_subtle_personalization() {
     );
 }
 
+#[test]
+fn test_personalization_with_request_score_weights() {
+    test_app::<WebApi, _>(None, |client, url, _services| async move {
+        ingest(&client, &url).await?;
+        interact(&client, &url).await?;
+
+        let SemanticSearchResponse { documents } = send_assert_json(
+            &client,
+            client
+                .post(url.join("/semantic_search")?)
+                .json(&json!({
+                    "document": { "id": "d1" },
+                    "count": 5,
+                    "personalize": { "user": { "id": "u1" } },
+                    "score_weights": [0.05, 0.05, 0.9]
+                }))
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_order!(
+            documents,
+            ["d6", "d4", "d5", "d8", "d7"],
+            "unexpected subtle personalized documents: {documents:?}",
+        );
+
+        send_assert(
+            &client,
+            client
+                .post(url.join("/semantic_search")?)
+                .json(&json!({
+                    "document": { "id": "d1" },
+                    "personalize": { "user": { "id": "u1" } },
+                    "score_weights": [1.5, 0., 0.]
+                }))
+                .build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+
+        Ok(())
+    });
+}
+
 #[test]
 fn test_full_personalization_with_inline_history() {
     test_app::<WebApi, _>(
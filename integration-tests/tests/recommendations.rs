@@ -13,6 +13,7 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use anyhow::Error;
+use chrono::{DateTime, Duration, Utc};
 use itertools::Itertools;
 use reqwest::{Client, StatusCode, Url};
 use serde::Deserialize;
@@ -170,3 +171,279 @@ fn test_full_personalization_with_user_id_that_has_two_interactions() {
         Ok(())
     });
 }
+
+#[test]
+fn test_trending_documents() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _services| async move {
+        ingest(&client, &url).await?;
+
+        let RecommendationResponse { documents } = send_assert_json(
+            &client,
+            client.get(url.join("/documents/_trending")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert!(documents.is_empty());
+
+        interact(&client, &url).await?;
+        send_assert(
+            &client,
+            client
+                .patch(url.join("/users/u2/interactions")?)
+                .json(&json!({ "documents": [ { "id": "d9" } ] }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+
+        let RecommendationResponse { documents } = send_assert_json(
+            &client,
+            client.get(url.join("/documents/_trending")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(
+            documents
+                .iter()
+                .map(|document| document.id.as_str())
+                .collect_vec(),
+            ["d9", "d2"],
+        );
+        assert!(documents[0].score > documents[1].score);
+
+        Ok(())
+    });
+}
+
+#[derive(Debug, Deserialize)]
+struct InterestData {
+    id: String,
+    view_count: usize,
+    last_view: DateTime<Utc>,
+    weight: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct InterestsResponse {
+    interests: Vec<InterestData>,
+}
+
+#[test]
+fn test_user_interests() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _services| async move {
+        ingest(&client, &url).await?;
+
+        let InterestsResponse { interests } = send_assert_json(
+            &client,
+            client.get(url.join("/users/u1/interests")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert!(interests.is_empty());
+
+        interact(&client, &url).await?;
+
+        let InterestsResponse { interests } = send_assert_json(
+            &client,
+            client.get(url.join("/users/u1/interests")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert!(!interests.is_empty());
+        assert_eq!(
+            interests
+                .iter()
+                .map(|interest| interest.view_count)
+                .sum::<usize>(),
+            2,
+        );
+        for interests in interests.windows(2) {
+            assert!(interests[0].weight >= interests[1].weight);
+        }
+        for interest in &interests {
+            assert!((0. ..=1.).contains(&interest.weight));
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_user_impressions() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _services| async move {
+        ingest(&client, &url).await?;
+        interact(&client, &url).await?;
+
+        let InterestsResponse { interests: before } = send_assert_json(
+            &client,
+            client.get(url.join("/users/u1/interests")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        send_assert(
+            &client,
+            client
+                .post(url.join("/users/u1/impressions")?)
+                .json(&json!({ "documents": [ { "id": "d2" }, { "id": "d10" } ] }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        let InterestsResponse { interests: after } = send_assert_json(
+            &client,
+            client.get(url.join("/users/u1/interests")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+
+        assert_eq!(before.len(), after.len());
+        let mut aged = 0;
+        for interest in &after {
+            let previous = before
+                .iter()
+                .find(|previous| previous.id == interest.id)
+                .unwrap();
+            assert_eq!(interest.view_count, previous.view_count);
+            if interest.last_view < previous.last_view {
+                assert_eq!(interest.last_view, previous.last_view - Duration::days(1));
+                aged += 1;
+            }
+        }
+        assert_eq!(aged, 1);
+
+        Ok(())
+    });
+}
+
+#[derive(Debug, Deserialize)]
+struct SourcesResponse {
+    trusted: Vec<String>,
+    excluded: Vec<String>,
+}
+
+#[test]
+fn test_user_sources() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _services| async move {
+        ingest(&client, &url).await?;
+        interact(&client, &url).await?;
+        send_assert(
+            &client,
+            client
+                .put(url.join("/documents/d8/properties/source")?)
+                .json(&json!({ "property": "example.org" }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+
+        let SourcesResponse { trusted, excluded } = send_assert_json(
+            &client,
+            client.get(url.join("/users/u1/sources")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert!(trusted.is_empty());
+        assert!(excluded.is_empty());
+
+        send_assert(
+            &client,
+            client
+                .put(url.join("/users/u1/sources")?)
+                .json(&json!({
+                    "trusted": ["example.com", "example.org"],
+                    "excluded": ["example.org"]
+                }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        let SourcesResponse { trusted, excluded } = send_assert_json(
+            &client,
+            client.get(url.join("/users/u1/sources")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(trusted, ["example.com"]);
+        assert_eq!(excluded, ["example.org"]);
+
+        let RecommendationResponse { documents } = send_assert_json(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .json(&json!({ "count": 5, "include_properties": false }))
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert!(!documents.is_empty());
+        assert!(documents.iter().all(|document| document.id != "d8"));
+
+        send_assert(
+            &client,
+            client.delete(url.join("/users/u1/sources")?).build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        let RecommendationResponse { documents } = send_assert_json(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .json(&json!({ "count": 5 }))
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert!(documents.iter().any(|document| document.id == "d8"));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_dismissed_documents() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _services| async move {
+        ingest(&client, &url).await?;
+        interact(&client, &url).await?;
+
+        send_assert(
+            &client,
+            client
+                .post(url.join("/users/u1/dismissed_documents")?)
+                .json(&json!({ "documents": [ { "id": "d8" }, { "id": "unknown" } ] }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+
+        let RecommendationResponse { documents } = send_assert_json(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .json(&json!({ "count": 5 }))
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert!(!documents.is_empty());
+        assert!(documents.iter().all(|document| document.id != "d8"));
+
+        Ok(())
+    });
+}
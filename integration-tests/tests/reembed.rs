@@ -0,0 +1,209 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{bail, Error};
+use reqwest::{Client, StatusCode, Url};
+use serde::Deserialize;
+use serde_json::json;
+use toml::toml;
+use xayn_integration_tests::{
+    build_test_config_from_parts,
+    send_assert,
+    send_assert_json,
+    test_app,
+    Services,
+    UNCHANGED_CONFIG,
+};
+use xayn_web_api::{
+    config::{Command, Config},
+    reembed,
+    Application,
+    WebApi,
+};
+use xayn_web_api_db_ctrl::elastic;
+
+async fn ingest(client: &Client, url: &Url) -> Result<(), Error> {
+    send_assert(
+        client,
+        client
+            .post(url.join("/documents")?)
+            .json(&json!({
+                "documents": [
+                    { "id": "d1", "snippet": "Computer" },
+                    { "id": "d2", "snippet": "Technology" },
+                    { "id": "d3", "snippet": "Dogs" },
+                    { "id": "d4", "snippet": "Chicken", "is_candidate": false }
+                ]
+            }))
+            .build()?,
+        StatusCode::CREATED,
+        false,
+    )
+    .await;
+    interact(client, url).await
+}
+
+async fn interact(client: &Client, url: &Url) -> Result<(), Error> {
+    send_assert(
+        client,
+        client
+            .patch(url.join("/users/u1/interactions")?)
+            .json(&json!({ "documents": [ { "id": "d1" }, { "id": "d3" } ] }))
+            .build()?,
+        StatusCode::NO_CONTENT,
+        false,
+    )
+    .await;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct DocumentData {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DocumentsResponse {
+    documents: Vec<DocumentData>,
+}
+
+async fn search(client: &Client, url: &Url) -> Result<Vec<String>, Error> {
+    let DocumentsResponse { documents } = send_assert_json(
+        client,
+        client
+            .post(url.join("/semantic_search")?)
+            .json(&json!({ "document": { "query": "Computer" } }))
+            .build()?,
+        StatusCode::OK,
+        false,
+    )
+    .await;
+    Ok(documents.into_iter().map(|document| document.id).collect())
+}
+
+async fn recommend(client: &Client, url: &Url) -> Result<Vec<String>, Error> {
+    let DocumentsResponse { documents } = send_assert_json(
+        client,
+        client
+            .post(url.join("/users/u1/recommendations")?)
+            .build()?,
+        StatusCode::OK,
+        false,
+    )
+    .await;
+    Ok(documents.into_iter().map(|document| document.id).collect())
+}
+
+async fn run_reembed(services: &Services, index: &str, after: Option<&str>) -> Result<(), Error> {
+    let config = build_test_config_from_parts(
+        WebApi::NAME,
+        services.silo.postgres_config(),
+        services.silo.elastic_config(),
+        toml! {
+            [tenants]
+            enable_legacy_tenant = false
+        },
+    );
+    let config = format!("inline:{config}");
+    let tenant = services.tenant.tenant_id.to_string();
+    let mut args = vec![
+        "integration-test",
+        "--config",
+        &config,
+        "reembed",
+        "--tenant",
+        &tenant,
+        "--index",
+        index,
+        "--batch-size",
+        "1",
+    ];
+    if let Some(after) = after {
+        args.extend(["--after", after]);
+    }
+    let config = Config::load_with_args([""; 0], args);
+    let Command::Reembed(args) = config.command() else {
+        bail!("unexpected command: {:?}", config.command());
+    };
+
+    reembed(config.finalize(false)?, args).await
+}
+
+async fn es_index_name(services: &Services) -> Result<String, Error> {
+    let tenant = services
+        .silo
+        .list_tenants()
+        .await?
+        .into_iter()
+        .find(|tenant| tenant.tenant_id == services.tenant.tenant_id)
+        .unwrap();
+    Ok(tenant.es_index_name)
+}
+
+#[test]
+fn test_reembed() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, services| async move {
+        ingest(&client, &url).await?;
+        let searched = search(&client, &url).await?;
+        let recommended = recommend(&client, &url).await?;
+
+        let index = format!("{}_reembedded", services.tenant.es_index_name);
+        run_reembed(&services, &index, None).await?;
+        assert_eq!(es_index_name(&services).await?, index);
+        elastic::delete_index(
+            services.silo.elastic_client(),
+            &services.tenant.es_index_name,
+        )
+        .await?;
+
+        assert_eq!(search(&client, &url).await?, searched);
+        assert!(searched.iter().all(|id| id != "d4"));
+        // the interests are reset and rebuilt from the following interactions
+        send_assert(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .build()?,
+            StatusCode::CONFLICT,
+            false,
+        )
+        .await;
+        interact(&client, &url).await?;
+        assert_eq!(recommend(&client, &url).await?, recommended);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_reembed_resumes_after_document() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, services| async move {
+        ingest(&client, &url).await?;
+        let searched = search(&client, &url).await?;
+
+        let index = format!("{}_reembedded", services.tenant.es_index_name);
+        run_reembed(&services, &index, Some("d2")).await?;
+        assert_eq!(es_index_name(&services).await?, index);
+        elastic::delete_index(
+            services.silo.elastic_client(),
+            &services.tenant.es_index_name,
+        )
+        .await?;
+
+        // the documents up to the resumed one haven't been staged and are re-embedded afterwards
+        assert_eq!(search(&client, &url).await?, searched);
+
+        Ok(())
+    });
+}
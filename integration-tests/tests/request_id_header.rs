@@ -0,0 +1,59 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use reqwest::StatusCode;
+use xayn_integration_tests::{send_assert, test_app, UNCHANGED_CONFIG};
+use xayn_web_api::WebApi;
+
+#[test]
+fn test_request_id_is_taken_from_header() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        for request_id in ["67e55044-10b1-426f-9247-bb680e5fe0c8", "trace-1:a/b"] {
+            let response = send_assert(
+                &client,
+                client
+                    .get(url.join("/users/u1/interests")?)
+                    .header("X-Request-Id", request_id)
+                    .build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+            assert_eq!(response.headers()["X-Request-Id"], request_id);
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_request_id_is_generated_if_invalid() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        let response = send_assert(
+            &client,
+            client
+                .get(url.join("/users/u1/interests")?)
+                .header("X-Request-Id", "in valid")
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        let request_id = response.headers()["X-Request-Id"].to_str()?;
+        assert_ne!(request_id, "in valid");
+        assert_ne!(request_id, "00000000-0000-0000-0000-000000000000");
+
+        Ok(())
+    });
+}
@@ -472,3 +472,69 @@ fn test_semantic_search_with_dev_option_raw_scores() {
         Ok(())
     });
 }
+
+#[test]
+fn test_related_documents() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        ingest(&client, &url).await?;
+
+        let SemanticSearchResponse { documents } = send_assert_json(
+            &client,
+            client.get(url.join("/documents/d1/related")?).build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_order!(
+            documents,
+            ["d3", "d2"],
+            "unexpected documents: {documents:?}",
+        );
+
+        let SemanticSearchResponse { documents } = send_assert_json(
+            &client,
+            client
+                .get(url.join("/documents/d1/related?count=1&include_snippet=true")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_order!(documents, ["d3"], "unexpected documents: {documents:?}");
+        assert_eq!(
+            documents[0].snippet.as_deref(),
+            Some("this is another sentence which we have"),
+        );
+
+        send_assert(
+            &client,
+            client
+                .patch(url.join("/users/u1/interactions")?)
+                .json(&json!({ "documents": [ { "id": "d3" } ] }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        let SemanticSearchResponse { documents } = send_assert_json(
+            &client,
+            client
+                .get(url.join("/documents/d1/related?user_id=u1")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_order!(documents, ["d2"], "unexpected documents: {documents:?}");
+
+        send_assert(
+            &client,
+            client.get(url.join("/documents/d9/related")?).build()?,
+            StatusCode::NOT_FOUND,
+            false,
+        )
+        .await;
+
+        Ok(())
+    });
+}
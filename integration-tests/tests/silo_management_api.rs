@@ -23,7 +23,7 @@ use toml::toml;
 use url::Url;
 use xayn_integration_tests::{send_assert, send_assert_json, test_app, TEST_EMBEDDING_SIZE};
 use xayn_web_api::WebApi;
-use xayn_web_api_db_ctrl::{elastic, tenant::TenantWithOptionals, OperationResult};
+use xayn_web_api_db_ctrl::{elastic, tenant::TenantWithOptionals, EmbeddingDims, OperationResult};
 use xayn_web_api_shared::{
     elastic::ClientWithoutIndex,
     json_object,
@@ -369,3 +369,54 @@ fn test_creating_tenant_with_alternative_model() {
         },
     );
 }
+
+#[test]
+fn test_embedding_dims_are_reported() {
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [tenants]
+            enable_legacy_tenant = false
+        }),
+        |client, url, services| async move {
+            ingest(&client, &url, vec![("d0", "document 0")]).await?;
+            send_assert(
+                &client,
+                client
+                    .patch(url.join("/users/u0/interactions")?)
+                    .json(&json!({ "documents": [ { "id": "d0" } ] }))
+                    .build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+
+            let ManagementResponse { results } = send_assert_json(
+                &client,
+                client
+                    .post(url.join("/_ops/silo_management")?)
+                    .json(&json!({ "operations": [ { "EmbeddingDims": {} } ] }))
+                    .build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+
+            let [OperationResult::EmbeddingDims { tenants }] = &results[..] else {
+                panic!("failed to report embedding dims: {results:?}");
+            };
+            assert_eq!(
+                tenants,
+                &[EmbeddingDims {
+                    tenant_id: services.tenant.tenant_id.clone(),
+                    model: services.tenant.model.clone(),
+                    model_dims: Some(TEST_EMBEDDING_SIZE),
+                    es_index_dims: Some(TEST_EMBEDDING_SIZE),
+                    coi_dims: vec![TEST_EMBEDDING_SIZE],
+                }]
+            );
+            assert!(tenants[0].is_consistent());
+
+            Ok(())
+        },
+    );
+}
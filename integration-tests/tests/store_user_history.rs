@@ -31,6 +31,28 @@ struct PersonalizedDocumentsResponse {
     documents: Vec<PersonalizedDocumentData>,
 }
 
+#[derive(Deserialize)]
+struct InteractionData {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct InteractionHistoryData {
+    id: String,
+    #[serde(rename = "type")]
+    interaction_type: String,
+}
+
+#[derive(Deserialize)]
+struct InteractionHistoryResponse {
+    interactions: Vec<InteractionHistoryData>,
+}
+
+#[derive(Deserialize)]
+struct IgnoredInteractionsResponse {
+    ignored: Vec<InteractionData>,
+}
+
 fn store_user_history(enabled: bool) {
     test_app::<WebApi, _>(
         Some(toml! {
@@ -61,12 +83,49 @@ fn store_user_history(enabled: bool) {
                 &client,
                 client
                     .patch(url.join("/users/u0/interactions")?)
-                    .json(&json!({ "documents": [ { "id": "2" }, { "id": "5" } ] }))
+                    .json(&json!({
+                        "documents": [ { "id": "2" }, { "id": "5", "type": "click" } ]
+                    }))
                     .build()?,
                 StatusCode::NO_CONTENT,
                 false,
             )
             .await;
+            let ignored = send_assert_json::<IgnoredInteractionsResponse>(
+                &client,
+                client
+                    .patch(url.join("/users/u0/interactions")?)
+                    .json(&json!({ "documents": [ { "id": "9" } ] }))
+                    .build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+            assert_eq!(ignored.ignored.len(), 1);
+            assert_eq!(ignored.ignored[0].id, "9");
+
+            let interactions = send_assert_json::<InteractionHistoryResponse>(
+                &client,
+                client.get(url.join("/users/u0/interactions")?).build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+            let interactions = interactions
+                .interactions
+                .iter()
+                .map(|interaction| {
+                    (
+                        interaction.id.as_str(),
+                        interaction.interaction_type.as_str(),
+                    )
+                })
+                .collect::<HashSet<_>>();
+            if enabled {
+                assert_eq!(interactions, [("2", "positive"), ("5", "click")].into());
+            } else {
+                assert!(interactions.is_empty());
+            }
 
             let documents = send_assert_json::<PersonalizedDocumentsResponse>(
                 &client,
@@ -88,6 +147,29 @@ fn store_user_history(enabled: bool) {
                 assert_eq!(documents, ["1", "2", "3", "4", "5"].into());
             }
 
+            send_assert(
+                &client,
+                client.delete(url.join("/users/u0/interactions")?).build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+            let documents = send_assert_json::<PersonalizedDocumentsResponse>(
+                &client,
+                client
+                    .post(url.join("/users/u0/recommendations")?)
+                    .build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+            let documents = documents
+                .documents
+                .iter()
+                .map(|document| document.id.as_str())
+                .collect::<HashSet<_>>();
+            assert_eq!(documents, ["1", "2", "3", "4", "5"].into());
+
             Ok(())
         },
     );
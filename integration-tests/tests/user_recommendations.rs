@@ -15,10 +15,12 @@
 use std::collections::HashMap;
 
 use anyhow::Error;
+use chrono::{Duration, SecondsFormat, Utc};
 use itertools::Itertools;
 use reqwest::{Client, Request, StatusCode, Url};
 use serde::Deserialize;
 use serde_json::{json, Value};
+use toml::toml;
 use xayn_integration_tests::{send_assert, send_assert_json, test_app, UNCHANGED_CONFIG};
 use xayn_web_api::WebApi;
 use xayn_web_api_shared::serde::json_object;
@@ -319,6 +321,31 @@ fn test_personalization_all_dates() {
     });
 }
 
+#[test]
+fn test_personalization_max_excluded_documents() {
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [storage.elastic]
+            max_excluded_documents = 1
+        }),
+        |client, url, _| async move {
+            ingest_with_dates(&client, &url).await?;
+            personalize(&client, &url, None, None, |documents| {
+                assert!(!documents.is_empty());
+                assert!(
+                    documents
+                        .iter()
+                        .all(|document| document.id != "d2" && document.id != "d9"),
+                    "unexpected personalized documents: {documents:?}",
+                );
+            })
+            .await?;
+
+            Ok(())
+        },
+    );
+}
+
 #[test]
 fn test_personalization_limited_dates() {
     test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
@@ -342,6 +369,63 @@ fn test_personalization_limited_dates() {
     });
 }
 
+#[test]
+fn test_personalization_max_document_age() {
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [personalization]
+            max_document_age = 10
+        }),
+        |client, url, _| async move {
+            let now = Utc::now();
+            let recent = (now - Duration::days(1)).to_rfc3339_opts(SecondsFormat::Secs, true);
+            let old = (now - Duration::days(20)).to_rfc3339_opts(SecondsFormat::Secs, true);
+            send_assert(
+                &client,
+                client
+                    .post(url.join("/documents")?)
+                    .json(&json!({
+                        "documents": [
+                            { "id": "d1", "snippet": "Computer", "properties": { "publication_date": recent } },
+                            { "id": "d2", "snippet": "Technology", "properties": { "publication_date": recent } },
+                            { "id": "d3", "snippet": "Laptop", "properties": { "publication_date": old } },
+                            { "id": "d4", "snippet": "Computers", "properties": { "publication_date": old } },
+                            { "id": "d5", "snippet": "Smartphone" },
+                            { "id": "d9", "snippet": "Robot Chicken", "properties": { "publication_date": recent } }
+                        ]
+                    }))
+                    .build()?,
+                StatusCode::CREATED,
+                false,
+            )
+            .await;
+            interact(&client, &url).await?;
+
+            let documents = send_assert_json::<RecommendationsResponse>(
+                &client,
+                client
+                    .post(url.join("/users/u1/recommendations")?)
+                    .build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+            let RecommendationsResponse::Documents(documents) = documents else {
+                panic!("unexpected response: {documents:?}");
+            };
+            assert_eq!(
+                documents
+                    .iter()
+                    .map(|document| document.id.as_str())
+                    .collect_vec(),
+                ["d1"],
+            );
+
+            Ok(())
+        },
+    );
+}
+
 #[test]
 fn test_personalization_with_tags() {
     test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
@@ -405,3 +489,453 @@ fn test_personalize_no_body() {
         Ok(())
     });
 }
+
+#[test]
+fn test_personalization_pinned_documents() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        ingest_with_dates(&client, &url).await?;
+        interact(&client, &url).await?;
+        for documents in [
+            json!([ { "id": "d7", "position": 0 }, { "id": "d0", "position": 1 } ]),
+            json!([ { "id": "d7", "position": 0 }, { "id": "d8", "position": 0 } ]),
+            json!([ { "id": "d7", "position": 0 }, { "id": "d7", "position": 1 } ]),
+            json!([ { "id": "d8", "position": 100 } ]),
+        ] {
+            send_assert(
+                &client,
+                client
+                    .post(url.join("/users/u1/pinned_documents")?)
+                    .json(&json!({ "documents": documents }))
+                    .build()?,
+                StatusCode::BAD_REQUEST,
+                false,
+            )
+            .await;
+        }
+        for documents in [
+            json!([ { "id": "d8", "position": 2 } ]),
+            json!([ { "id": "d7", "position": 0 }, { "id": "d2", "position": 1 } ]),
+        ] {
+            send_assert(
+                &client,
+                client
+                    .post(url.join("/users/u1/pinned_documents")?)
+                    .json(&json!({ "documents": documents }))
+                    .build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+        }
+        let pinned = send_assert_json::<Value>(
+            &client,
+            client
+                .get(url.join("/users/u1/pinned_documents")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(
+            pinned,
+            json!({ "documents": [ { "id": "d7", "position": 0 }, { "id": "d2", "position": 1 } ] }),
+        );
+
+        let documents = send_assert_json::<RecommendationsResponse>(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .json(&json!({ "count": 3 }))
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        let RecommendationsResponse::Documents(documents) = documents else {
+            panic!("unexpected response: {documents:?}");
+        };
+        assert_eq!(documents.len(), 3);
+        assert_eq!(documents[0].id, "d7");
+        // Hint: d2 is pinned, but the user interacted with it
+        assert!(documents[1..]
+            .iter()
+            .all(|document| document.id != "d7" && document.id != "d2"));
+
+        send_assert(
+            &client,
+            client
+                .delete(url.join("/users/u1/pinned_documents")?)
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        let documents = send_assert_json::<RecommendationsResponse>(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .json(&json!({ "count": 3 }))
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        let RecommendationsResponse::Documents(documents) = documents else {
+            panic!("unexpected response: {documents:?}");
+        };
+        assert!(documents.iter().all(|document| document.id != "d7"));
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_personalization_pinned_documents_without_interests() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        ingest_with_dates(&client, &url).await?;
+        send_assert(
+            &client,
+            client
+                .post(url.join("/users/u1/pinned_documents")?)
+                .json(&json!({ "documents": [ { "id": "d7", "position": 3 } ] }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        let documents = send_assert_json::<RecommendationsResponse>(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        let RecommendationsResponse::Documents(documents) = documents else {
+            panic!("unexpected response: {documents:?}");
+        };
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].id, "d7");
+
+        send_assert(
+            &client,
+            client
+                .delete(url.join("/users/u1/pinned_documents")?)
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        send_assert(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .build()?,
+            StatusCode::CONFLICT,
+            false,
+        )
+        .await;
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_personalization_boost_rules() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        ingest_with_dates(&client, &url).await?;
+        interact(&client, &url).await?;
+        send_assert(
+            &client,
+            client
+                .put(url.join("/boost_rules/r1")?)
+                .json(&json!({ "property_id": "publication_date", "value": "2023-01-12T20:20:20Z", "weight": 1 }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        send_assert(
+            &client,
+            client
+                .put(url.join("/boost_rules/r2")?)
+                .json(&json!({ "property_id": "publication_date", "value": "2023-08-12T20:20:20Z", "weight": 1, "expires_at": "2000-01-01T00:00:00Z" }))
+                .build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        send_assert(
+            &client,
+            client
+                .put(url.join("/boost_rules/r3")?)
+                .json(&json!({ "property_id": "publication_date", "value": "2023-01-12T20:20:20Z", "weight": 2 }))
+                .build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+
+        let documents = send_assert_json::<RecommendationsResponse>(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        let RecommendationsResponse::Documents(documents) = documents else {
+            panic!("unexpected response: {documents:?}");
+        };
+        assert_eq!(documents[0].id, "d1");
+        assert!(documents[1..]
+            .iter()
+            .all(|document| document.score < documents[0].score));
+
+        send_assert(
+            &client,
+            client.delete(url.join("/boost_rules/r1")?).build()?,
+            StatusCode::NO_CONTENT,
+            false,
+        )
+        .await;
+        send_assert(
+            &client,
+            client.delete(url.join("/boost_rules/r1")?).build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_personalization_min_similarity() {
+    #[derive(Debug, Deserialize)]
+    struct TruncatedResponse {
+        documents: Vec<PersonalizedDocumentData>,
+        truncated: Option<String>,
+    }
+
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |client, url, _| async move {
+        ingest_with_dates(&client, &url).await?;
+        interact(&client, &url).await?;
+
+        let response = send_assert_json::<TruncatedResponse>(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .json(&json!({ "min_similarity": -1 }))
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert_eq!(response.documents.len(), 7);
+        assert_eq!(response.truncated, None);
+
+        let response = send_assert_json::<TruncatedResponse>(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .json(&json!({ "min_similarity": 1 }))
+                .build()?,
+            StatusCode::OK,
+            false,
+        )
+        .await;
+        assert!(response.documents.is_empty());
+        assert_eq!(response.truncated.as_deref(), Some("min_similarity"));
+
+        send_assert(
+            &client,
+            client
+                .post(url.join("/users/u1/recommendations")?)
+                .json(&json!({ "min_similarity": 2 }))
+                .build()?,
+            StatusCode::BAD_REQUEST,
+            false,
+        )
+        .await;
+
+        Ok(())
+    });
+}
+
+#[test]
+fn test_personalization_exploration() {
+    #[derive(Debug, Deserialize)]
+    struct ExplorationDocumentData {
+        id: String,
+        #[serde(default)]
+        exploration: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct ExplorationResponse {
+        documents: Vec<ExplorationDocumentData>,
+    }
+
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [personalization]
+            exploration_share = 0.5
+        }),
+        |client, url, _| async move {
+            ingest_with_dates(&client, &url).await?;
+            interact(&client, &url).await?;
+            send_assert(
+                &client,
+                client
+                    .patch(url.join("/users/u2/interactions")?)
+                    .json(&json!({ "documents": [ { "id": "d7" } ] }))
+                    .build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+
+            let response = send_assert_json::<ExplorationResponse>(
+                &client,
+                client
+                    .post(url.join("/users/u1/recommendations")?)
+                    .json(&json!({ "count": 2 }))
+                    .build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+            assert_eq!(response.documents.len(), 2);
+            assert!(!response.documents[0].exploration);
+            assert_eq!(response.documents[1].id, "d7");
+            assert!(response.documents[1].exploration);
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn test_personalization_exploration_max_document_age() {
+    #[derive(Debug, Deserialize)]
+    struct ExplorationDocumentData {
+        id: String,
+        #[serde(default)]
+        exploration: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct ExplorationResponse {
+        documents: Vec<ExplorationDocumentData>,
+    }
+
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [personalization]
+            exploration_share = 0.5
+            max_document_age = 36500
+        }),
+        |client, url, _| async move {
+            ingest_with_dates(&client, &url).await?;
+            interact(&client, &url).await?;
+            send_assert(
+                &client,
+                client
+                    .patch(url.join("/users/u2/interactions")?)
+                    .json(&json!({ "documents": [ { "id": "d7" } ] }))
+                    .build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+
+            let response = send_assert_json::<ExplorationResponse>(
+                &client,
+                client
+                    .post(url.join("/users/u1/recommendations")?)
+                    .json(&json!({ "count": 2 }))
+                    .build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+            // Hint: the trending d7 has no publication date
+            assert!(response
+                .documents
+                .iter()
+                .all(|document| document.id != "d7" && !document.exploration));
+
+            Ok(())
+        },
+    );
+}
+
+#[test]
+fn test_personalization_exploration_with_pinned_documents() {
+    #[derive(Debug, Deserialize)]
+    struct ExplorationDocumentData {
+        id: String,
+        #[serde(default)]
+        exploration: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct ExplorationResponse {
+        documents: Vec<ExplorationDocumentData>,
+    }
+
+    test_app::<WebApi, _>(
+        Some(toml! {
+            [personalization]
+            exploration_share = 0.5
+        }),
+        |client, url, _| async move {
+            ingest_with_dates(&client, &url).await?;
+            interact(&client, &url).await?;
+            send_assert(
+                &client,
+                client
+                    .patch(url.join("/users/u2/interactions")?)
+                    .json(&json!({ "documents": [ { "id": "d7" } ] }))
+                    .build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+            send_assert(
+                &client,
+                client
+                    .post(url.join("/users/u1/pinned_documents")?)
+                    .json(&json!({ "documents": [ { "id": "d8", "position": 0 } ] }))
+                    .build()?,
+                StatusCode::NO_CONTENT,
+                false,
+            )
+            .await;
+
+            let response = send_assert_json::<ExplorationResponse>(
+                &client,
+                client
+                    .post(url.join("/users/u1/recommendations")?)
+                    .json(&json!({ "count": 2 }))
+                    .build()?,
+                StatusCode::OK,
+                false,
+            )
+            .await;
+            // Hint: the pinned document takes the room of a recommended document, not of the
+            //       blended in one
+            assert_eq!(response.documents.len(), 2);
+            assert_eq!(response.documents[0].id, "d8");
+            assert!(!response.documents[0].exploration);
+            assert_eq!(response.documents[1].id, "d7");
+            assert!(response.documents[1].exploration);
+
+            Ok(())
+        },
+    );
+}
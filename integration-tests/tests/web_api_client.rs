@@ -0,0 +1,69 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use xayn_integration_tests::{test_app, UNCHANGED_CONFIG};
+use xayn_web_api::WebApi;
+use xayn_web_api_client::{
+    Client,
+    Config,
+    Error,
+    IngestedDocument,
+    PersonalizedDocumentsRequest,
+    SearchDocument,
+    SemanticSearchRequest,
+};
+
+#[test]
+fn test_web_api_client() {
+    test_app::<WebApi, _>(UNCHANGED_CONFIG, |_, url, services| async move {
+        let mut config = Config::new((*url).clone());
+        config.tenant_id = Some(services.tenant.tenant_id.clone());
+        let client = Client::new(config)?;
+
+        client
+            .ingest_documents(&[
+                IngestedDocument::new("d1", "Computer"),
+                IngestedDocument::new("d2", "Technology"),
+                IngestedDocument::new("d3", "Politic"),
+                IngestedDocument::new("d4", "Laptop"),
+                IngestedDocument::new("d5", "Smartphone"),
+            ])
+            .await?;
+
+        let result = client
+            .personalized_documents("u0", &PersonalizedDocumentsRequest::default())
+            .await;
+        assert!(matches!(result, Err(Error::NotEnoughInteractions)));
+
+        client.update_interactions("u0", ["d2", "d4"]).await?;
+        let documents = client
+            .personalized_documents("u0", &PersonalizedDocumentsRequest::default())
+            .await?;
+        let ids = documents
+            .iter()
+            .map(|document| document.id.as_str())
+            .collect::<HashSet<_>>();
+        assert_eq!(ids, ["d1", "d3", "d5"].into());
+
+        let documents = client
+            .semantic_search(&SemanticSearchRequest::new(SearchDocument::Id("d1".into())))
+            .await?;
+        assert!(!documents.is_empty());
+        assert!(documents.iter().all(|document| document.id != "d1"));
+
+        Ok(())
+    });
+}
@@ -0,0 +1,288 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Typed async client for the web-api.
+//!
+//! Covers ingestion, interactions, personalized documents and semantic search. Idempotent requests
+//! which failed due to the transport or a degraded web-api are retried with exponential backoff.
+
+mod models;
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use derive_more::From;
+use reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_TYPE},
+    Method,
+    StatusCode,
+};
+use serde::Serialize;
+use thiserror::Error;
+use url::Url;
+use xayn_web_api_shared::{
+    net::{ExponentialJitterRetryPolicy, ExponentialJitterRetryPolicyConfig},
+    request::TenantId,
+};
+
+use crate::models::{DocumentsResponse, IngestionRequest, InteractedDocument, InteractionRequest};
+pub use crate::models::{
+    IngestedDocument,
+    Personalize,
+    PersonalizedDocument,
+    PersonalizedDocumentsRequest,
+    PersonalizedUser,
+    SearchDocument,
+    SemanticSearchRequest,
+    SnippetId,
+};
+
+const TENANT_ID_HEADER: &str = "X-Xayn-Tenant-Id";
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Base url of the web-api, e.g. `http://localhost:4252/`.
+    pub url: Url,
+
+    /// The tenant, not needed if the web-api runs with the legacy tenant.
+    pub tenant_id: Option<TenantId>,
+
+    /// Request timeout.
+    pub timeout: Duration,
+
+    /// The retry policy for failed requests.
+    pub retry_policy: ExponentialJitterRetryPolicyConfig,
+}
+
+impl Config {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            tenant_id: None,
+            timeout: Duration::from_secs(30),
+            retry_policy: ExponentialJitterRetryPolicyConfig {
+                max_retries: 3,
+                step_size: Duration::from_millis(300),
+                max_backoff: Duration::from_millis(1000),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error, displaydoc::Display, From)]
+pub enum Error {
+    /// Transmitting a request or receiving the response failed: {0}
+    Transport(reqwest::Error),
+    /// The web-api failed, status={status}, url={url}, body={body}
+    Status {
+        status: StatusCode,
+        url: Url,
+        body: String,
+    },
+    /// Failed to serialize a request or deserialize a response: {0}
+    Serialization(serde_json::Error),
+    /// The user doesn't have enough interactions to personalize documents
+    #[from(ignore)]
+    NotEnoughInteractions,
+    /// The url can't be used as base url: {0}
+    #[from(ignore)]
+    InvalidBaseUrl(Url),
+}
+
+impl Error {
+    /// Checks if the request can be retried.
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Transport(_) => true,
+            Self::Status { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::Serialization(_) | Self::NotEnoughInteractions | Self::InvalidBaseUrl(_) => false,
+        }
+    }
+}
+
+/// A client for the web-api.
+///
+/// The client is cheap to clone.
+#[derive(Clone, Debug)]
+pub struct Client {
+    client: reqwest::Client,
+    url: Url,
+    retry_policy: ExponentialJitterRetryPolicyConfig,
+}
+
+impl Client {
+    pub fn new(config: Config) -> Result<Self, Error> {
+        if config.url.cannot_be_a_base() {
+            return Err(Error::InvalidBaseUrl(config.url));
+        }
+
+        let mut headers = HeaderMap::new();
+        if let Some(tenant_id) = &config.tenant_id {
+            headers.insert(
+                TENANT_ID_HEADER,
+                HeaderValue::try_from(tenant_id.to_string())
+                    .unwrap(/* tenant ids are valid header values */),
+            );
+        }
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(config.timeout)
+            .build()?;
+
+        Ok(Self {
+            client,
+            url: config.url,
+            retry_policy: config.retry_policy,
+        })
+    }
+
+    /// Ingests the documents, see `POST /documents`.
+    pub async fn ingest_documents(&self, documents: &[IngestedDocument]) -> Result<(), Error> {
+        self.send(
+            Method::POST,
+            self.endpoint(&["documents"]),
+            &IngestionRequest { documents },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Registers that the user interacted with the documents, see
+    /// `PATCH /users/{user_id}/interactions`.
+    ///
+    /// This is not retried, because each interaction updates the interests of the user again.
+    pub async fn update_interactions(
+        &self,
+        user_id: &str,
+        document_ids: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<(), Error> {
+        let documents = document_ids
+            .into_iter()
+            .map(|id| InteractedDocument { id: id.into() })
+            .collect();
+        self.send_without_retrying(
+            Method::PATCH,
+            self.endpoint(&["users", user_id, "interactions"]),
+            Bytes::from(serde_json::to_vec(&InteractionRequest { documents })?),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Recommends documents to the user, see `POST /users/{user_id}/recommendations`.
+    ///
+    /// Fails with [`Error::NotEnoughInteractions`] if the user didn't interact enough yet.
+    pub async fn personalized_documents(
+        &self,
+        user_id: &str,
+        request: &PersonalizedDocumentsRequest,
+    ) -> Result<Vec<PersonalizedDocument>, Error> {
+        let result = self
+            .send(
+                Method::POST,
+                self.endpoint(&["users", user_id, "recommendations"]),
+                request,
+            )
+            .await;
+        match result {
+            Ok(body) => Ok(serde_json::from_slice::<DocumentsResponse>(&body)?.documents),
+            Err(Error::Status {
+                status: StatusCode::CONFLICT,
+                ..
+            }) => Err(Error::NotEnoughInteractions),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Searches documents, see `POST /semantic_search`.
+    pub async fn semantic_search(
+        &self,
+        request: &SemanticSearchRequest,
+    ) -> Result<Vec<PersonalizedDocument>, Error> {
+        let body = self
+            .send(Method::POST, self.endpoint(&["semantic_search"]), request)
+            .await?;
+
+        Ok(serde_json::from_slice::<DocumentsResponse>(&body)?.documents)
+    }
+
+    fn endpoint(&self, segments: &[&str]) -> Url {
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .unwrap(/* checked to be a base url */)
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+
+    /// Sends an idempotent request and retries it on failure.
+    async fn send(&self, method: Method, url: Url, body: &impl Serialize) -> Result<Bytes, Error> {
+        let body = Bytes::from(serde_json::to_vec(body)?);
+        ExponentialJitterRetryPolicy::new(self.retry_policy.clone())
+            .with_retry_filter(Error::is_retryable)
+            .retry(|| self.send_without_retrying(method.clone(), url.clone(), body.clone()))
+            .await
+    }
+
+    async fn send_without_retrying(
+        &self,
+        method: Method,
+        url: Url,
+        body: Bytes,
+    ) -> Result<Bytes, Error> {
+        let response = self
+            .client
+            .request(method, url)
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let url = response.url().clone();
+        let body = response.bytes().await?;
+        if status.is_success() {
+            Ok(body)
+        } else {
+            let body = String::from_utf8_lossy(&body).into_owned();
+            Err(Error::Status { status, url, body })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        for base in ["http://localhost:4252", "http://localhost:4252/"] {
+            let client = Client::new(Config::new(base.parse().unwrap())).unwrap();
+            assert_eq!(
+                client.endpoint(&["users", "u/0", "interactions"]).as_str(),
+                "http://localhost:4252/users/u%2F0/interactions",
+            );
+        }
+
+        let client = Client::new(Config::new("http://localhost/api/".parse().unwrap())).unwrap();
+        assert_eq!(
+            client.endpoint(&["documents"]).as_str(),
+            "http://localhost/api/documents",
+        );
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Requests and responses of the web-api.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A document to ingest.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct IngestedDocument {
+    pub id: String,
+    pub snippet: String,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub properties: Map<String, Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_candidate: Option<bool>,
+}
+
+impl IngestedDocument {
+    pub fn new(id: impl Into<String>, snippet: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            snippet: snippet.into(),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct IngestionRequest<'a> {
+    pub(crate) documents: &'a [IngestedDocument],
+}
+
+#[derive(Serialize)]
+pub(crate) struct InteractionRequest {
+    pub(crate) documents: Vec<InteractedDocument>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct InteractedDocument {
+    pub(crate) id: String,
+}
+
+/// Options for personalized documents.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PersonalizedDocumentsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+    /// The filter, see the api documentation for its structure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_properties: Option<bool>,
+    pub include_snippet: bool,
+    /// Drops documents which are less similar to each of the user's interests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_similarity: Option<f32>,
+}
+
+/// The document to search for.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchDocument {
+    /// Searches documents similar to an ingested document.
+    Id(String),
+    /// Searches documents matching a free text query.
+    Query(String),
+}
+
+/// Personalizes the semantic search for a user.
+#[derive(Clone, Debug, Serialize)]
+pub struct Personalize {
+    pub exclude_seen: bool,
+    pub user: PersonalizedUser,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PersonalizedUser {
+    pub id: String,
+}
+
+/// Options for semantic search.
+#[derive(Clone, Debug, Serialize)]
+pub struct SemanticSearchRequest {
+    pub document: SearchDocument,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub personalize: Option<Personalize>,
+    pub enable_hybrid_search: bool,
+    /// The filter, see the api documentation for its structure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_properties: Option<bool>,
+    pub include_snippet: bool,
+}
+
+impl SemanticSearchRequest {
+    pub fn new(document: SearchDocument) -> Self {
+        Self {
+            document,
+            count: None,
+            personalize: None,
+            enable_hybrid_search: false,
+            filter: None,
+            include_properties: None,
+            include_snippet: false,
+        }
+    }
+}
+
+/// Id pointing to a specific snippet in a document.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub struct SnippetId {
+    pub document_id: String,
+    pub sub_id: u32,
+}
+
+/// A document returned by personalization or semantic search.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PersonalizedDocument {
+    pub id: String,
+    pub snippet_id: SnippetId,
+    pub score: f32,
+    #[serde(default)]
+    pub properties: Map<String, Value>,
+    pub snippet: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DocumentsResponse {
+    pub(crate) documents: Vec<PersonalizedDocument>,
+}
@@ -0,0 +1,27 @@
+-- Copyright 2023 Xayn AG
+--
+-- This program is free software: you can redistribute it and/or modify
+-- it under the terms of the GNU Affero General Public License as
+-- published by the Free Software Foundation, version 3.
+--
+-- This program is distributed in the hope that it will be useful,
+-- but WITHOUT ANY WARRANTY; without even the implied warranty of
+-- MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+-- GNU Affero General Public License for more details.
+--
+-- You should have received a copy of the GNU Affero General Public License
+-- along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+ALTER TABLE interaction
+    ADD COLUMN coi_id UUID REFERENCES center_of_interest(coi_id) ON DELETE SET NULL;
+
+CREATE TYPE interaction_type AS ENUM (
+    'positive',
+    'click',
+    'read',
+    'like',
+    'share'
+);
+
+ALTER TABLE interaction
+    ADD COLUMN interaction_type interaction_type NOT NULL DEFAULT 'positive';
@@ -0,0 +1,30 @@
+-- Copyright 2023 Xayn AG
+--
+-- This program is free software: you can redistribute it and/or modify
+-- it under the terms of the GNU Affero General Public License as
+-- published by the Free Software Foundation, version 3.
+--
+-- This program is distributed in the hope that it will be useful,
+-- but WITHOUT ANY WARRANTY; without even the implied warranty of
+-- MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+-- GNU Affero General Public License for more details.
+--
+-- You should have received a copy of the GNU Affero General Public License
+-- along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+CREATE TABLE IF NOT EXISTS interaction_count (
+    document_id TEXT NOT NULL,
+    sub_id INTEGER NOT NULL,
+    day DATE NOT NULL,
+    count INTEGER NOT NULL,
+    PRIMARY KEY (document_id, sub_id, day),
+    FOREIGN KEY (document_id, sub_id) REFERENCES snippet(document_id, sub_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_interaction_count_by_day
+    ON interaction_count(day);
+
+INSERT INTO interaction_count (document_id, sub_id, day, count)
+    SELECT document_id, sub_id, time_stamp::DATE, COUNT(*)
+    FROM interaction
+    GROUP BY document_id, sub_id, time_stamp::DATE;
@@ -0,0 +1,25 @@
+-- Copyright 2023 Xayn AG
+--
+-- This program is free software: you can redistribute it and/or modify
+-- it under the terms of the GNU Affero General Public License as
+-- published by the Free Software Foundation, version 3.
+--
+-- This program is distributed in the hope that it will be useful,
+-- but WITHOUT ANY WARRANTY; without even the implied warranty of
+-- MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+-- GNU Affero General Public License for more details.
+--
+-- You should have received a copy of the GNU Affero General Public License
+-- along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+CREATE TABLE IF NOT EXISTS idempotency_key (
+    key TEXT NOT NULL PRIMARY KEY,
+    fingerprint BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    locked_until TIMESTAMPTZ NOT NULL,
+    status SMALLINT,
+    response BYTEA
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_key_by_created_at
+    ON idempotency_key(created_at);
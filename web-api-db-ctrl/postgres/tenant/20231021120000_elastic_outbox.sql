@@ -0,0 +1,20 @@
+-- Copyright 2023 Xayn AG
+--
+-- This program is free software: you can redistribute it and/or modify
+-- it under the terms of the GNU Affero General Public License as
+-- published by the Free Software Foundation, version 3.
+--
+-- This program is distributed in the hope that it will be useful,
+-- but WITHOUT ANY WARRANTY; without even the implied warranty of
+-- MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+-- GNU Affero General Public License for more details.
+--
+-- You should have received a copy of the GNU Affero General Public License
+-- along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+-- Candidates which are stored in postgres but not yet confirmed to be stored in elastic.
+CREATE TABLE IF NOT EXISTS elastic_outbox (
+    document_id TEXT PRIMARY KEY
+        REFERENCES document(document_id) ON DELETE CASCADE,
+    time_stamp TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,23 @@
+-- Copyright 2023 Xayn AG
+--
+-- This program is free software: you can redistribute it and/or modify
+-- it under the terms of the GNU Affero General Public License as
+-- published by the Free Software Foundation, version 3.
+--
+-- This program is distributed in the hope that it will be useful,
+-- but WITHOUT ANY WARRANTY; without even the implied warranty of
+-- MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+-- GNU Affero General Public License for more details.
+--
+-- You should have received a copy of the GNU Affero General Public License
+-- along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+-- re-embedded snippets which are staged until the tenant switches to the new es index
+CREATE TABLE IF NOT EXISTS reembedded_snippet (
+    document_id TEXT NOT NULL,
+    sub_id INTEGER NOT NULL,
+    snippet TEXT NOT NULL,
+    embedding FLOAT4[] NOT NULL,
+    PRIMARY KEY (document_id, sub_id),
+    FOREIGN KEY (document_id, sub_id) REFERENCES snippet(document_id, sub_id) ON DELETE CASCADE
+);
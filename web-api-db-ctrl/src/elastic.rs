@@ -119,6 +119,18 @@ fn check_mapping_compatibility(
     let existing_embeddings = &existing_mapping[MAPPINGS][PROPERTIES][EMBEDDING];
     let expected_embeddings = &base_mapping[MAPPINGS][PROPERTIES][EMBEDDING];
     if existing_embeddings != expected_embeddings {
+        // Hint: different dims are reported per tenant by the embedding dims verification, e.g. a
+        // tenant might be re-embedded into a new index with the dims of its new model
+        let without_dims = |embeddings: &Value| {
+            let mut embeddings = embeddings.clone();
+            if let Some(embeddings) = embeddings.as_object_mut() {
+                embeddings.remove("dims");
+            }
+            embeddings
+        };
+        if without_dims(existing_embeddings) == without_dims(expected_embeddings) {
+            return Ok(());
+        }
         error!({ %existing_embeddings, %expected_embeddings }, "mappings in ES have incompatible embedding definition");
         bail!("incompatible existing elasticsearch index");
     }
@@ -147,6 +159,27 @@ pub(crate) async fn does_index_exist(
         .is_some())
 }
 
+/// Returns the dims of the embedding `dense_vector` of the index, if the index exists.
+#[instrument(skip(elastic))]
+pub(crate) async fn get_embedding_dims(
+    elastic: &ClientWithoutIndex,
+    index: &str,
+) -> Result<Option<usize>, Error> {
+    let Some(mapping) = get_opt_tenant_mapping(&elastic.with_index(index)).await? else {
+        return Ok(None);
+    };
+    match embedding_dims(&mapping) {
+        Some(dims) => Ok(Some(dims)),
+        None => bail!("unexpected ES mapping structure can't get embedding.dims"),
+    }
+}
+
+fn embedding_dims(mapping: &Value) -> Option<usize> {
+    mapping[MAPPINGS][PROPERTIES][EMBEDDING]["dims"]
+        .as_u64()
+        .and_then(|dims| dims.try_into().ok())
+}
+
 #[instrument(skip(elastic))]
 async fn get_opt_tenant_mapping(elastic: &Client) -> Result<Option<Value>, Error> {
     let response = elastic
@@ -201,6 +234,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_embedding_dims_are_read_from_mapping() {
+        let mapping = mapping_with_embedding_size(&MAPPING, 4321).unwrap();
+        assert_eq!(embedding_dims(&mapping), Some(4321));
+        assert_eq!(embedding_dims(&json!({})), None);
+    }
+
+    #[test]
+    fn test_mapping_with_other_dims_is_compatible() {
+        let existing = mapping_with_embedding_size(&MAPPING, 128).unwrap();
+        let expected = mapping_with_embedding_size(&MAPPING, 384).unwrap();
+        assert!(check_mapping_compatibility(&existing, &expected).is_ok());
+
+        let mut existing = existing;
+        existing[MAPPINGS][PROPERTIES][EMBEDDING]["similarity"] = json!("cosine");
+        assert!(check_mapping_compatibility(&existing, &expected).is_err());
+    }
+
     #[test]
     fn test_snippet_has_a_mapping() {
         let result = mapping_with_embedding_size(&MAPPING, 128).unwrap();
@@ -22,13 +22,15 @@ pub mod postgres;
 mod postgres;
 pub mod tenant;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 use anyhow::{anyhow, bail};
 pub use elastic::create_tenant_index as elastic_create_tenant;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use sqlx::pool::PoolOptions;
 use tenant::{Tenant, TenantWithOptionals};
+use tracing::error;
 use xayn_web_api_shared::{
     elastic::{ClientWithoutIndex as EsClient, Config as EsConfig},
     postgres::{Client as PgClient, Config as PgConfig},
@@ -53,6 +55,45 @@ pub struct LegacyTenantInfo {
     pub es_index: String,
 }
 
+/// The embedding dims of all components of a tenant.
+///
+/// All dims must agree, otherwise similarity scores are meaningless.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingDims {
+    pub tenant_id: TenantId,
+    pub model: String,
+    /// The dims of the tenants model, `None` if the model isn't known to this deployment.
+    pub model_dims: Option<usize>,
+    /// The dims of the es `dense_vector`, `None` if the index doesn't exist.
+    pub es_index_dims: Option<usize>,
+    /// The distinct dims of the stored cois.
+    pub coi_dims: Vec<usize>,
+}
+
+impl EmbeddingDims {
+    /// Checks if all known dims agree.
+    ///
+    /// The model of the tenant might not be configured in this deployment, then only the dims of
+    /// the es index and the cois are compared.
+    pub fn is_consistent(&self) -> bool {
+        self.model_dims
+            .iter()
+            .chain(&self.es_index_dims)
+            .chain(&self.coi_dims)
+            .all_equal()
+    }
+}
+
+impl fmt::Display for EmbeddingDims {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tenant {}: model {} has {:?} dims, es index has {:?} dims, cois have {:?} dims",
+            self.tenant_id, self.model, self.model_dims, self.es_index_dims, self.coi_dims,
+        )
+    }
+}
+
 impl Silo {
     pub async fn new(
         postgres_config: PgConfig,
@@ -102,6 +143,60 @@ impl Silo {
         postgres::initialize(&self.postgres, opt_legacy_setup, migrate_tenant).await
     }
 
+    /// Reports the embedding dims of the model, es index and cois for all tenants.
+    ///
+    /// This scans the cois of all tenants.
+    pub async fn embedding_dims(&self) -> Result<Vec<EmbeddingDims>, Error> {
+        let mut report = Vec::new();
+        for tenant in self.list_tenants().await? {
+            let coi_dims = postgres::coi_embedding_dims(&self.postgres, &tenant.tenant_id).await?;
+            report.push(self.tenant_embedding_dims(tenant, coi_dims).await?);
+        }
+        Ok(report)
+    }
+
+    /// Reports the tenants whose model, es index and coi dims don't agree.
+    ///
+    /// The cois aren't scanned, only one coi per tenant is sampled. All dims of the cois are only
+    /// part of the full [`Self::embedding_dims()`] report. The tenant which is re-embedded is skipped, because its current index still has the dims of
+    /// its previous model until it is switched to the new index.
+    pub async fn verify_embedding_dims(
+        &self,
+        reembedded: Option<&TenantId>,
+    ) -> Result<Vec<EmbeddingDims>, Error> {
+        let mut mismatches = Vec::new();
+        for tenant in self.list_tenants().await? {
+            if reembedded == Some(&tenant.tenant_id) {
+                continue;
+            }
+            let coi_dims =
+                postgres::sample_coi_embedding_dims(&self.postgres, &tenant.tenant_id).await?;
+            let dims = self.tenant_embedding_dims(tenant, coi_dims).await?;
+            if !dims.is_consistent() {
+                error!(%dims, "mismatching embedding dims");
+                mismatches.push(dims);
+            }
+        }
+        Ok(mismatches)
+    }
+
+    async fn tenant_embedding_dims(
+        &self,
+        tenant: Tenant,
+        coi_dims: Vec<usize>,
+    ) -> Result<EmbeddingDims, Error> {
+        let es_index_dims =
+            elastic::get_embedding_dims(&self.elastic, &tenant.es_index_name).await?;
+
+        Ok(EmbeddingDims {
+            model_dims: self.embedding_sizes.get(&tenant.model).copied(),
+            tenant_id: tenant.tenant_id,
+            model: tenant.model,
+            es_index_dims,
+            coi_dims,
+        })
+    }
+
     pub async fn admin_as_mt_user_hack(&self) -> Result<(), Error> {
         postgres::admin_as_mt_user_hack(&self.postgres).await
     }
@@ -145,6 +240,18 @@ impl Silo {
         Ok(())
     }
 
+    /// Creates the es index of the tenant if it doesn't exist yet.
+    ///
+    /// The tenant isn't changed, this can be used to prepare an index
+    /// before switching to it with [`Self::change_es_index()`].
+    pub async fn create_es_index_if_missing(&self, tenant: &Tenant) -> Result<(), Error> {
+        if !elastic::does_index_exist(&self.elastic, &tenant.es_index_name).await? {
+            let embedding_size = self.embedding_size_for(tenant)?;
+            elastic::create_tenant_index(&self.elastic, tenant, embedding_size).await?;
+        }
+        Ok(())
+    }
+
     pub async fn run_operations(
         &self,
         initialize: bool,
@@ -196,6 +303,13 @@ impl Silo {
                 .unwrap_or_else(|err| OperationResult::Error {
                     msg: err.to_string(),
                 }),
+            Operation::EmbeddingDims {} => self
+                .embedding_dims()
+                .await
+                .map(|tenants| OperationResult::EmbeddingDims { tenants })
+                .unwrap_or_else(|err| OperationResult::Error {
+                    msg: err.to_string(),
+                }),
         }
     }
 
@@ -240,6 +354,7 @@ pub enum Operation {
     DeleteTenant {
         tenant_id: TenantId,
     },
+    EmbeddingDims {},
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -247,6 +362,45 @@ pub enum OperationResult {
     ListTenants { tenants: Vec<Tenant> },
     CreateTenant { tenant: Tenant },
     DeleteTenant { tenant: Option<Tenant> },
+    EmbeddingDims { tenants: Vec<EmbeddingDims> },
     Success,
     Error { msg: String },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedding_dims_consistency() {
+        let dims = EmbeddingDims {
+            tenant_id: TenantId::random_legacy_tenant_id(),
+            model: "model".into(),
+            model_dims: Some(128),
+            es_index_dims: Some(128),
+            coi_dims: vec![128],
+        };
+        assert!(dims.is_consistent());
+        assert!(!EmbeddingDims {
+            es_index_dims: Some(384),
+            ..dims.clone()
+        }
+        .is_consistent());
+        assert!(!EmbeddingDims {
+            coi_dims: vec![128, 384],
+            ..dims.clone()
+        }
+        .is_consistent());
+        assert!(EmbeddingDims {
+            model_dims: None,
+            ..dims.clone()
+        }
+        .is_consistent());
+        assert!(!EmbeddingDims {
+            model_dims: None,
+            coi_dims: vec![384],
+            ..dims
+        }
+        .is_consistent());
+    }
+}
@@ -602,6 +602,56 @@ fn generate_tenant_lock_id(tenant_id: &TenantId) -> i64 {
     }
 }
 
+/// Returns the distinct dims of the stored coi embeddings of the tenant.
+#[instrument(skip(pool), err)]
+pub(super) async fn coi_embedding_dims(
+    pool: &Pool<Postgres>,
+    tenant_id: &TenantId,
+) -> Result<Vec<usize>, Error> {
+    let tenant = QuotedIdentifier::db_name_for_tenant_id(tenant_id);
+
+    //Hint: $ binds won't work for identifiers (e.g. schema names)
+    let query = format!(
+        "SELECT DISTINCT array_length(embedding, 1)
+        FROM {tenant}.center_of_interest;"
+    );
+
+    sqlx::query_as::<_, (Option<i32>,)>(&query)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .filter_map(|(dims,)| dims)
+        .sorted_unstable()
+        .map(|dims| usize::try_from(dims).map_err(Into::into))
+        .collect()
+}
+
+/// Returns the dims of an arbitrary stored coi embedding of the tenant, if there are any cois.
+///
+/// This is a cheap sample compared to [`coi_embedding_dims()`], which scans all cois.
+#[instrument(skip(pool), err)]
+pub(super) async fn sample_coi_embedding_dims(
+    pool: &Pool<Postgres>,
+    tenant_id: &TenantId,
+) -> Result<Vec<usize>, Error> {
+    let tenant = QuotedIdentifier::db_name_for_tenant_id(tenant_id);
+
+    //Hint: $ binds won't work for identifiers (e.g. schema names)
+    let query = format!(
+        "SELECT array_length(embedding, 1)
+        FROM {tenant}.center_of_interest
+        LIMIT 1;"
+    );
+
+    sqlx::query_as::<_, (Option<i32>,)>(&query)
+        .fetch_optional(pool)
+        .await?
+        .and_then(|(dims,)| dims)
+        .map(|dims| usize::try_from(dims).map_err(Into::into))
+        .into_iter()
+        .collect()
+}
+
 pub(crate) async fn change_es_index(
     tx: &mut Transaction<'_, Postgres>,
     tenant_id: &TenantId,
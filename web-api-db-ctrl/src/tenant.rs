@@ -14,7 +14,7 @@
 
 use anyhow::{anyhow, Error};
 use serde::{Deserialize, Serialize};
-use sqlx::PgConnection;
+use sqlx::{PgConnection, Postgres, Transaction};
 use xayn_web_api_shared::request::TenantId;
 
 //Hint: Silo API stability: This is currently directly serialized and returned from the /silo_management API.
@@ -86,4 +86,16 @@ impl Tenant {
         }
         .into())
     }
+
+    /// Switches the tenant to a different es index.
+    ///
+    /// The index must already exist. This allows to switch as part of a larger transaction, e.g.
+    /// together with updating the documents of the tenant.
+    pub async fn change_es_index(
+        tx: &mut Transaction<'_, Postgres>,
+        tenant_id: &TenantId,
+        es_index_name: String,
+    ) -> Result<(), Error> {
+        crate::postgres::change_es_index(tx, tenant_id, es_index_name).await
+    }
 }
@@ -17,6 +17,7 @@ use std::{
     fmt::{Debug, Display},
     future::Future,
     hash::Hash,
+    ops::Range,
     str::FromStr,
     sync::Arc,
     time::Duration,
@@ -24,6 +25,7 @@ use std::{
 
 use bytes::Bytes;
 use derive_more::From;
+use futures_util::{stream, StreamExt};
 use itertools::Itertools;
 use reqwest::{
     header::{HeaderMap, HeaderValue, CONTENT_TYPE},
@@ -45,7 +47,12 @@ use thiserror::Error;
 use tracing::{error, warn};
 
 use crate::{
-    net::{ExponentialJitterRetryPolicy, ExponentialJitterRetryPolicyConfig},
+    net::{
+        CircuitBreaker,
+        CircuitBreakerConfig,
+        ExponentialJitterRetryPolicy,
+        ExponentialJitterRetryPolicyConfig,
+    },
     serde::{serde_duration_as_seconds, serialize_redacted, serialize_to_ndjson, JsonObject},
 };
 
@@ -66,7 +73,22 @@ pub struct Config {
     /// The retry policy for internal requests to elastic search.
     pub retry_policy: ExponentialJitterRetryPolicyConfig,
 
+    /// The circuit breaker for requests to elastic search.
+    pub circuit_breaker: CircuitBreakerConfig,
+
     pub default_request_per_second: usize,
+
+    /// Max number of operations per bulk request.
+    pub bulk_max_operations: usize,
+
+    /// Max number of bytes per bulk request.
+    pub bulk_max_bytes: usize,
+
+    /// Max number of bulk requests which are sent concurrently.
+    pub bulk_max_concurrency: usize,
+
+    /// Max number of excluded documents which are part of a search request.
+    pub max_excluded_documents: usize,
 }
 
 impl Default for Config {
@@ -82,7 +104,12 @@ impl Default for Config {
                 step_size: Duration::from_millis(300),
                 max_backoff: Duration::from_millis(1000),
             },
+            circuit_breaker: CircuitBreakerConfig::default(),
             default_request_per_second: 500,
+            bulk_max_operations: 1000,
+            bulk_max_bytes: 10 * 1024 * 1024,
+            bulk_max_concurrency: 2,
+            max_excluded_documents: 1000,
         }
     }
 }
@@ -119,7 +146,12 @@ pub struct Client {
     url_to_index: Arc<SegmentableUrl>,
     client: reqwest::Client,
     retry_policy: ExponentialJitterRetryPolicyConfig,
+    circuit_breaker: Arc<CircuitBreaker>,
     default_request_per_second: usize,
+    bulk_max_operations: usize,
+    bulk_max_bytes: usize,
+    bulk_max_concurrency: usize,
+    max_excluded_documents: usize,
 }
 
 impl Client {
@@ -131,7 +163,12 @@ impl Client {
             index_name,
             timeout,
             retry_policy,
+            circuit_breaker,
             default_request_per_second,
+            bulk_max_operations,
+            bulk_max_bytes,
+            bulk_max_concurrency,
+            max_excluded_documents,
         } = config;
         Ok(Self {
             auth: Auth { user, password }.into(),
@@ -141,7 +178,12 @@ impl Client {
                 .into(),
             client: reqwest::ClientBuilder::new().timeout(timeout).build()?,
             retry_policy,
+            circuit_breaker: CircuitBreaker::new(circuit_breaker).into(),
             default_request_per_second,
+            bulk_max_operations: bulk_max_operations.max(1),
+            bulk_max_bytes,
+            bulk_max_concurrency: bulk_max_concurrency.max(1),
+            max_excluded_documents,
         })
     }
 
@@ -172,7 +214,12 @@ impl Client {
                 .into(),
             client: self.client.clone(),
             retry_policy: self.retry_policy.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
             default_request_per_second: self.default_request_per_second,
+            bulk_max_operations: self.bulk_max_operations,
+            bulk_max_bytes: self.bulk_max_bytes,
+            bulk_max_concurrency: self.bulk_max_concurrency,
+            max_excluded_documents: self.max_excluded_documents,
         }
     }
 
@@ -180,6 +227,10 @@ impl Client {
         self.default_request_per_second
     }
 
+    pub fn max_excluded_documents(&self) -> usize {
+        self.max_excluded_documents
+    }
+
     pub fn get_index(&self) -> &str {
         self.url_to_index.last_segment().unwrap(/*Client always has some index*/)
     }
@@ -262,6 +313,39 @@ pub struct BulkResponse<I> {
     pub items: Vec<HashMap<String, BulkItemResponse<I>>>,
 }
 
+/// The response of a chunk of a chunked bulk request.
+#[derive(Debug)]
+pub struct BulkChunkResponse<I> {
+    /// The indices of the operations in the chunk.
+    pub operations: Range<usize>,
+    pub response: Result<BulkResponse<I>, Error>,
+}
+
+/// Chunks the serialized operations wrt the max number of operations and bytes per chunk.
+///
+/// Returns the indices of the operations of each chunk together with the chunk.
+fn chunk_operations(
+    operations: impl IntoIterator<Item = Vec<u8>>,
+    max_operations: usize,
+    max_bytes: usize,
+) -> Vec<(Range<usize>, Vec<u8>)> {
+    let mut chunks = Vec::<(Range<usize>, Vec<u8>)>::new();
+    for (idx, operation) in operations.into_iter().enumerate() {
+        match chunks.last_mut() {
+            Some((operations, chunk))
+                if operations.len() < max_operations
+                    && chunk.len() + operation.len() <= max_bytes =>
+            {
+                chunk.extend(operation);
+                operations.end = idx + 1;
+            }
+            _ => chunks.push((idx..idx + 1, operation)),
+        }
+    }
+
+    chunks
+}
+
 impl<I> BulkResponse<I> {
     pub fn failed_documents(self, allow_not_found: bool, expected_result: &'static str) -> Vec<I>
     where
@@ -469,6 +553,55 @@ impl Client {
             .await
     }
 
+    /// Sends the operations in chunked bulk requests.
+    ///
+    /// Each operation consists of the instruction and its optional data. The operations are
+    /// chunked wrt the configured max number of operations and bytes per bulk request, where a
+    /// single operation exceeding the max number of bytes is sent on its own. The responses of
+    /// all chunks are returned, a failed chunk doesn't stop the other chunks.
+    pub async fn chunked_bulk_request<I>(
+        &self,
+        operations: impl IntoIterator<
+            Item = impl IntoIterator<Item = Result<impl Serialize, serde_json::Error>>,
+        >,
+    ) -> Result<Vec<BulkChunkResponse<I>>, Error>
+    where
+        I: DeserializeOwned,
+    {
+        let operations = operations
+            .into_iter()
+            .map(serialize_to_ndjson)
+            .try_collect::<_, Vec<_>, _>()?;
+        let chunks = chunk_operations(operations, self.bulk_max_operations, self.bulk_max_bytes);
+
+        let url = self.create_url(["_bulk"], [("refresh", None)]);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-ndjson"),
+        );
+
+        let responses = stream::iter(chunks)
+            .map(|(operations, chunk)| {
+                let response = self.query_with_bytes::<BulkResponse<I>>(
+                    Method::POST,
+                    url.clone(),
+                    Some((headers.clone(), chunk.into())),
+                );
+                async move {
+                    BulkChunkResponse {
+                        operations,
+                        response: response.await,
+                    }
+                }
+            })
+            .buffered(self.bulk_max_concurrency)
+            .collect()
+            .await;
+
+        Ok(responses)
+    }
+
     pub async fn search_request<F, I, E>(
         &self,
         mut body: JsonObject,
@@ -510,17 +643,26 @@ impl Client {
     where
         T: DeserializeOwned,
     {
-        self.retry(
-            |err| matches!(err, Error::Transport(_)),
-            || async {
-                let method = method.clone();
-                let url = url.clone();
-                let post_data = post_data.clone();
-                self.query_with_bytes_without_retrying(method, url, post_data)
-                    .await
-            },
-        )
-        .await
+        self.circuit_breaker
+            .check()
+            .map_err(|retry_after| Error::Unavailable { retry_after })?;
+
+        let result = self
+            .retry(
+                |err| matches!(err, Error::Transport(_)),
+                || async {
+                    let method = method.clone();
+                    let url = url.clone();
+                    let post_data = post_data.clone();
+                    self.query_with_bytes_without_retrying(method, url, post_data)
+                        .await
+                },
+            )
+            .await;
+        self.circuit_breaker
+            .record(!matches!(&result, Err(error) if error.is_server_failure()));
+
+        result
     }
 
     async fn query_with_bytes_without_retrying<B, T>(
@@ -594,6 +736,19 @@ pub enum Error {
     Serialization(serde_json::Error),
     /// Given resource was not found: {0}
     ResourceNotFound(String),
+    /// Elastic Search is unavailable, retry after {retry_after:?}
+    Unavailable { retry_after: Duration },
+}
+
+impl Error {
+    /// Checks if the error indicates a degraded elastic search.
+    fn is_server_failure(&self) -> bool {
+        match self {
+            Self::Transport(_) => true,
+            Self::Status { status, .. } => status.is_server_error(),
+            Self::Serialization(_) | Self::ResourceNotFound(_) | Self::Unavailable { .. } => false,
+        }
+    }
 }
 
 pub trait NotFoundAsOptionExt<T> {
@@ -657,3 +812,54 @@ impl FromStr for SegmentableUrl {
         s.parse::<Url>()?.try_into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk_lengths(
+        operations: &[&str],
+        max_operations: usize,
+        max_bytes: usize,
+    ) -> Vec<(Range<usize>, usize)> {
+        chunk_operations(
+            operations
+                .iter()
+                .map(|operation| operation.as_bytes().to_vec()),
+            max_operations,
+            max_bytes,
+        )
+        .into_iter()
+        .map(|(operations, chunk)| (operations, chunk.len()))
+        .collect()
+    }
+
+    #[test]
+    fn test_chunk_operations_empty() {
+        assert!(chunk_lengths(&[], 2, 10).is_empty());
+    }
+
+    #[test]
+    fn test_chunk_operations_by_count() {
+        assert_eq!(
+            chunk_lengths(&["a", "b", "c", "d", "e"], 2, 10),
+            [(0..2, 2), (2..4, 2), (4..5, 1)],
+        );
+    }
+
+    #[test]
+    fn test_chunk_operations_by_bytes() {
+        assert_eq!(
+            chunk_lengths(&["aaa", "bbb", "cc", "dddddd", "e"], 10, 6),
+            [(0..2, 6), (2..3, 2), (3..4, 6), (4..5, 1)],
+        );
+    }
+
+    #[test]
+    fn test_chunk_operations_exceeding_bytes() {
+        assert_eq!(
+            chunk_lengths(&["a", "bbbbbbbb", "c"], 10, 4),
+            [(0..1, 1), (1..2, 8), (2..3, 1)],
+        );
+    }
+}
@@ -18,7 +18,8 @@ use std::{
     fmt::{Debug, Display},
     future::Future,
     ops::{ControlFlow, Mul},
-    time::Duration,
+    sync::Mutex,
+    time::{Duration, Instant},
 };
 
 use derive_more::Deref;
@@ -30,7 +31,7 @@ use rand::random;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
-use crate::serde::serde_duration_in_config;
+use crate::serde::{serde_duration_as_seconds, serde_duration_in_config};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(test, serde(deny_unknown_fields))]
@@ -118,3 +119,125 @@ where
         ControlFlow::Break(result)
     }
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub struct CircuitBreakerConfig {
+    /// Number of consecutive failed requests after which the circuit opens, `0` disables it.
+    pub failure_threshold: usize,
+
+    /// Duration in seconds for which requests fail fast once the circuit is open.
+    #[serde(with = "serde_duration_as_seconds")]
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CircuitState {
+    failures: usize,
+    open_until: Option<Instant>,
+}
+
+/// Fails requests fast while a service is degraded.
+///
+/// The circuit opens after the configured number of consecutive failures. Once the open duration
+/// has passed requests are let through again, the next failure reopens the circuit immediately
+/// while the next success closes it.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<CircuitState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Checks if a request can be sent, otherwise returns the remaining open duration.
+    pub fn check(&self) -> Result<(), Duration> {
+        let state = self.state.lock().unwrap(/* never poisoned */);
+        match state.open_until {
+            Some(open_until) if open_until > Instant::now() => {
+                Err(open_until.duration_since(Instant::now()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Records the outcome of a request.
+    pub fn record(&self, success: bool) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap(/* never poisoned */);
+        if success {
+            *state = CircuitState::default();
+        } else {
+            state.failures += 1;
+            if state.failures >= self.config.failure_threshold {
+                if state.open_until.is_none() {
+                    warn!(failures = state.failures, "circuit opened");
+                }
+                state.open_until = Some(Instant::now() + self.config.open_duration);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker_opens_after_threshold() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_duration: Duration::from_secs(60),
+        });
+        assert!(breaker.check().is_ok());
+        breaker.record(false);
+        assert!(breaker.check().is_ok());
+        breaker.record(false);
+        assert!(breaker.check().is_err());
+    }
+
+    #[test]
+    fn test_circuit_breaker_closes_on_success() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_duration: Duration::ZERO,
+        });
+        breaker.record(false);
+        breaker.record(false);
+        assert!(breaker.check().is_ok());
+        breaker.record(true);
+        breaker.record(false);
+        assert!(breaker.check().is_ok());
+    }
+
+    #[test]
+    fn test_circuit_breaker_disabled() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 0,
+            open_duration: Duration::from_secs(60),
+        });
+        for _ in 0..10 {
+            breaker.record(false);
+        }
+        assert!(breaker.check().is_ok());
+    }
+}
@@ -12,7 +12,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, str::FromStr, time::Duration};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -21,7 +21,10 @@ use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgConnectOptions, Pool, Postgres, Type};
 use thiserror::Error;
 
-use crate::{request::TenantId, serde::serialize_redacted};
+use crate::{
+    request::TenantId,
+    serde::{serde_duration_in_config, serialize_redacted},
+};
 
 pub type Client = Pool<Postgres>;
 
@@ -35,6 +38,12 @@ pub struct Config {
     /// db password with the db url.
     pub base_url: String,
 
+    /// The base url of an optional read replica.
+    ///
+    /// If set, read-only user state queries are sent to the replica. The overrides below apply
+    /// to the replica url as well.
+    pub replica_url: Option<String>,
+
     /// Override port from base url.
     pub port: Option<u16>,
 
@@ -61,12 +70,25 @@ pub struct Config {
 
     /// Maximum number of connections in the pool.
     pub max_pool_size: u8,
+
+    /// Maximum time to wait for a connection from the pool.
+    #[serde(with = "serde_duration_in_config")]
+    pub acquire_timeout: Duration,
+
+    /// Time after which idle connections above `min_pool_size` are closed, `0s` keeps them open.
+    #[serde(with = "serde_duration_in_config")]
+    pub idle_timeout: Duration,
+
+    /// Maximum time a single statement may run, `0s` disables the timeout.
+    #[serde(with = "serde_duration_in_config")]
+    pub statement_timeout: Duration,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             base_url: "postgres://user:pw@localhost:5432/xayn".into(),
+            replica_url: None,
             port: None,
             user: None,
             password: String::from("pw").into(),
@@ -75,14 +97,27 @@ impl Default for Config {
             skip_migrations: false,
             min_pool_size: 0,
             max_pool_size: 25,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(600),
+            statement_timeout: Duration::ZERO,
         }
     }
 }
 
 impl Config {
     pub fn to_connection_options(&self) -> Result<PgConnectOptions, sqlx::Error> {
+        self.to_connection_options_for(&self.base_url)
+    }
+
+    /// Creates the connection options for the read replica, if one is configured.
+    pub fn to_replica_connection_options(&self) -> Option<Result<PgConnectOptions, sqlx::Error>> {
+        self.replica_url
+            .as_deref()
+            .map(|replica_url| self.to_connection_options_for(replica_url))
+    }
+
+    fn to_connection_options_for(&self, base_url: &str) -> Result<PgConnectOptions, sqlx::Error> {
         let Self {
-            base_url,
             port,
             user,
             password,
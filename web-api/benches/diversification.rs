@@ -0,0 +1,69 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+mod utils;
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use rand::{rngs::SmallRng, SeedableRng};
+use xayn_web_api::bench_diversify;
+
+use crate::utils::random_documents;
+
+macro_rules! bench_diversify {
+    ($(
+        $function: ident,
+        $embedding_size: expr,
+        $document_size: expr,
+        $lambda: expr
+    );+ $(;)?) => {$(
+        fn $function(c: &mut Criterion) {
+            let mut rng = SmallRng::from_entropy();
+            let documents = random_documents(&mut rng, $document_size, $embedding_size);
+
+            let name = format!(
+                "diversify {} documents with lambda {} (embedding size: {})",
+                $document_size,
+                $lambda,
+                $embedding_size,
+            );
+            c.bench_function(
+                &name,
+                |b| b.iter_batched(
+                    || documents.clone(),
+                    |documents| bench_diversify(black_box(documents), black_box($lambda)),
+                    BatchSize::SmallInput,
+                ),
+            );
+        }
+    )+};
+}
+
+bench_diversify! {
+    bench_diversify_128_100_05, 128, 100, 0.5;
+    bench_diversify_128_250_05, 128, 250, 0.5;
+    bench_diversify_128_500_05, 128, 500, 0.5;
+    bench_diversify_128_1000_05, 128, 1000, 0.5;
+}
+
+criterion_group!(
+    diversify_with_lambda_05,
+    bench_diversify_128_100_05,
+    bench_diversify_128_250_05,
+    bench_diversify_128_500_05,
+    bench_diversify_128_1000_05,
+);
+
+criterion_main!(diversify_with_lambda_05);
@@ -0,0 +1,49 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Synthetic data for the benchmarks.
+
+use itertools::Itertools;
+use rand::{
+    distributions::{Distribution, Uniform},
+    Rng,
+};
+use xayn_ai_bert::{Embedding1, NormalizedEmbedding};
+
+/// Creates a random normalized embedding.
+pub(crate) fn random_embedding(rng: &mut impl Rng, embedding_size: usize) -> NormalizedEmbedding {
+    Embedding1::from(
+        Uniform::new_inclusive(-1.0, 1.0)
+            .sample_iter(rng)
+            .take(embedding_size)
+            .collect_vec(),
+    )
+    .normalize()
+    .unwrap()
+}
+
+/// Creates random documents with normalized embeddings and scores in `[0, 1]`.
+pub(crate) fn random_documents(
+    rng: &mut impl Rng,
+    document_size: usize,
+    embedding_size: usize,
+) -> Vec<(NormalizedEmbedding, f32)> {
+    let scores = Uniform::new_inclusive(0.0, 1.0);
+    (0..document_size)
+        .map(|_| {
+            let embedding = random_embedding(rng, embedding_size);
+            (embedding, scores.sample(rng))
+        })
+        .collect()
+}
@@ -0,0 +1,36 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{env, error::Error, fs, path::PathBuf};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Hint: use the vendored protoc so that building doesn't require a system installation
+    env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    env::set_var("PROTOC_INCLUDE", protoc_bin_vendored::include_path()?);
+
+    let descriptors = PathBuf::from(env::var("OUT_DIR")?).join("web_api_descriptors.bin");
+    tonic_build::configure()
+        .file_descriptor_set_path(&descriptors)
+        .compile_well_known_types(true)
+        .extern_path(".google.protobuf", "::pbjson_types")
+        .compile(&["proto/web_api.proto"], &["proto"])?;
+    // Hint: the messages are converted to and from the models of the REST api through their json
+    // mapping, the proto field names are kept to match the json fields of the models
+    pbjson_build::Builder::new()
+        .register_descriptors(&fs::read(descriptors)?)?
+        .preserve_proto_field_names()
+        .build(&[".xayn.web_api.v1"])?;
+
+    Ok(())
+}
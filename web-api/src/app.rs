@@ -14,22 +14,31 @@
 
 mod state;
 
-use std::{env::current_dir, fmt::Debug, path::PathBuf, sync::Arc};
+use std::{env::current_dir, fmt::Debug, path::PathBuf, sync::Arc, time::Duration};
 
-use actix_web::web::ServiceConfig;
+use actix_web::{
+    web::{self, Data, Json, ServiceConfig},
+    HttpResponse,
+    Responder,
+};
 use async_trait::async_trait;
 use futures_util::FutureExt;
 use serde::{de::DeserializeOwned, Serialize};
+use tokio_util::task::LocalPoolHandle;
 use tracing::{info, instrument};
 
 pub(crate) use self::state::{AppState, TenantState};
 use crate::{
-    config::Config,
+    backoffice,
+    config::{Config, ReembedArgs},
+    embedding::Models,
     extractor,
+    grpc,
     logging,
     net::{self, AppHandle},
-    storage,
+    storage::{self, initialize_silo},
     tenants,
+    Error,
 };
 
 #[async_trait]
@@ -80,20 +89,148 @@ where
     let net_config = net::Config::clone(config.as_ref());
     let app_state = Arc::new(AppState::create(config).await?);
     let legacy_tenant = app_state.legacy_tenant().cloned();
+    #[cfg(unix)]
+    reload_config_on_hangup(app_state.clone())?;
+    // Hint: storage futures are not `Send`, the background tasks are run on a single threaded
+    // runtime which is stopped on shutdown
+    let background = LocalPoolHandle::new(1);
+    prune_periodically(&background, app_state.clone());
+    reconcile_periodically(&background, app_state.clone());
 
     let shutdown = Box::new({
         let app_state = app_state.clone();
-        move || async { app_state.close().await }.boxed()
+        move || {
+            async move {
+                drop(background);
+                app_state.close().await;
+            }
+            .boxed()
+        }
     });
 
-    net::start_actix_server(
+    let grpc_state = app_state.clone();
+    let handle = net::start_actix_server(
         net_config,
         legacy_tenant,
         move |service| app_state.clone().attach_to(service),
         A::configure_service,
         A::configure_ops_service,
         shutdown,
+    )?;
+
+    if net_config.grpc.enabled {
+        let grpc = grpc::start(net_config.grpc, grpc_state).await?;
+        Ok(handle.with_grpc(grpc))
+    } else {
+        Ok(handle)
+    }
+}
+
+/// Runs the database migrations without starting the server.
+#[instrument(skip_all)]
+pub async fn migrate(config: Config) -> Result<(), SetupError> {
+    let models = Models::load(config.as_ref(), config.as_ref()).await?;
+    initialize_silo(
+        config.as_ref(),
+        config.as_ref(),
+        models.embedding_sizes(),
+        None,
     )
+    .await?;
+    info!("migrated the databases");
+
+    Ok(())
+}
+
+/// Re-embeds the documents of a tenant into a new index without starting the server.
+pub async fn reembed(config: Config, args: ReembedArgs) -> Result<(), SetupError> {
+    backoffice::reembed::reembed(&config, args).await
+}
+
+/// Reloads the config whenever the process receives a `SIGHUP`.
+#[cfg(unix)]
+fn reload_config_on_hangup(app_state: Arc<AppState>) -> Result<(), SetupError> {
+    use tokio::signal::unix::{signal, SignalKind};
+    use tracing::{error, instrument::WithSubscriber};
+
+    let mut hangup = signal(SignalKind::hangup())?;
+    tokio::spawn(
+        async move {
+            while hangup.recv().await.is_some() {
+                if let Err(error) = app_state.reload_config() {
+                    error!({ %error }, "failed to reload config");
+                }
+            }
+        }
+        .with_current_subscriber(),
+    );
+
+    Ok(())
+}
+
+/// Prunes the outdated interaction counts and idempotency keys of all tenants once per hour.
+fn prune_periodically(pool: &LocalPoolHandle, app_state: Arc<AppState>) {
+    use tracing::{error, instrument::WithSubscriber};
+
+    pool.spawn_pinned(move || {
+        async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+            loop {
+                interval.tick().await;
+                if let Err(error) = app_state.prune().await {
+                    error!({ %error }, "failed to prune outdated data");
+                }
+            }
+        }
+        .with_current_subscriber()
+    });
+}
+
+/// Syncs the pending documents of all tenants with elastic in the configured interval.
+fn reconcile_periodically(pool: &LocalPoolHandle, app_state: Arc<AppState>) {
+    use tracing::{error, instrument::WithSubscriber};
+
+    let period = app_state.config().ingestion.reconciliation_interval;
+    if period.is_zero() {
+        return;
+    }
+    pool.spawn_pinned(move || {
+        async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(error) = app_state.reconcile().await {
+                    error!({ %error }, "failed to reconcile pending documents");
+                }
+            }
+        }
+        .with_current_subscriber()
+    });
+}
+
+pub(crate) fn configure_ops_service(config: &mut ServiceConfig) {
+    config
+        .service(web::resource("/config/reload").route(web::post().to(reload_config)))
+        .service(web::resource("/metrics").route(web::get().to(metrics)));
+}
+
+#[instrument(skip(state))]
+async fn reload_config(state: Data<AppState>) -> Result<impl Responder, Error> {
+    state.reload_config()?;
+
+    Ok(HttpResponse::NoContent())
+}
+
+#[derive(Debug, Serialize)]
+struct MetricsResponse {
+    postgres_pool: storage::postgres::PoolMetrics,
+}
+
+#[allow(clippy::unused_async)]
+async fn metrics(state: Data<AppState>) -> impl Responder {
+    Json(MetricsResponse {
+        postgres_pool: state.postgres_pool_metrics(),
+    })
 }
 
 /// Generate application names/env prefixes for the given application.
@@ -12,7 +12,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError, RwLock};
 
 use actix_web::{
     dev::Payload,
@@ -20,8 +20,9 @@ use actix_web::{
     FromRequest,
     HttpRequest,
 };
-use derive_more::AsRef;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use futures_util::{future::BoxFuture, FutureExt};
+use tracing::{error, info};
 use xayn_ai_coi::CoiSystem;
 use xayn_snippet_extractor::pool::SnippetExtractorPool;
 use xayn_web_api_db_ctrl::Silo;
@@ -29,19 +30,20 @@ use xayn_web_api_shared::request::TenantId;
 
 use crate::{
     app::SetupError,
+    backoffice::routes::{IDEMPOTENCY_KEY_EXPIRATION, MAX_NUMBER_PENDING_DOCUMENTS},
     config::Config,
     embedding::{Embedder, Models},
-    error::common::InternalError,
+    error::common::{InternalError, NonReloadableConfigChanges},
     extractor::TextExtractor,
+    logging,
     middleware::request_context::RequestContext,
-    storage::{initialize_silo, Storage, StorageBuilder},
+    models::DocumentId,
+    storage::{self, initialize_silo, postgres, Storage, StorageBuilder, Warning},
     Error,
 };
 
-#[derive(AsRef)]
 pub(crate) struct AppState {
-    #[as_ref(forward)]
-    pub(crate) config: Config,
+    config: RwLock<Arc<Config>>,
     pub(crate) models: Models,
     pub(crate) extractor: TextExtractor,
     pub(crate) snippet_extractor: SnippetExtractorPool,
@@ -62,13 +64,18 @@ impl AppState {
     pub(super) async fn create(config: Config) -> Result<Self, SetupError> {
         let extractor = TextExtractor::new(config.as_ref())?;
         let models = Models::load(config.as_ref(), config.as_ref()).await?;
-        let (silo, legacy_tenant) =
-            initialize_silo(config.as_ref(), config.as_ref(), models.embedding_sizes()).await?;
+        let (silo, legacy_tenant) = initialize_silo(
+            config.as_ref(),
+            config.as_ref(),
+            models.embedding_sizes(),
+            None,
+        )
+        .await?;
         let storage_builder = Arc::new(Storage::builder(config.as_ref(), legacy_tenant).await?);
         let snippet_extractor = SnippetExtractorPool::new(config.as_ref())?;
         Ok(Self {
             coi: config.coi.clone().build(),
-            config,
+            config: RwLock::new(Arc::new(config)),
             models,
             extractor,
             snippet_extractor,
@@ -81,9 +88,111 @@ impl AppState {
         self.storage_builder.close().await;
     }
 
+    /// The current config.
+    ///
+    /// The reloadable sections of the config might change between calls.
+    pub(crate) fn config(&self) -> Arc<Config> {
+        self.config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Reloads the config and applies the changes of the reloadable sections.
+    ///
+    /// The reload is rejected if any other section changed.
+    pub(crate) fn reload_config(&self) -> Result<(), Error> {
+        // Hint: the config is loaded without holding the lock, requests keep reading the current
+        //       config meanwhile
+        let config = self.config();
+        let reloaded = config.reload().map_err(InternalError::from_anyhow)?;
+        let sections = config
+            .non_reloadable_changes(&reloaded)
+            .map_err(InternalError::from_anyhow)?;
+        if !sections.is_empty() {
+            return Err(NonReloadableConfigChanges { sections }.into());
+        }
+
+        logging::reload_level(reloaded.logging.level).map_err(InternalError::from_std)?;
+        *self.config.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(reloaded);
+        info!("reloaded config");
+
+        Ok(())
+    }
+
+    /// Extracts the tenant specific state outside of an actix request.
+    pub(crate) async fn tenant_state(&self, tenant_id: TenantId) -> Result<TenantState, Error> {
+        TenantState::build(&self.storage_builder, &self.models, tenant_id).await
+    }
+
+    /// Deletes the interaction counts and idempotency keys of all tenants which are outdated.
+    ///
+    /// A failure for one tenant is logged and doesn't stop the pruning of the other tenants.
+    pub(crate) async fn prune(&self) -> Result<(), Error> {
+        let time = Utc::now();
+        let retention = self.config().personalization.interaction_count_retention;
+        let before = (time - Duration::days(retention.into())).date_naive();
+        let expiration = time - Duration::hours(IDEMPOTENCY_KEY_EXPIRATION);
+        for tenant in self.silo.list_tenants().await? {
+            let tenant_id = tenant.tenant_id;
+            if let Err(error) = self
+                .prune_tenant(tenant_id.clone(), before, expiration)
+                .await
+            {
+                error!(%tenant_id, %error, "failed to prune outdated data");
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn prune_tenant(
+        &self,
+        tenant_id: TenantId,
+        before: NaiveDate,
+        expiration: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let TenantState(storage, _) = self.tenant_state(tenant_id).await?;
+        storage::Interaction::prune_interaction_counts(&storage, before).await?;
+        storage::Idempotency::prune(&storage, expiration).await
+    }
+
+    /// Syncs the pending documents of all tenants with elastic.
+    ///
+    /// A failure for one tenant is logged and doesn't stop the reconciliation of the other tenants.
+    pub(crate) async fn reconcile(&self) -> Result<(), Error> {
+        // Hint: the elastic updates of recently pending documents might still be in progress
+        let before = Utc::now() - Duration::minutes(1);
+        for tenant in self.silo.list_tenants().await? {
+            let tenant_id = tenant.tenant_id;
+            match self.reconcile_tenant(tenant_id.clone(), before).await {
+                Ok(failed) if !failed.is_empty() => {
+                    error!(%tenant_id, "Failed to reconcile {} documents", failed.len());
+                }
+                Ok(_) => {}
+                Err(error) => error!(%tenant_id, %error, "failed to reconcile pending documents"),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn reconcile_tenant(
+        &self,
+        tenant_id: TenantId,
+        before: DateTime<Utc>,
+    ) -> Result<Warning<DocumentId>, Error> {
+        let TenantState(storage, _) = self.tenant_state(tenant_id).await?;
+        storage::Consistency::apply_pending(&storage, before, MAX_NUMBER_PENDING_DOCUMENTS).await
+    }
+
     pub(crate) fn legacy_tenant(&self) -> Option<&TenantId> {
         self.storage_builder.legacy_tenant()
     }
+
+    pub(crate) fn postgres_pool_metrics(&self) -> postgres::PoolMetrics {
+        self.storage_builder.postgres_pool_metrics()
+    }
 }
 
 /// Extract tenant specific state.
@@ -91,6 +200,25 @@ impl AppState {
 /// For now this only extracts storage.
 pub(crate) struct TenantState(pub(crate) Storage, pub(crate) Arc<Embedder>);
 
+impl TenantState {
+    async fn build(
+        builder: &StorageBuilder,
+        models: &Models,
+        tenant_id: TenantId,
+    ) -> Result<Self, Error> {
+        let storage = builder.build_for(tenant_id).await?;
+        let model = &storage.tenant().model;
+        if let Some(embedder) = models.get(model) {
+            Ok(TenantState(storage, embedder.clone()))
+        } else {
+            Err(InternalError::from_message(format!(
+                "deployment doesn't support tenants model: {model}"
+            ))
+            .into())
+        }
+    }
+}
+
 impl FromRequest for TenantState {
     type Error = Error;
 
@@ -107,16 +235,7 @@ impl FromRequest for TenantState {
         async move {
             match result {
                 Ok(Some((builder, models, tenant_id))) => {
-                    let storage = builder.build_for(tenant_id).await?;
-                    let model = &storage.tenant().model;
-                    if let Some(embedder) = models.get(model) {
-                        Ok(TenantState(storage, embedder.clone()))
-                    } else {
-                        Err(InternalError::from_message(format!(
-                            "deployment doesn't support tenants model: {model}"
-                        ))
-                        .into())
-                    }
+                    TenantState::build(&builder, &models, tenant_id).await
                 }
                 Ok(None) => Err(InternalError::from_message("Arc<StorageBuilder> missing").into()),
                 Err(error) => Err(InternalError::from_std(error).into()),
@@ -12,13 +12,19 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub(crate) mod moderation;
 pub(crate) mod preprocessor;
+pub(crate) mod reembed;
 pub(crate) mod routes;
 
+use std::time::Duration;
+
 use anyhow::bail;
 use serde::{Deserialize, Serialize};
+use xayn_web_api_shared::serde::serde_duration_as_seconds;
 
-use crate::{app::SetupError, storage::elastic::IndexUpdateConfig};
+use self::moderation::ModerationStage;
+use crate::{app::SetupError, models::DocumentTag, storage::elastic::IndexUpdateConfig};
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
@@ -30,6 +36,12 @@ pub struct IngestionConfig {
     pub(crate) max_snippet_size: usize,
     pub(crate) max_properties_size: usize,
     pub(crate) max_properties_string_size: usize,
+    pub(crate) duplicates: DuplicatesConfig,
+    /// The content moderation stages, applied in order.
+    pub(crate) moderation: Vec<ModerationStage>,
+    /// Interval in seconds in which pending documents are synced with elastic, 0 disables it.
+    #[serde(with = "serde_duration_as_seconds")]
+    pub(crate) reconciliation_interval: Duration,
 }
 
 impl Default for IngestionConfig {
@@ -42,6 +54,9 @@ impl Default for IngestionConfig {
             max_snippet_size: 2_048,
             max_properties_size: 2_560,
             max_properties_string_size: 2_048,
+            duplicates: DuplicatesConfig::default(),
+            moderation: Vec::new(),
+            reconciliation_interval: Duration::from_secs(5 * 60),
         }
     }
 }
@@ -52,11 +67,63 @@ impl IngestionConfig {
             bail!("invalid IngestionConfig, max_indexed_properties must be > 0 to account for publication_date");
         }
         self.index_update.validate()?;
+        self.duplicates.validate()?;
+        for stage in &self.moderation {
+            stage.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub(crate) struct DuplicatesConfig {
+    /// How to handle new documents which are near duplicates of existing documents.
+    pub(crate) policy: DuplicatePolicy,
+
+    /// Min cosine similarity of the embeddings for a document to be a near duplicate.
+    pub(crate) threshold: f32,
+
+    /// Tag which is added to near duplicates for the tag policy.
+    pub(crate) tag: DocumentTag,
+}
+
+impl Default for DuplicatesConfig {
+    fn default() -> Self {
+        Self {
+            policy: DuplicatePolicy::Allow,
+            threshold: 0.95,
+            tag: DocumentTag::new("duplicate").unwrap(/* valid tag */),
+        }
+    }
+}
+
+impl DuplicatesConfig {
+    fn validate(&self) -> Result<(), SetupError> {
+        if !(-1. ..=1.).contains(&self.threshold) {
+            bail!("invalid DuplicatesConfig, threshold must be in [-1, 1]");
+        }
+        if DocumentTag::new(self.tag.to_string()).is_err() {
+            bail!("invalid DuplicatesConfig, tag must be a valid document tag");
+        }
 
         Ok(())
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DuplicatePolicy {
+    /// Ingest near duplicates like any other document.
+    Allow,
+    /// Reject near duplicates as invalid documents.
+    Reject,
+    /// Ingest near duplicates with an additional tag.
+    Tag,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
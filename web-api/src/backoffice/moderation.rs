@@ -0,0 +1,278 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Content moderation of ingested documents.
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use crate::{app::SetupError, error::common::DocumentRejected, models::DocumentProperties};
+
+/// The parts of an ingested document which are moderated.
+pub(crate) struct ModeratedDocument<'a> {
+    /// The snippet, if the document isn't a file.
+    pub(crate) snippet: Option<&'a str>,
+    pub(crate) properties: &'a DocumentProperties,
+}
+
+impl ModeratedDocument<'_> {
+    fn property(&self, property: &str) -> Option<&Value> {
+        self.properties.get(property).map(|value| &**value)
+    }
+}
+
+/// A stage of the content moderation.
+pub(crate) trait Moderate {
+    /// Returns the reason if the document is rejected.
+    fn moderate(&self, document: &ModeratedDocument<'_>) -> Option<String>;
+}
+
+/// The moderation stages, they are applied in the configured order.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub(crate) enum ModerationStage {
+    Malformed(Malformed),
+    LanguageAllowList(LanguageAllowList),
+    BannedDomains(BannedDomains),
+}
+
+impl ModerationStage {
+    pub(crate) fn validate(&self) -> Result<(), SetupError> {
+        match self {
+            Self::Malformed(_) => {}
+            Self::LanguageAllowList(stage) => {
+                if stage.allowed.is_empty() {
+                    bail!("invalid ModerationStage, language allow list must not be empty");
+                }
+            }
+            Self::BannedDomains(stage) => {
+                if stage.domains.iter().any(String::is_empty) {
+                    bail!("invalid ModerationStage, banned domains must not be empty");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Malformed(_) => "malformed",
+            Self::LanguageAllowList(_) => "language_allow_list",
+            Self::BannedDomains(_) => "banned_domains",
+        }
+    }
+
+    fn as_stage(&self) -> &dyn Moderate {
+        match self {
+            Self::Malformed(stage) => stage,
+            Self::LanguageAllowList(stage) => stage,
+            Self::BannedDomains(stage) => stage,
+        }
+    }
+}
+
+/// Applies the stages in order and rejects the document at the first failing stage.
+pub(crate) fn moderate(
+    stages: &[ModerationStage],
+    document: &ModeratedDocument<'_>,
+) -> Result<(), DocumentRejected> {
+    for stage in stages {
+        if let Some(reason) = stage.as_stage().moderate(document) {
+            return Err(DocumentRejected {
+                stage: stage.name(),
+                reason,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Moderates the updated properties of an ingested document, its snippet is unchanged.
+pub(crate) fn moderate_properties(
+    stages: &[ModerationStage],
+    properties: &DocumentProperties,
+) -> Result<(), DocumentRejected> {
+    moderate(
+        stages,
+        &ModeratedDocument {
+            snippet: None,
+            properties,
+        },
+    )
+}
+
+/// Rejects snippets which are too short or contain broken characters.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Malformed {
+    /// Min number of alphanumeric characters of a snippet.
+    #[serde(default)]
+    pub(crate) min_alphanumeric: usize,
+}
+
+impl Moderate for Malformed {
+    fn moderate(&self, document: &ModeratedDocument<'_>) -> Option<String> {
+        let snippet = document.snippet?;
+        if snippet
+            .chars()
+            .any(|c| c == char::REPLACEMENT_CHARACTER || (c.is_control() && !c.is_whitespace()))
+        {
+            return Some("snippet contains broken or control characters".into());
+        }
+        let alphanumeric = snippet.chars().filter(|c| c.is_alphanumeric()).count();
+        (alphanumeric < self.min_alphanumeric).then(|| {
+            format!(
+                "snippet has {alphanumeric} alphanumeric characters, expected at least {}",
+                self.min_alphanumeric,
+            )
+        })
+    }
+}
+
+/// Rejects documents whose language property isn't allowed.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct LanguageAllowList {
+    /// The property which contains the language of the document.
+    #[serde(default = "LanguageAllowList::default_property")]
+    pub(crate) property: String,
+    /// The allowed languages, compared case-insensitively.
+    pub(crate) allowed: Vec<String>,
+    /// Whether documents without the language property are rejected.
+    #[serde(default)]
+    pub(crate) reject_missing: bool,
+}
+
+impl LanguageAllowList {
+    fn default_property() -> String {
+        "language".into()
+    }
+}
+
+impl Moderate for LanguageAllowList {
+    fn moderate(&self, document: &ModeratedDocument<'_>) -> Option<String> {
+        match document.property(&self.property) {
+            Some(Value::String(language)) => {
+                let is_allowed = self
+                    .allowed
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(language));
+                (!is_allowed).then(|| format!("language {language:?} is not allowed"))
+            }
+            Some(_) => Some(format!("property {:?} is not a string", self.property)),
+            None => self
+                .reject_missing
+                .then(|| format!("property {:?} is missing", self.property)),
+        }
+    }
+}
+
+/// Rejects documents which link to a banned domain or one of its subdomains.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BannedDomains {
+    /// The property which contains the url of the document.
+    #[serde(default = "BannedDomains::default_property")]
+    pub(crate) property: String,
+    pub(crate) domains: Vec<String>,
+}
+
+impl BannedDomains {
+    fn default_property() -> String {
+        "link".into()
+    }
+}
+
+impl Moderate for BannedDomains {
+    fn moderate(&self, document: &ModeratedDocument<'_>) -> Option<String> {
+        let Some(Value::String(link)) = document.property(&self.property) else {
+            return None;
+        };
+        let Ok(url) = Url::parse(link) else {
+            return Some(format!("property {:?} is not a valid url", self.property));
+        };
+        let host = url.host_str()?.trim_end_matches('.').to_lowercase();
+        self.domains
+            .iter()
+            .find(|domain| {
+                let domain = domain.to_lowercase();
+                host == domain || host.ends_with(&format!(".{domain}"))
+            })
+            .map(|domain| format!("domain {domain:?} is banned"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::{DocumentProperty, DocumentPropertyId};
+
+    fn properties(properties: Value) -> DocumentProperties {
+        let Value::Object(properties) = properties else {
+            unreachable!();
+        };
+        let properties = properties
+            .into_iter()
+            .map(|(id, value)| {
+                let id = DocumentPropertyId::try_from(id).unwrap();
+                let value = DocumentProperty::try_from_value(&id, value, 128).unwrap();
+                (id, value)
+            })
+            .collect();
+        DocumentProperties::new(properties, 0, 1).unwrap()
+    }
+
+    #[test]
+    fn test_moderation_stages() {
+        let stages = serde_json::from_value::<Vec<ModerationStage>>(json!([
+            { "type": "malformed", "min_alphanumeric": 3 },
+            { "type": "language_allow_list", "allowed": ["en", "de"] },
+            { "type": "banned_domains", "domains": ["example.com"] }
+        ]))
+        .unwrap();
+        let moderate = |snippet, props| {
+            moderate(
+                &stages,
+                &ModeratedDocument {
+                    snippet: Some(snippet),
+                    properties: &properties(props),
+                },
+            )
+            .map_err(|rejected| rejected.stage)
+        };
+
+        assert_eq!(moderate("abc", json!({})), Ok(()));
+        assert_eq!(moderate("a b", json!({})), Err("malformed"));
+        assert_eq!(moderate("abc\u{fffd}", json!({})), Err("malformed"));
+        assert_eq!(moderate("abc", json!({ "language": "EN" })), Ok(()));
+        assert_eq!(
+            moderate("abc", json!({ "language": "fr" })),
+            Err("language_allow_list"),
+        );
+        assert_eq!(
+            moderate("abc", json!({ "link": "https://example.org/a" })),
+            Ok(()),
+        );
+        assert_eq!(
+            moderate("abc", json!({ "link": "https://news.Example.com/a" })),
+            Err("banned_domains"),
+        );
+    }
+}
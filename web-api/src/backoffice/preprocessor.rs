@@ -66,6 +66,34 @@ where
     res.map_err(PreprocessError::Fatal)
 }
 
+/// Recomputes the embeddings of already preprocessed snippets.
+///
+/// Split snippets are not split again, they are stored as the resulting splits.
+pub(crate) async fn reembed(
+    embedder: &Embedder,
+    preprocessing_step: PreprocessingStep,
+    contents: Vec<DocumentContent>,
+) -> Result<Vec<DocumentContent>, Error> {
+    contents
+        .into_iter()
+        .map(|DocumentContent { snippet, .. }| async move {
+            let mut content = match preprocessing_step {
+                PreprocessingStep::Summarize => {
+                    embed_with_summarizer(embedder, EmbeddingKind::Content, snippet).await?
+                }
+                PreprocessingStep::None
+                | PreprocessingStep::CuttersSplit
+                | PreprocessingStep::NltkSplitV1 => {
+                    embed_whole(embedder, EmbeddingKind::Content, snippet).await?
+                }
+            };
+            Ok::<_, Error>(content.remove(0))
+        })
+        .collect::<FuturesOrdered<_>>()
+        .try_collect()
+        .await
+}
+
 async fn embed_whole(
     embedder: &Embedder,
     kind: EmbeddingKind,
@@ -0,0 +1,122 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{anyhow, bail};
+use tracing::{info, instrument};
+
+use super::preprocessor;
+use crate::{
+    app::SetupError,
+    config::{Config, ReembedArgs},
+    embedding::{Embedder, Models},
+    models::{DocumentForIngestion, DocumentId},
+    storage::{self, initialize_silo, Storage},
+};
+
+/// Re-embeds all documents of a tenant with its configured model into a new es index.
+///
+/// The documents are processed in batches ordered by id, each batch is written to the new
+/// index and the embeddings are staged in postgres. If the process is interrupted it can be
+/// resumed after the last logged document id. Documents which have been ingested in the meantime
+/// are re-embedded afterwards. Once all documents are re-embedded the staged embeddings are
+/// applied and the tenant is switched to the new index in one transaction, the current index and
+/// embeddings are left untouched until then. The switch fails if documents have been ingested
+/// after the last staging, in which case the command can be resumed. The interests of the users
+/// are in the embedding space of the previous model, they are deleted in the same transaction and
+/// rebuilt from the following interactions.
+#[instrument(skip_all, fields(tenant = %args.tenant, index = %args.index))]
+pub(crate) async fn reembed(config: &Config, args: ReembedArgs) -> Result<(), SetupError> {
+    let ReembedArgs {
+        tenant: tenant_id,
+        index,
+        after,
+        batch_size,
+    } = args;
+    if batch_size == 0 {
+        bail!("invalid batch size, it must be > 0");
+    }
+    let mut after = after.map(DocumentId::try_from).transpose()?;
+
+    let models = Models::load(config.as_ref(), config.as_ref()).await?;
+    let (silo, legacy_tenant) = initialize_silo(
+        config.as_ref(),
+        config.as_ref(),
+        models.embedding_sizes(),
+        Some(&tenant_id),
+    )
+    .await?;
+    let storage_builder = Storage::builder(config.as_ref(), legacy_tenant).await?;
+    let storage = storage_builder.build_for_index(tenant_id, index).await?;
+    let model = &storage.tenant().model;
+    let embedder = models
+        .get(model)
+        .ok_or_else(|| anyhow!("deployment doesn't support tenants model: {model}"))?;
+
+    silo.create_es_index_if_missing(storage.tenant()).await?;
+    storage::Reembedding::copy_schema(&storage, config.as_ref()).await?;
+    if after.is_none() {
+        storage::Reembedding::discard(&storage).await?;
+    }
+
+    let mut count = 0;
+    loop {
+        let documents =
+            storage::Reembedding::get_after(&storage, after.as_ref(), batch_size).await?;
+        let Some(last) = documents.last().map(|document| document.id.clone()) else {
+            break;
+        };
+        count += stage(&storage, embedder, documents).await?;
+        info!(count, %last, "re-embedded documents");
+        after = Some(last);
+    }
+    loop {
+        let documents = storage::Reembedding::get_unstaged(&storage, batch_size).await?;
+        if documents.is_empty() {
+            break;
+        }
+        count += stage(&storage, embedder, documents).await?;
+        info!(count, "re-embedded documents ingested in the meantime");
+    }
+
+    let deleted_cois = storage::Reembedding::apply(&storage).await?;
+    storage_builder.close().await;
+    info!(count, deleted_cois, "switched tenant to the new index");
+
+    Ok(())
+}
+
+/// Re-embeds and stages the documents, returns the number of staged documents.
+async fn stage(
+    storage: &Storage,
+    embedder: &Embedder,
+    documents: Vec<DocumentForIngestion>,
+) -> Result<usize, SetupError> {
+    let first = documents.first().map(|document| document.id.clone());
+    let mut reembedded = Vec::with_capacity(documents.len());
+    for mut document in documents {
+        document.snippets =
+            preprocessor::reembed(embedder, document.preprocessing_step, document.snippets).await?;
+        reembedded.push(document);
+    }
+    let count = reembedded.len();
+    let failed = storage::Reembedding::stage(storage, reembedded)
+        .await?
+        .into_iter()
+        .collect::<Vec<_>>();
+    if !failed.is_empty() {
+        bail!("failed to index documents from {first:?}: {failed:?}");
+    }
+
+    Ok(count)
+}
@@ -12,57 +12,83 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, matches};
+use std::{collections::HashMap, matches, sync::Arc};
 
 use actix_web::{
-    web::{self, Data, Json, Path, ServiceConfig},
+    body::{BoxBody, MessageBody},
+    web::{self, Data, Json, Path, Query, ServiceConfig},
+    HttpRequest,
     HttpResponse,
     Responder,
+    ResponseError,
 };
 use anyhow::anyhow;
 use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
 use futures_util::{
-    stream::{FuturesOrdered, StreamExt},
+    stream::{self, FuturesOrdered, StreamExt, TryStreamExt},
     TryFutureExt,
 };
 use itertools::{Either, Itertools};
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 use tokio::time::Instant;
 use tracing::{debug, error, info, instrument};
 use xayn_web_api_db_ctrl::{Operation, Silo};
 
-use super::preprocessor::PreprocessError;
+use super::{
+    moderation::{moderate, moderate_properties, ModeratedDocument},
+    preprocessor::PreprocessError,
+};
 use crate::{
     app::{AppState, TenantState},
     backoffice,
-    backoffice::IngestionConfig,
-    embedding::EmbeddingKind,
+    backoffice::{DuplicatePolicy, DuplicatesConfig, IngestionConfig},
+    embedding::{Embedder, EmbeddingKind},
     error::common::{
         BadRequest,
+        BoostRuleNotFound,
         DocumentInBatchError,
         DocumentNotFound,
         DocumentPropertyNotFound,
+        DuplicateDocument,
         FailedToDeleteSomeDocuments,
         FailedToIngestDocuments,
         FailedToSetSomeDocumentCandidates,
         FailedToValidateDocuments,
         FileUploadNotEnabled,
+        IdempotencyKeyInUse,
+        IdempotencyKeyMismatch,
+        InvalidBoostRuleWeight,
+        InvalidDocumentCount,
         InvalidDocumentSnippet,
     },
     models::{
         self,
+        BoostRule,
+        BoostRuleId,
         DocumentId,
         DocumentProperties,
         DocumentProperty,
         DocumentPropertyId,
         DocumentSnippet,
         DocumentTags,
+        ExcerptedDocument,
+        IdempotencyKey,
         PreprocessingStep,
         Sha256Hash,
     },
-    storage::{self, property_filter::IndexedPropertiesSchemaUpdate},
+    storage::{
+        self,
+        property_filter::IndexedPropertiesSchemaUpdate,
+        ConsistencyReport,
+        Exclusions,
+        IdempotencyState,
+        KnnSearchParams,
+        SearchStrategy,
+        Storage,
+    },
     utils::deprecate,
     Error,
 };
@@ -71,7 +97,9 @@ pub(crate) fn configure_service(config: &mut ServiceConfig) {
     config
         .service(
             web::resource("/documents")
+                .route(web::get().to(list_documents))
                 .route(web::post().to(upsert_documents))
+                .route(web::put().to(update_documents))
                 .route(web::delete().to(delete_documents)),
         )
         .service(
@@ -91,12 +119,22 @@ pub(crate) fn configure_service(config: &mut ServiceConfig) {
                 .route(web::get().to(deprecate!(get_document_candidates(state))))
                 .route(web::put().to(deprecate!(set_document_candidates(request, state)))),
         )
+        .service(web::resource("/documents/_validate").route(web::post().to(validate_documents)))
+        .service(
+            web::resource("/documents/_consistency")
+                .route(web::get().to(get_consistency))
+                .route(web::post().to(reconcile)),
+        )
         .service(
             web::resource("/documents/_indexed_properties")
                 .route(web::post().to(create_indexed_properties))
                 .route(web::get().to(get_indexed_properties_schema)),
         )
-        .service(web::resource("/documents/{document_id}").route(web::delete().to(delete_document)))
+        .service(
+            web::resource("/documents/{document_id}")
+                .route(web::put().to(update_document))
+                .route(web::delete().to(delete_document)),
+        )
         .service(
             web::resource("/documents/{document_id}/properties")
                 .route(web::get().to(get_document_properties))
@@ -108,6 +146,12 @@ pub(crate) fn configure_service(config: &mut ServiceConfig) {
                 .route(web::get().to(get_document_property))
                 .route(web::put().to(put_document_property))
                 .route(web::delete().to(delete_document_property)),
+        )
+        .service(web::resource("/boost_rules").route(web::get().to(get_boost_rules)))
+        .service(
+            web::resource("/boost_rules/{rule_id}")
+                .route(web::put().to(put_boost_rule))
+                .route(web::delete().to(delete_boost_rule)),
         );
 }
 
@@ -157,22 +201,22 @@ impl InputDataRequest {
 
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
-struct UnvalidatedDocumentForIngestion {
-    id: String,
+pub(crate) struct UnvalidatedDocumentForIngestion {
+    pub(crate) id: String,
     #[serde(flatten)]
-    data: InputDataRequest,
+    pub(crate) data: InputDataRequest,
     #[serde(default)]
-    properties: HashMap<String, Value>,
+    pub(crate) properties: HashMap<String, Value>,
     #[serde(default)]
-    tags: Vec<String>,
+    pub(crate) tags: Vec<String>,
     #[serde(default)]
-    is_candidate: Option<bool>,
+    pub(crate) is_candidate: Option<bool>,
     #[serde(default)]
-    default_is_candidate: Option<bool>,
+    pub(crate) default_is_candidate: Option<bool>,
     #[serde(default)]
-    summarize: bool,
+    pub(crate) summarize: bool,
     #[serde(default)]
-    split: Option<bool>,
+    pub(crate) split: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -348,35 +392,170 @@ struct IngestionRequestBody {
     documents: Vec<UnvalidatedDocumentForIngestion>,
 }
 
+const IDEMPOTENCY_KEY_HEADER: &str = "X-Idempotency-Key";
+
+/// Number of hours after which an idempotency key can be reused.
+pub(crate) const IDEMPOTENCY_KEY_EXPIRATION: i64 = 24;
+
+/// Number of minutes after which an unfinished request with an idempotency key can be taken over.
+const IDEMPOTENCY_KEY_LEASE: i64 = 5;
+
+fn extract_idempotency_key(request: &HttpRequest) -> Result<Option<IdempotencyKey>, Error> {
+    request
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .map(|key| {
+            let key = key.to_str().map_err(|_| {
+                BadRequest::from(format!("{IDEMPOTENCY_KEY_HEADER} header must be ascii"))
+            })?;
+            Ok(IdempotencyKey::try_from(key)?)
+        })
+        .transpose()
+}
+
 #[instrument(skip_all)]
 async fn upsert_documents(
     state: Data<AppState>,
-    Json(body): Json<IngestionRequestBody>,
+    request: HttpRequest,
+    Json(body): Json<Value>,
     TenantState(storage, embedder): TenantState,
-) -> Result<impl Responder, Error> {
+) -> Result<HttpResponse, Error> {
+    let Some(key) = extract_idempotency_key(&request)? else {
+        return upsert_documents_body(&state, &storage, &embedder, body).await;
+    };
+
+    let fingerprint = Sha256Hash::calculate(&serde_json::to_vec(&body)?);
+    let time = Utc::now();
+    let lease = time + Duration::minutes(IDEMPOTENCY_KEY_LEASE);
+    let expiration = time - Duration::hours(IDEMPOTENCY_KEY_EXPIRATION);
+    match storage::Idempotency::reserve(&storage, &key, &fingerprint, time, lease, expiration)
+        .await?
+    {
+        IdempotencyState::Reserved => {}
+        IdempotencyState::InProgress => return Err(IdempotencyKeyInUse.into()),
+        IdempotencyState::Mismatch => return Err(IdempotencyKeyMismatch.into()),
+        IdempotencyState::Completed { status, body } => {
+            info!(%key, "replayed idempotent request");
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::CREATED);
+            let mut response = HttpResponse::build(status);
+            if !body.is_empty() {
+                response.content_type(mime::APPLICATION_JSON);
+            }
+            return Ok(response.body(body));
+        }
+    }
+
+    // Hint: client errors are deterministic wrt the body, hence they are replayed as well
+    let response = match upsert_documents_body(&state, &storage, &embedder, body).await {
+        Ok(response) => response,
+        Err(error) if error.status_code().is_client_error() => error.error_response(),
+        Err(error) => {
+            if let Err(error) = storage::Idempotency::release(&storage, &key).await {
+                error!({ %error, %key }, "failed to release idempotency key");
+            }
+            return Err(error);
+        }
+    };
+    let (response, body) = response.into_parts();
+    let body = body.try_into_bytes().unwrap_or_default();
+    // Hint: the request has been processed already, hence it must not fail anymore
+    if let Err(error) =
+        storage::Idempotency::complete(&storage, &key, response.status().as_u16(), &body).await
+    {
+        error!({ %error, %key }, "failed to complete idempotent request");
+    }
+
+    Ok(response.set_body(BoxBody::new(body)))
+}
+
+async fn upsert_documents_body(
+    state: &AppState,
+    storage: &Storage,
+    embedder: &Arc<Embedder>,
+    body: Value,
+) -> Result<HttpResponse, Error> {
+    let body = serde_json::from_value::<IngestionRequestBody>(body)
+        .map_err(|error| BadRequest::from(format!("Json deserialize error: {error}")))?;
     if body.documents.is_empty() {
-        return Ok(HttpResponse::NoContent());
+        return Ok(HttpResponse::NoContent().finish());
     }
 
-    if body.documents.len() > state.config.ingestion.max_document_batch_size {
-        info!("{} documents exceeds maximum number", body.documents.len());
-        return Err(BadRequest::from(format!(
-            "Document batch size exceeded maximum of {}.",
-            state.config.ingestion.max_document_batch_size
-        ))
-        .into());
+    validate_document_batch_size(&*state.config(), body.documents.len())?;
+    ingest_documents(state, storage, embedder, body.documents, Vec::new()).await?;
+
+    Ok(HttpResponse::Created().finish())
+}
+
+/// Ingests documents outside of an actix request.
+///
+/// This is the same as `POST /documents` without support for idempotency keys.
+pub(crate) async fn ingest(
+    state: &AppState,
+    storage: &Storage,
+    embedder: &Arc<Embedder>,
+    documents: Vec<UnvalidatedDocumentForIngestion>,
+) -> Result<(), Error> {
+    if documents.is_empty() {
+        return Ok(());
+    }
+
+    validate_document_batch_size(&*state.config(), documents.len())?;
+    ingest_documents(state, storage, embedder, documents, Vec::new()).await
+}
+
+fn validate_document_batch_size(
+    config: &impl AsRef<IngestionConfig>,
+    size: usize,
+) -> Result<(), Error> {
+    let max = config.as_ref().max_document_batch_size;
+    if size > max {
+        info!("{size} documents exceeds maximum number");
+        return Err(
+            BadRequest::from(format!("Document batch size exceeded maximum of {max}.")).into(),
+        );
     }
 
-    let has_file = body.documents.iter().any(|doc| doc.data.is_file());
-    if !state.config.text_extractor.enabled && has_file {
+    Ok(())
+}
+
+/// Validates, embeds and stores the documents.
+///
+/// Documents which already exist are only re-embedded if their snippet or preprocessing changed.
+#[allow(clippy::too_many_lines)]
+async fn ingest_documents(
+    state: &AppState,
+    storage: &Storage,
+    embedder: &Arc<Embedder>,
+    unvalidated_documents: Vec<UnvalidatedDocumentForIngestion>,
+    mut invalid_documents: Vec<DocumentInBatchError>,
+) -> Result<(), Error> {
+    let config = state.config();
+    let has_file = unvalidated_documents.iter().any(|doc| doc.data.is_file());
+    if !config.text_extractor.enabled && has_file {
         return Err(FileUploadNotEnabled.into());
     }
 
-    let mut documents = Vec::with_capacity(body.documents.len());
-    let mut invalid_documents = Vec::new();
-    for document in body.documents {
+    let mut documents = Vec::with_capacity(unvalidated_documents.len());
+    for document in unvalidated_documents {
         let id = document.id.clone();
-        match document.validate(&state.config, &storage).await {
+        let document = document
+            .validate(&*config, storage)
+            .await
+            .and_then(|document| {
+                let snippet = match &document.original {
+                    InputData::Snippet(snippet) => Some(snippet.as_str()),
+                    InputData::Binary(_) => None,
+                };
+                moderate(
+                    &config.ingestion.moderation,
+                    &ModeratedDocument {
+                        snippet,
+                        properties: &document.properties,
+                    },
+                )?;
+                Ok(document)
+            });
+        match document {
             Ok(document) => documents.push(document),
             Err(error) => {
                 info!("Invalid document '{id}': {error}");
@@ -402,7 +581,7 @@ async fn upsert_documents(
     };
 
     let existing_documents =
-        storage::Document::get_excerpted(&storage, documents.iter().map(|document| &document.id))
+        storage::Document::get_excerpted(storage, documents.iter().map(|document| &document.id))
             .await?
             .into_iter()
             .map(|document| {
@@ -453,7 +632,7 @@ async fn upsert_documents(
         });
 
     storage::DocumentCandidate::remove(
-        &storage,
+        storage,
         changed_documents
             .iter()
             .filter_map(|(document, _, _, new_is_candidate)| {
@@ -466,15 +645,15 @@ async fn upsert_documents(
 
     for (document, new_properties, new_tags, _) in &changed_documents {
         if *new_properties {
-            storage::DocumentProperties::put(&storage, &document.id, &document.properties).await?;
+            storage::DocumentProperties::put(storage, &document.id, &document.properties).await?;
         }
         if *new_tags {
-            storage::Tag::put(&storage, &document.id, &document.tags).await?;
+            storage::Tag::put(storage, &document.id, &document.tags).await?;
         }
     }
 
     storage::DocumentCandidate::add(
-        &storage,
+        storage,
         changed_documents
             .iter()
             .filter_map(|(document, _, _, new_is_candidate)| {
@@ -486,10 +665,9 @@ async fn upsert_documents(
     .await?;
 
     let start = Instant::now();
-    let state = &state;
     let new_documents_len = new_documents.len();
 
-    let (new_documents, mut failed_documents, invalid_documents) = new_documents
+    let (new_documents, mut failed_documents, mut invalid_documents) = new_documents
         .into_iter()
         .map(|(mut document, new_is_candidate)| {
         let embedder = embedder.clone();
@@ -547,8 +725,16 @@ async fn upsert_documents(
         changed_documents.len(),
     );
 
+    let new_documents = handle_duplicates(
+        &config.ingestion.duplicates,
+        storage,
+        new_documents,
+        &mut invalid_documents,
+    )
+    .await?;
+
     failed_documents.extend(
-        storage::Document::insert(&storage, new_documents)
+        storage::Document::insert(storage, new_documents)
             .await?
             .into_iter()
             .map(|id| DocumentInBatchError {
@@ -570,10 +756,296 @@ async fn upsert_documents(
         }
         .into())
     } else {
-        Ok(HttpResponse::Created())
+        Ok(())
     }
 }
 
+/// Max number of concurrent knn searches for near duplicates.
+const DUPLICATES_CONCURRENCY: usize = 10;
+
+/// Handles new documents which are near duplicates of existing or other new documents.
+///
+/// Only the embedding of the first snippet of each document is compared.
+async fn handle_duplicates(
+    config: &DuplicatesConfig,
+    storage: &Storage,
+    documents: Vec<models::DocumentForIngestion>,
+    invalid_documents: &mut Vec<DocumentInBatchError>,
+) -> Result<Vec<models::DocumentForIngestion>, Error> {
+    if config.policy == DuplicatePolicy::Allow {
+        return Ok(documents);
+    }
+
+    let existing = stream::iter(&documents)
+        .map(|document| find_existing_duplicate(config, storage, document))
+        .buffered(DUPLICATES_CONCURRENCY)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(resolve_duplicates(
+        config,
+        documents,
+        existing,
+        invalid_documents,
+    ))
+}
+
+/// Finds an existing document which is a near duplicate of the new document.
+async fn find_existing_duplicate(
+    config: &DuplicatesConfig,
+    storage: &Storage,
+    document: &models::DocumentForIngestion,
+) -> Result<Option<DocumentId>, Error> {
+    let Some(embedding) = document.snippets.first().map(|snippet| &snippet.embedding) else {
+        return Ok(None);
+    };
+
+    let excluded = Exclusions {
+        documents: vec![document.id.clone()],
+        snippets: Vec::new(),
+    };
+    let duplicate = storage::Document::get_by_embedding(
+        storage,
+        KnnSearchParams {
+            excluded: &excluded,
+            embedding,
+            count: 1,
+            num_candidates: 10,
+            strategy: SearchStrategy::Knn,
+            include_properties: false,
+            include_snippet: false,
+            filter: None,
+            published_after: None,
+            with_raw_scores: false,
+        },
+    )
+    .await?
+    .into_iter()
+    .find(|other| other.embedding.dot_product(embedding) >= config.threshold)
+    .map(|other| other.id.into_document_id());
+
+    Ok(duplicate)
+}
+
+/// Applies the duplicate policy to the new documents.
+///
+/// A document is a near duplicate of an earlier accepted document of the batch or else of its
+/// `existing` duplicate, if any.
+fn resolve_duplicates(
+    config: &DuplicatesConfig,
+    documents: Vec<models::DocumentForIngestion>,
+    existing: Vec<Option<DocumentId>>,
+    invalid_documents: &mut Vec<DocumentInBatchError>,
+) -> Vec<models::DocumentForIngestion> {
+    let mut accepted = Vec::<models::DocumentForIngestion>::with_capacity(documents.len());
+    for (mut document, existing) in documents.into_iter().zip(existing) {
+        let duplicate_of = document
+            .snippets
+            .first()
+            .and_then(|snippet| {
+                accepted.iter().find(|other| {
+                    other.snippets.first().map_or(false, |other| {
+                        other.embedding.dot_product(&snippet.embedding) >= config.threshold
+                    })
+                })
+            })
+            .map(|other| other.id.clone())
+            .or(existing);
+
+        let Some(duplicate_of) = duplicate_of else {
+            accepted.push(document);
+            continue;
+        };
+        match config.policy {
+            DuplicatePolicy::Allow => accepted.push(document),
+            DuplicatePolicy::Reject => {
+                info!(
+                    "Rejected document '{}' as near duplicate of '{duplicate_of}'",
+                    document.id,
+                );
+                invalid_documents.push(DocumentInBatchError::new(
+                    document.id,
+                    &DuplicateDocument { duplicate_of },
+                ));
+            }
+            DuplicatePolicy::Tag => {
+                if !document.tags.contains(&config.tag) {
+                    if let Err(error) = document.tags.try_push(config.tag.clone()) {
+                        info!(
+                            "Failed to tag near duplicate document '{}': {error}",
+                            document.id,
+                        );
+                    }
+                }
+                accepted.push(document);
+            }
+        }
+    }
+
+    accepted
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UnvalidatedDocumentUpdate {
+    snippet: Option<String>,
+    file: Option<String>,
+    properties: Option<HashMap<String, Value>>,
+    #[serde(default)]
+    merge_properties: bool,
+    tags: Option<Vec<String>>,
+    is_candidate: Option<bool>,
+    #[serde(default)]
+    summarize: bool,
+    split: Option<bool>,
+}
+
+impl UnvalidatedDocumentUpdate {
+    /// Resolves the update wrt the existing document.
+    ///
+    /// Omitted properties, tags and candidate status are preserved, given properties are either
+    /// merged into or replace the existing properties.
+    fn resolve(
+        self,
+        id: String,
+        existing: ExcerptedDocument,
+    ) -> Result<UnvalidatedDocumentForIngestion, Error> {
+        let data = match (self.snippet, self.file) {
+            (Some(snippet), None) => InputDataRequest::Snippet(snippet),
+            (None, Some(file)) => InputDataRequest::File(file),
+            (Some(_), Some(_)) => {
+                let message = "either snippet or file must be present, but both were found";
+                return Err(BadRequest::from(message).into());
+            }
+            (None, None) => {
+                return Err(BadRequest::from("either snippet or file must be present").into());
+            }
+        };
+        let existing_properties = existing
+            .properties
+            .into_iter()
+            .map(|(property_id, property)| (String::from(property_id), Value::from(property)));
+        let properties = match self.properties {
+            Some(properties) if self.merge_properties => {
+                existing_properties.chain(properties).collect()
+            }
+            Some(properties) => properties,
+            None => existing_properties.collect(),
+        };
+        let tags = self.tags.unwrap_or_else(|| {
+            existing
+                .tags
+                .iter()
+                .map(|tag| String::from(tag.clone()))
+                .collect()
+        });
+
+        Ok(UnvalidatedDocumentForIngestion {
+            id,
+            data,
+            properties,
+            tags,
+            is_candidate: Some(self.is_candidate.unwrap_or(existing.is_candidate)),
+            default_is_candidate: None,
+            summarize: self.summarize,
+            split: self.split,
+        })
+    }
+}
+
+#[instrument(skip(state, body, storage, embedder))]
+async fn update_document(
+    state: Data<AppState>,
+    document_id: Path<String>,
+    Json(body): Json<UnvalidatedDocumentUpdate>,
+    TenantState(storage, embedder): TenantState,
+) -> Result<impl Responder, Error> {
+    let id = document_id.into_inner();
+    let document_id = DocumentId::try_from(id.as_str())?;
+    let existing = storage::Document::get_excerpted(&storage, [&document_id])
+        .await?
+        .pop()
+        .ok_or(DocumentNotFound)?;
+    let document = body.resolve(id, existing)?;
+    ingest_documents(&state, &storage, &embedder, vec![document], Vec::new()).await?;
+
+    Ok(HttpResponse::NoContent())
+}
+
+// Hint: the update isn't flattened, because `deny_unknown_fields` doesn't work with `flatten`
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "Map<String, Value>")]
+struct UnvalidatedDocumentUpdateInBatch {
+    id: String,
+    update: UnvalidatedDocumentUpdate,
+}
+
+impl TryFrom<Map<String, Value>> for UnvalidatedDocumentUpdateInBatch {
+    type Error = String;
+
+    fn try_from(mut update: Map<String, Value>) -> Result<Self, Self::Error> {
+        let Some(Value::String(id)) = update.remove("id") else {
+            return Err("missing or invalid field `id`".to_owned());
+        };
+        let update =
+            serde_json::from_value(Value::Object(update)).map_err(|error| error.to_string())?;
+
+        Ok(Self { id, update })
+    }
+}
+
+/// Represents body of a PUT documents request.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UpdateRequestBody {
+    documents: Vec<UnvalidatedDocumentUpdateInBatch>,
+}
+
+#[instrument(skip_all)]
+async fn update_documents(
+    state: Data<AppState>,
+    Json(body): Json<UpdateRequestBody>,
+    TenantState(storage, embedder): TenantState,
+) -> Result<impl Responder, Error> {
+    if body.documents.is_empty() {
+        return Ok(HttpResponse::NoContent());
+    }
+    validate_document_batch_size(&*state.config(), body.documents.len())?;
+
+    let mut ids = Vec::with_capacity(body.documents.len());
+    let mut invalid_documents = Vec::new();
+    for document in &body.documents {
+        match DocumentId::try_from(document.id.as_str()) {
+            Ok(id) => ids.push(id),
+            Err(error) => invalid_documents.push(DocumentInBatchError::new(&document.id, &error)),
+        }
+    }
+    let mut existing_documents = storage::Document::get_excerpted(&storage, &ids)
+        .await?
+        .into_iter()
+        .map(|document| (document.id.clone(), document))
+        .collect::<HashMap<_, _>>();
+
+    let mut documents = Vec::with_capacity(ids.len());
+    for UnvalidatedDocumentUpdateInBatch { id, update } in body.documents {
+        let Ok(document_id) = DocumentId::try_from(id.as_str()) else {
+            // already reported as invalid
+            continue;
+        };
+        let Some(existing) = existing_documents.remove(&document_id) else {
+            invalid_documents.push(DocumentInBatchError::new(id, &DocumentNotFound));
+            continue;
+        };
+        match update.resolve(id.clone(), existing) {
+            Ok(document) => documents.push(document),
+            Err(error) => invalid_documents.push(DocumentInBatchError::new(id, &*error)),
+        }
+    }
+    ingest_documents(&state, &storage, &embedder, documents, invalid_documents).await?;
+
+    Ok(HttpResponse::NoContent())
+}
+
 async fn delete_document(id: Path<String>, state: TenantState) -> Result<impl Responder, Error> {
     delete_documents(
         Json(BatchDeleteRequest {
@@ -613,6 +1085,220 @@ struct BatchDeleteRequest {
     documents: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchValidateRequest {
+    documents: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchValidateResponse {
+    missing: Vec<DocumentId>,
+    pending: Vec<DocumentId>,
+    not_indexed: Vec<DocumentId>,
+    invalid_properties: Vec<DocumentInBatchError>,
+}
+
+/// Checks the ingestion state of the documents.
+///
+/// Reports the documents which don't exist, which are pending to be synced with elastic, the
+/// candidates which are neither pending nor in elastic and the documents whose properties violate
+/// the current indexed properties schema.
+#[instrument(skip_all)]
+async fn validate_documents(
+    state: Data<AppState>,
+    Json(body): Json<BatchValidateRequest>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    validate_document_batch_size(&*state.config(), body.documents.len())?;
+    let ids = body
+        .documents
+        .into_iter()
+        .map(TryInto::try_into)
+        .try_collect::<_, Vec<DocumentId>, _>()?;
+
+    let documents = storage::Document::get_excerpted(&storage, &ids)
+        .await?
+        .into_iter()
+        .map(|document| (document.id.clone(), document))
+        .collect::<HashMap<_, _>>();
+    let schema = storage::IndexedProperties::load_schema(&storage).await?;
+    let (pending, indexed) =
+        storage::Consistency::get_sync_state(&storage, &documents.keys().collect_vec()).await?;
+
+    let mut response = BatchValidateResponse {
+        missing: Vec::new(),
+        pending: Vec::new(),
+        not_indexed: Vec::new(),
+        invalid_properties: Vec::new(),
+    };
+    for id in ids {
+        let Some(document) = documents.get(&id) else {
+            response.missing.push(id);
+            continue;
+        };
+        for (property_id, property) in &*document.properties {
+            if let Err(error) = schema.validate_property(property_id, property) {
+                response
+                    .invalid_properties
+                    .push(DocumentInBatchError::new(id.to_string(), &error));
+            }
+        }
+        if pending.contains(&id) {
+            response.pending.push(id);
+        } else if document.is_candidate && !indexed.contains(&id) {
+            response.not_indexed.push(id);
+        }
+    }
+
+    Ok(Json(response))
+}
+
+/// Default number of documents to list.
+const DEFAULT_NUMBER_DOCUMENTS: usize = 100;
+
+/// Max number of documents to list.
+const MAX_NUMBER_DOCUMENTS: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ListDocumentsQuery {
+    after: Option<String>,
+    count: Option<usize>,
+    #[serde(default)]
+    include_properties: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ListedDocument {
+    id: DocumentId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<DocumentProperties>,
+}
+
+#[derive(Debug, Serialize)]
+struct ListDocumentsResponse {
+    documents: Vec<ListedDocument>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<DocumentId>,
+}
+
+/// Lists the documents in the search index page by page.
+#[instrument(skip(storage))]
+async fn list_documents(
+    Query(params): Query<ListDocumentsQuery>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let after = params.after.map(TryInto::try_into).transpose()?;
+    let count = params.count.unwrap_or(DEFAULT_NUMBER_DOCUMENTS);
+    if !(1..=MAX_NUMBER_DOCUMENTS).contains(&count) {
+        return Err(InvalidDocumentCount {
+            count,
+            min: 1,
+            max: MAX_NUMBER_DOCUMENTS,
+        }
+        .into());
+    }
+
+    let documents = storage::Document::list_indexed(&storage, after.as_ref(), count).await?;
+    let next = (documents.len() == count)
+        .then(|| documents.last().map(|document| document.id.clone()))
+        .flatten();
+    let documents = documents
+        .into_iter()
+        .map(|document| ListedDocument {
+            id: document.id,
+            properties: params.include_properties.then_some(document.properties),
+        })
+        .collect();
+
+    Ok(Json(ListDocumentsResponse { documents, next }))
+}
+
+/// Max number of pending documents to report or reconcile at once.
+pub(crate) const MAX_NUMBER_PENDING_DOCUMENTS: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConsistencyQuery {
+    after: Option<String>,
+    count: Option<usize>,
+}
+
+impl ConsistencyQuery {
+    fn validate(self) -> Result<(Option<DocumentId>, usize), Error> {
+        let after = self.after.map(TryInto::try_into).transpose()?;
+        let count = self.count.unwrap_or(DEFAULT_NUMBER_DOCUMENTS);
+        if !(1..=MAX_NUMBER_DOCUMENTS).contains(&count) {
+            return Err(InvalidDocumentCount {
+                count,
+                min: 1,
+                max: MAX_NUMBER_DOCUMENTS,
+            }
+            .into());
+        }
+
+        Ok((after, count))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ConsistencyResponse {
+    pending: Vec<DocumentId>,
+    missing: Vec<DocumentId>,
+    unexpected: Vec<DocumentId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last: Option<DocumentId>,
+    candidates: usize,
+    indexed: usize,
+}
+
+impl From<ConsistencyReport> for ConsistencyResponse {
+    fn from(report: ConsistencyReport) -> Self {
+        Self {
+            pending: report.pending,
+            missing: report.missing,
+            unexpected: report.unexpected,
+            last: report.last,
+            candidates: report.candidates,
+            indexed: report.indexed,
+        }
+    }
+}
+
+/// Compares a page of the stored documents with the search index.
+#[instrument(skip(storage))]
+async fn get_consistency(
+    Query(params): Query<ConsistencyQuery>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let (after, count) = params.validate()?;
+    let report = storage::Consistency::check(&storage, after.as_ref(), count).await?;
+
+    Ok(Json(ConsistencyResponse::from(report)))
+}
+
+/// Syncs the inconsistent documents of a page and the pending documents with the search index.
+#[instrument(skip(storage))]
+async fn reconcile(
+    Query(params): Query<ConsistencyQuery>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let (after, count) = params.validate()?;
+    let report = storage::Consistency::check(&storage, after.as_ref(), count).await?;
+    storage::Consistency::mark_pending(&storage, report.missing.iter().chain(&report.unexpected))
+        .await?;
+    let failed =
+        storage::Consistency::apply_pending(&storage, Utc::now(), MAX_NUMBER_PENDING_DOCUMENTS)
+            .await?;
+    if !failed.is_empty() {
+        error!("Failed to reconcile {} documents", failed.len());
+    }
+    let report = storage::Consistency::check(&storage, after.as_ref(), count).await?;
+
+    Ok(Json(ConsistencyResponse::from(report)))
+}
+
 #[derive(Debug, Serialize)]
 struct DocumentCandidatesResponse {
     documents: Vec<DocumentId>,
@@ -691,13 +1377,15 @@ async fn put_document_properties(
     TenantState(storage, _): TenantState,
 ) -> Result<impl Responder, Error> {
     let document_id = document_id.into_inner().try_into()?;
+    let config = state.config();
     let properties = validate_document_properties(
         properties.properties,
         &storage,
-        state.config.ingestion.max_properties_size,
-        state.config.ingestion.max_properties_string_size,
+        config.ingestion.max_properties_size,
+        config.ingestion.max_properties_string_size,
     )
     .await?;
+    moderate_properties(&config.ingestion.moderation, &properties)?;
     storage::DocumentProperties::put(&storage, &document_id, &properties)
         .await?
         .ok_or(DocumentNotFound)?;
@@ -705,12 +1393,17 @@ async fn put_document_properties(
     Ok(HttpResponse::NoContent())
 }
 
-#[instrument(skip(storage))]
+#[instrument(skip(state, storage))]
 async fn delete_document_properties(
+    state: Data<AppState>,
     document_id: Path<String>,
     TenantState(storage, _): TenantState,
 ) -> Result<impl Responder, Error> {
     let document_id = document_id.into_inner().try_into()?;
+    moderate_properties(
+        &state.config().ingestion.moderation,
+        &DocumentProperties::default(),
+    )?;
     storage::DocumentProperties::delete(&storage, &document_id)
         .await?
         .ok_or(DocumentNotFound)?;
@@ -755,10 +1448,11 @@ async fn put_document_property(
     let (document_id, property_id) = ids.into_inner();
     let document_id = document_id.try_into()?;
     let property_id = DocumentPropertyId::try_from(property_id)?;
+    let config = state.config();
     let property = DocumentProperty::try_from_value(
         &property_id,
         body.property,
-        state.config.ingestion.max_properties_string_size,
+        config.ingestion.max_properties_string_size,
     )?;
 
     let properties = storage::DocumentProperties::get(&storage, &document_id)
@@ -768,13 +1462,14 @@ async fn put_document_property(
         .chain([(property_id.clone(), property.clone())])
         .map(|(property_id, property)| (property_id.into(), property.into()));
 
-    validate_document_properties(
+    let properties = validate_document_properties(
         properties,
         &storage,
-        state.config.ingestion.max_properties_size,
-        state.config.ingestion.max_properties_string_size,
+        config.ingestion.max_properties_size,
+        config.ingestion.max_properties_string_size,
     )
     .await?;
+    moderate_properties(&config.ingestion.moderation, &properties)?;
 
     storage::DocumentProperty::put(&storage, &document_id, &property_id, &property)
         .await?
@@ -783,14 +1478,23 @@ async fn put_document_property(
     Ok(HttpResponse::NoContent())
 }
 
-#[instrument(skip(storage))]
+#[instrument(skip(state, storage))]
 async fn delete_document_property(
+    state: Data<AppState>,
     ids: Path<(String, String)>,
     TenantState(storage, _): TenantState,
 ) -> Result<impl Responder, Error> {
     let (document_id, property_id) = ids.into_inner();
     let document_id = document_id.try_into()?;
     let property_id = property_id.try_into()?;
+    let config = state.config();
+    if !config.ingestion.moderation.is_empty() {
+        let mut properties = storage::DocumentProperties::get(&storage, &document_id)
+            .await?
+            .ok_or(DocumentNotFound)?;
+        properties.remove(&property_id);
+        moderate_properties(&config.ingestion.moderation, &properties)?;
+    }
     storage::DocumentProperty::delete(&storage, &document_id, &property_id)
         .await?
         .ok_or(DocumentNotFound)?
@@ -805,7 +1509,7 @@ async fn create_indexed_properties(
     Json(update): Json<IndexedPropertiesSchemaUpdate>,
     TenantState(storage, _): TenantState,
 ) -> Result<impl Responder, Error> {
-    storage::IndexedProperties::extend_schema(&storage, update, &state.config.ingestion)
+    storage::IndexedProperties::extend_schema(&storage, update, &state.config().ingestion)
         .await
         .map(|res| Json(res).customize().with_status(StatusCode::ACCEPTED))
 }
@@ -839,9 +1543,189 @@ async fn silo_management(
     Ok(Json(json!({ "results": results })))
 }
 
+#[derive(Debug, Serialize)]
+struct BoostRulesResponse {
+    rules: Vec<BoostRule>,
+}
+
+#[instrument(skip(storage))]
+async fn get_boost_rules(TenantState(storage, _): TenantState) -> Result<impl Responder, Error> {
+    let rules = storage::BoostRules::get_all(&storage).await?;
+
+    Ok(Json(BoostRulesResponse { rules }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoostRuleRequest {
+    property_id: String,
+    value: Value,
+    weight: f32,
+    #[serde(default)]
+    expires_at: Option<DateTime<Utc>>,
+}
+
+#[instrument(skip(state, storage))]
+async fn put_boost_rule(
+    state: Data<AppState>,
+    rule_id: Path<String>,
+    Json(body): Json<BoostRuleRequest>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let id = BoostRuleId::try_from(rule_id.into_inner())?;
+    let property_id = DocumentPropertyId::try_from(body.property_id)?;
+    let value = DocumentProperty::try_from_value(
+        &property_id,
+        body.value,
+        state.config().ingestion.max_properties_string_size,
+    )?;
+    if !(-1. ..=1.).contains(&body.weight) {
+        return Err(InvalidBoostRuleWeight {
+            weight: body.weight,
+        }
+        .into());
+    }
+
+    storage::BoostRules::put(
+        &storage,
+        &BoostRule {
+            id,
+            property_id,
+            value,
+            weight: body.weight,
+            expires_at: body.expires_at,
+        },
+    )
+    .await?;
+
+    Ok(HttpResponse::NoContent())
+}
+
+#[instrument(skip(storage))]
+async fn delete_boost_rule(
+    rule_id: Path<String>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let id = rule_id.into_inner().try_into()?;
+    storage::BoostRules::delete(&storage, &id)
+        .await?
+        .ok_or(BoostRuleNotFound)?;
+
+    Ok(HttpResponse::NoContent())
+}
+
 #[cfg(test)]
 mod tests {
+    use xayn_ai_bert::Embedding1;
+
     use super::*;
+    use crate::models::{DocumentContent, DocumentTag};
+
+    fn mock_document(id: &str, embedding: Vec<f32>) -> models::DocumentForIngestion {
+        models::DocumentForIngestion {
+            id: id.try_into().unwrap(),
+            original_sha256: Sha256Hash::zero(),
+            snippets: vec![DocumentContent {
+                snippet: DocumentSnippet::new_with_length_constraint("snippet", 1..).unwrap(),
+                embedding: Embedding1::from(embedding).normalize().unwrap(),
+            }],
+            preprocessing_step: PreprocessingStep::None,
+            properties: DocumentProperties::default(),
+            tags: DocumentTags::default(),
+            is_candidate: true,
+        }
+    }
+
+    fn mock_documents() -> Vec<models::DocumentForIngestion> {
+        vec![
+            mock_document("d1", vec![1., 0., 0.]),
+            mock_document("d2", vec![0., 1., 0.]),
+            mock_document("d3", vec![1., 0.1, 0.]),
+            mock_document("d4", vec![0., 0., 1.]),
+        ]
+    }
+
+    fn mock_config(policy: DuplicatePolicy) -> DuplicatesConfig {
+        DuplicatesConfig {
+            policy,
+            ..DuplicatesConfig::default()
+        }
+    }
+
+    fn ids(documents: &[models::DocumentForIngestion]) -> Vec<&str> {
+        documents
+            .iter()
+            .map(|document| document.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_duplicates_allow() {
+        let mut invalid = Vec::new();
+        let existing = vec![None, Some("e1".try_into().unwrap()), None, None];
+
+        let accepted = resolve_duplicates(
+            &mock_config(DuplicatePolicy::Allow),
+            mock_documents(),
+            existing,
+            &mut invalid,
+        );
+        assert_eq!(ids(&accepted), ["d1", "d2", "d3", "d4"]);
+        assert!(accepted.iter().all(|document| document.tags.is_empty()));
+        assert!(invalid.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_duplicates_reject() {
+        let mut invalid = Vec::new();
+        let existing = vec![None, Some("e1".try_into().unwrap()), None, None];
+
+        let accepted = resolve_duplicates(
+            &mock_config(DuplicatePolicy::Reject),
+            mock_documents(),
+            existing,
+            &mut invalid,
+        );
+        assert_eq!(ids(&accepted), ["d1", "d4"]);
+        assert_eq!(
+            invalid
+                .iter()
+                .map(|error| (error.id.as_str(), error.kind.as_str(), &error.details))
+                .collect_vec(),
+            [
+                ("d2", "DuplicateDocument", &json!({ "duplicate_of": "e1" }),),
+                ("d3", "DuplicateDocument", &json!({ "duplicate_of": "d1" }),),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_resolve_duplicates_tag() {
+        let config = mock_config(DuplicatePolicy::Tag);
+        let mut invalid = Vec::new();
+        let mut documents = mock_documents();
+        documents[3].tags = (0..10)
+            .map(|tag| DocumentTag::new(tag.to_string()).unwrap())
+            .collect_vec()
+            .try_into()
+            .unwrap();
+        let existing = vec![
+            None,
+            Some("e1".try_into().unwrap()),
+            None,
+            Some("e2".try_into().unwrap()),
+        ];
+
+        let accepted = resolve_duplicates(&config, documents, existing, &mut invalid);
+        assert_eq!(ids(&accepted), ["d1", "d2", "d3", "d4"]);
+        assert!(accepted[0].tags.is_empty());
+        assert_eq!(*accepted[1].tags, [config.tag.clone()]);
+        assert_eq!(*accepted[2].tags, [config.tag.clone()]);
+        // the max number of tags is kept
+        assert_eq!(accepted[3].tags.len(), 10);
+        assert!(!accepted[3].tags.contains(&config.tag));
+        assert!(invalid.is_empty());
+    }
 
     #[test]
     fn test_resolve_is_candidate_op() {
@@ -13,7 +13,16 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use tracing::instrument;
-use xayn_web_api::{application_names, logging, start, Application, WebApi};
+use xayn_web_api::{
+    application_names,
+    config::Command,
+    logging,
+    migrate,
+    reembed,
+    start,
+    Application,
+    WebApi,
+};
 
 type Config = <WebApi as Application>::Config;
 
@@ -22,6 +31,17 @@ type Config = <WebApi as Application>::Config;
 async fn main() -> Result<(), anyhow::Error> {
     let config = Config::load(application_names!());
     logging::initialize_global(config.logging_config())?;
+    let command = config.command();
     let config = config.finalize(true)?;
-    start::<WebApi>(config).await?.wait_for_termination().await
+    let result = match command {
+        Command::Serve => start::<WebApi>(config).await?.wait_for_termination().await,
+        Command::Migrate => migrate(config).await,
+        Command::Reembed(args) => reembed(config, args).await,
+        Command::CheckConfig => {
+            println!("config is valid");
+            Ok(())
+        }
+    };
+    logging::shutdown_global();
+    result
 }
@@ -23,11 +23,14 @@ use figment::{
     providers::{Env, Format, Serialized, Toml},
     Figment,
 };
+use itertools::Itertools;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 use tracing::warn;
 use xayn_ai_coi::CoiConfig;
 
 use self::cli::Args;
+pub use self::cli::{Command, ReembedArgs};
 use crate::{
     backoffice::IngestionConfig,
     embedding,
@@ -57,6 +60,17 @@ pub struct Config {
     pub(crate) ingestion: IngestionConfig,
     pub(crate) snippet_extractor: xayn_snippet_extractor::Config,
     pub(crate) tenants: tenants::Config,
+    #[as_ref(ignore)]
+    #[serde(skip)]
+    source: Source,
+}
+
+/// The sources from which a config was loaded.
+#[derive(Clone, Debug, Default)]
+struct Source {
+    application_names: Vec<String>,
+    config: Option<String>,
+    overrides: Value,
 }
 
 impl Config {
@@ -80,11 +94,79 @@ impl Config {
     ) -> UnvalidatedConfig {
         load_with_parsed_args(application_names, Args::parse_from(args))
     }
+
+    /// Reloads the config from the sources it was originally loaded from.
+    ///
+    /// Environment variables from `.env` and `.env.local` which are already set in the process
+    /// environment are not updated.
+    pub(crate) fn reload(&self) -> Result<Self, SetupError> {
+        let mut config = load_config::<Self, _>(
+            &self.source.application_names,
+            self.source.config.as_deref(),
+            &self.source.overrides,
+        )?
+        .validate()?;
+        config.source = self.source.clone();
+
+        Ok(config)
+    }
+
+    /// Serializes the sections which can't be changed on a reload.
+    fn non_reloadable(&self) -> Result<Value, SetupError> {
+        let mut config = serde_json::to_value(self)?;
+        if let Some(config) = config.as_object_mut() {
+            config.remove("personalization");
+            config.remove("semantic_search");
+            if let Some(logging) = config.get_mut("logging").and_then(Value::as_object_mut) {
+                logging.remove("level");
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Lists the sections which differ from the other config and can't be reloaded.
+    pub(crate) fn non_reloadable_changes(&self, other: &Self) -> Result<Vec<String>, SetupError> {
+        let this = self.non_reloadable()?;
+        let other = other.non_reloadable()?;
+        let (Some(this), Some(other)) = (this.as_object(), other.as_object()) else {
+            bail!("config isn't serialized as an object");
+        };
+
+        Ok(this
+            .keys()
+            .chain(other.keys())
+            .unique()
+            .filter(|section| this.get(*section) != other.get(*section))
+            .cloned()
+            .collect())
+    }
+
+    fn validate(mut self) -> Result<Self, SetupError> {
+        self.ingestion.validate()?;
+        self.personalization.validate()?;
+        self.semantic_search.validate()?;
+
+        if self.models.is_empty() && self.embedding.is_none() {
+            warn!("using default fallback for model config, models/embedders should be defined explicitly");
+            self.models.inject_default(embedding::Config::default())?;
+        } else if let Some(default) = self.embedding.take() {
+            warn!("moving config \"embedding\" into \"models\" using the name \"default\"");
+            self.models.inject_default(default)?;
+        }
+
+        if self.tenants.enable_legacy_tenant && !self.models.has_default_model() {
+            bail!("legacy tenants require a model/embedder with the name \"default\"");
+        }
+
+        Ok(self)
+    }
 }
 
 pub struct UnvalidatedConfig {
     config: Config,
     print_config: bool,
+    command: Command,
 }
 
 impl UnvalidatedConfig {
@@ -92,6 +174,11 @@ impl UnvalidatedConfig {
         self.config.as_ref()
     }
 
+    /// The operation which was requested through the CLI args.
+    pub fn command(&self) -> Command {
+        self.command.clone()
+    }
+
     /// Finalizes the config doing an post deserialization validation steps.
     ///
     /// If the `--print-config` CLI arg was used a JSON serialization of the config
@@ -99,24 +186,11 @@ impl UnvalidatedConfig {
     /// will exit with a success status code after printing.
     pub fn finalize(self, exit_on_print: bool) -> Result<Config, SetupError> {
         let Self {
-            mut config,
+            config,
             print_config,
+            ..
         } = self;
-        config.ingestion.validate()?;
-        config.personalization.validate()?;
-        config.semantic_search.validate()?;
-
-        if config.models.is_empty() && config.embedding.is_none() {
-            warn!("using default fallback for model config, models/embedders should be defined explicitly");
-            config.models.inject_default(embedding::Config::default())?;
-        } else if let Some(default) = config.embedding.take() {
-            warn!("moving config \"embedding\" into \"models\" using the name \"default\"");
-            config.models.inject_default(default)?;
-        }
-
-        if config.tenants.enable_legacy_tenant && !config.models.has_default_model() {
-            bail!("legacy tenants require a model/embedder with the name \"default\"");
-        }
+        let config = config.validate()?;
 
         if print_config {
             println!("{}", serde_json::to_string_pretty(&config)?);
@@ -132,13 +206,20 @@ fn load_with_parsed_args(
     application_names: impl IntoIterator<Item = impl Display>,
     mut cli_args: Args,
 ) -> UnvalidatedConfig {
-    let config = cli_args.config.take();
-    let config = match load_config(
-        application_names,
-        config.as_deref(),
-        cli_args.to_config_overrides(),
+    let source = Source {
+        application_names: application_names
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect(),
+        config: cli_args.config.take(),
+        overrides: cli_args.to_config_overrides(),
+    };
+    let config = match load_config::<Config, _>(
+        &source.application_names,
+        source.config.as_deref(),
+        &source.overrides,
     ) {
-        Ok(config) => config,
+        Ok(config) => Config { source, ..config },
         Err(err) => {
             eprintln!("Error: {err}");
             cli::Args::command().print_help().ok();
@@ -149,6 +230,7 @@ fn load_with_parsed_args(
     UnvalidatedConfig {
         config,
         print_config: cli_args.print_config,
+        command: cli_args.command.unwrap_or_default(),
     }
 }
 
@@ -238,3 +320,28 @@ fn load_dotenv(file_name: &str) -> Result<(), figment::Error> {
         _ => Ok(()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::filter::LevelFilter;
+
+    use super::*;
+
+    #[test]
+    fn test_non_reloadable_changes() {
+        let config = Config::default();
+        let mut other = Config::default();
+        assert!(config.non_reloadable_changes(&other).unwrap().is_empty());
+
+        other.logging.level = LevelFilter::DEBUG;
+        other.personalization.store_user_history = !config.personalization.store_user_history;
+        other.semantic_search.max_number_documents += 1;
+        assert!(config.non_reloadable_changes(&other).unwrap().is_empty());
+
+        other.ingestion.max_document_batch_size += 1;
+        other.logging.install_panic_hook = !config.logging.install_panic_hook;
+        let mut changes = config.non_reloadable_changes(&other).unwrap();
+        changes.sort();
+        assert_eq!(changes, ["ingestion", "logging"]);
+    }
+}
@@ -14,9 +14,9 @@
 
 use std::{net::SocketAddr, path::PathBuf};
 
-use clap::Parser;
-use serde::Serialize;
+use clap::{Parser, Subcommand};
 use serde_json::{json, Map, Value};
+use xayn_web_api_shared::request::TenantId;
 
 /// Cli arguments for the web-api server.
 #[derive(Parser, Debug)]
@@ -43,10 +43,49 @@ pub(super) struct Args {
     /// Print the config and exist instead of running the server
     #[arg(long)]
     pub(super) print_config: bool,
+
+    #[command(subcommand)]
+    pub(super) command: Option<Command>,
+}
+
+/// The operations of the web-api binaries.
+#[derive(Subcommand, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Command {
+    /// Run the server (default)
+    #[default]
+    Serve,
+    /// Run the database migrations and exit
+    Migrate,
+    /// Validate the config and exit
+    CheckConfig,
+    /// Re-embed the documents of a tenant into a new index and switch to it
+    Reembed(ReembedArgs),
+}
+
+/// Cli arguments for re-embedding the documents of a tenant.
+#[derive(clap::Args, Clone, Debug, PartialEq, Eq)]
+pub struct ReembedArgs {
+    /// The tenant whose documents are re-embedded.
+    #[arg(long)]
+    pub(crate) tenant: TenantId,
+
+    /// The new es index, it is created if it doesn't exist yet.
+    #[arg(long)]
+    pub(crate) index: String,
+
+    /// Resume after the document with this id.
+    ///
+    /// The id of the last document of each batch is logged.
+    #[arg(long)]
+    pub(crate) after: Option<String>,
+
+    /// Number of documents which are re-embedded per batch.
+    #[arg(long, default_value_t = 100)]
+    pub(crate) batch_size: usize,
 }
 
 impl Args {
-    pub(super) fn to_config_overrides(&self) -> impl Serialize {
+    pub(super) fn to_config_overrides(&self) -> Value {
         let mut map = Map::new();
         if let Some(bind_to) = &self.bind_to {
             map.insert(String::from("net"), json!({ "bind_to": bind_to }));
@@ -12,16 +12,26 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use anyhow::bail;
 use aws_config::retry::RetryConfig;
 use aws_sdk_sagemakerruntime::{config::Region, primitives::Blob};
+use itertools::Itertools;
+use ndarray::Array1;
 use secrecy::{ExposeSecret, Secret};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use tokio::{sync::Semaphore, task::spawn_blocking};
+use tracing::info;
 use url::Url;
-use xayn_ai_bert::{AvgEmbedder, Config as EmbedderConfig, Embedding1, NormalizedEmbedding};
+use xayn_ai_bert::{
+    AvgEmbedder,
+    Config as EmbedderConfig,
+    Embedding1,
+    FirstEmbedder,
+    NormalizedEmbedding,
+};
 use xayn_web_api_shared::serde::serialize_redacted;
 
 use crate::{app::SetupError, error::common::InternalError, utils::RelativePathBuf};
@@ -72,6 +82,103 @@ pub(crate) struct Prefix {
     pub(crate) snippet: String,
 }
 
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Pooler {
+    /// Averages the token embeddings.
+    #[default]
+    Average,
+    /// Takes the embedding of the first token, ie the `[CLS]` token.
+    #[serde(alias = "cls")]
+    First,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ChunkPooling {
+    #[default]
+    Average,
+    Max,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub(crate) struct Chunking {
+    /// Whether long content is embedded in overlapping windows.
+    pub(crate) enabled: bool,
+    /// The number of words per window.
+    pub(crate) window: usize,
+    /// The number of words shared by consecutive windows.
+    pub(crate) overlap: usize,
+    /// How the embeddings of the windows are combined.
+    pub(crate) pooling: ChunkPooling,
+}
+
+impl Default for Chunking {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 200,
+            overlap: 50,
+            pooling: ChunkPooling::default(),
+        }
+    }
+}
+
+impl Chunking {
+    fn validate(&self) -> Result<(), SetupError> {
+        if self.window == 0 {
+            bail!("invalid chunking config, window must be positive");
+        }
+        if self.overlap >= self.window {
+            bail!("invalid chunking config, overlap must be less than window");
+        }
+
+        Ok(())
+    }
+
+    /// Splits the sequence into overlapping windows of words if it's longer than one window.
+    fn split(&self, sequence: &str) -> Option<Vec<String>> {
+        if !self.enabled {
+            return None;
+        }
+        let words = sequence.split_whitespace().collect_vec();
+        if words.len() <= self.window {
+            return None;
+        }
+
+        let windows = (0..words.len() - self.overlap)
+            .step_by(self.window - self.overlap)
+            .map(|start| words[start..(start + self.window).min(words.len())].join(" "))
+            .collect();
+
+        Some(windows)
+    }
+
+    fn pool(
+        &self,
+        embeddings: impl IntoIterator<Item = NormalizedEmbedding>,
+    ) -> Result<NormalizedEmbedding, InternalError> {
+        let embeddings = embeddings
+            .into_iter()
+            .map(|embedding| Array1::clone(&embedding));
+        let pooled = match self.pooling {
+            // the sum has the same direction as the mean
+            ChunkPooling::Average => embeddings.reduce(|sum, embedding| sum + embedding),
+            ChunkPooling::Max => embeddings.reduce(|mut max, embedding| {
+                max.zip_mut_with(&embedding, |max, value| *max = max.max(*value));
+                max
+            }),
+        }
+        .ok_or_else(|| InternalError::from_message("no chunks to pool"))?;
+
+        Embedding1::from(pooled)
+            .normalize()
+            .map_err(InternalError::from_std)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 #[cfg_attr(test, serde(deny_unknown_fields))]
@@ -81,7 +188,13 @@ pub struct Pipeline {
     #[serde(deserialize_with = "RelativePathBuf::deserialize_string")]
     pub(crate) runtime: RelativePathBuf,
     pub(crate) token_size: usize,
+    /// Token sizes to which the sequences are padded, none of them may exceed the token size.
+    pub(crate) token_buckets: Vec<usize>,
+    pub(crate) pooler: Pooler,
     pub(crate) prefix: Prefix,
+    pub(crate) chunking: Chunking,
+    /// Number of sequences per cpu which are embedded in parallel.
+    pub(crate) threads_per_cpu: f32,
 }
 
 impl Default for Pipeline {
@@ -90,7 +203,11 @@ impl Default for Pipeline {
             directory: "assets".into(),
             runtime: "assets".into(),
             token_size: 250,
+            token_buckets: Vec::new(),
+            pooler: Pooler::default(),
             prefix: Prefix::default(),
+            chunking: Chunking::default(),
+            threads_per_cpu: 1.0,
         }
     }
 }
@@ -99,15 +216,57 @@ impl Pipeline {
     fn load(&self) -> Result<Embedder, SetupError> {
         let config = EmbedderConfig::new(self.directory.relative(), self.runtime.relative())?
             .with_token_size(self.token_size)?
-            .with_pooler();
+            .with_token_buckets(self.token_buckets.iter().copied())?;
         config.validate()?;
-        let embedder = config.build()?;
+        if self.threads_per_cpu <= 0. {
+            bail!("invalid pipeline config, threads_per_cpu must be positive");
+        }
+        let pipeline = match self.pooler {
+            Pooler::Average => PipelineEmbedder::Average(config.with_pooler().build()?),
+            Pooler::First => PipelineEmbedder::First(config.with_pooler().build()?),
+        };
+        self.warm_up(&pipeline)?;
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let workers = (num_cpus::get() as f32 * self.threads_per_cpu)
+            .ceil()
+            .max(1.0) as usize;
+        let inner = InnerEmbedder::Pipeline {
+            pipeline: Arc::new(pipeline),
+            workers: Semaphore::new(workers),
+        };
 
         Ok(Embedder {
             prefix: self.prefix.clone(),
-            inner: InnerEmbedder::Pipeline(embedder),
+            chunking: self.chunking.clone(),
+            inner,
         })
     }
+
+    /// Runs dummy sequences for all token buckets.
+    ///
+    /// The onnx runtime optimizes the model lazily for each input shape, which otherwise delays the
+    /// first requests. The server only starts to listen after the models are loaded. Without
+    /// buckets the sequences are only padded to the longest one per batch, hence there are too
+    /// many input shapes to warm up.
+    fn warm_up(&self, pipeline: &PipelineEmbedder) -> Result<(), SetupError> {
+        if self.token_buckets.is_empty() {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+        let token_sizes = &self.token_buckets;
+        for &token_size in token_sizes {
+            // the tokenizer adds a start and an end token
+            pipeline.run(&"a ".repeat(token_size.saturating_sub(2)))?;
+        }
+        info!(?token_sizes, elapsed = ?start.elapsed(), "warmed up embedder");
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -121,6 +280,8 @@ pub struct Sagemaker {
     pub(crate) aws_profile: Option<String>,
     #[serde(default)]
     pub(crate) prefix: Prefix,
+    #[serde(default)]
+    pub(crate) chunking: Chunking,
 }
 
 impl Sagemaker {
@@ -144,6 +305,7 @@ impl Sagemaker {
 
         Ok(Embedder {
             prefix: self.prefix.clone(),
+            chunking: self.chunking.clone(),
             inner: InnerEmbedder::Sagemaker {
                 client,
                 embedding_size: self.embedding_size,
@@ -163,6 +325,8 @@ pub struct OpenAi {
     pub(crate) embedding_size: usize,
     #[serde(default)]
     pub(crate) prefix: Prefix,
+    #[serde(default)]
+    pub(crate) chunking: Chunking,
 }
 
 impl OpenAi {
@@ -187,6 +351,7 @@ impl OpenAi {
 
         Ok(Embedder {
             prefix: self.prefix.clone(),
+            chunking: self.chunking.clone(),
             inner: InnerEmbedder::OpenAi {
                 client,
                 url,
@@ -233,11 +398,17 @@ impl Models {
 
 pub(crate) struct Embedder {
     prefix: Prefix,
+    chunking: Chunking,
     inner: InnerEmbedder,
 }
 
 enum InnerEmbedder {
-    Pipeline(AvgEmbedder),
+    Pipeline {
+        /// The model is shared by all workers, onnx sessions can be run concurrently.
+        pipeline: Arc<PipelineEmbedder>,
+        /// Limits the number of blocking threads which embed in parallel.
+        workers: Semaphore,
+    },
     Sagemaker {
         client: aws_sdk_sagemakerruntime::Client,
         endpoint: String,
@@ -251,6 +422,30 @@ enum InnerEmbedder {
     },
 }
 
+enum PipelineEmbedder {
+    Average(AvgEmbedder),
+    First(FirstEmbedder),
+}
+
+impl PipelineEmbedder {
+    fn run(&self, sequence: &str) -> Result<NormalizedEmbedding, InternalError> {
+        match self {
+            Self::Average(embedder) => embedder.run(sequence),
+            Self::First(embedder) => embedder.run(sequence),
+        }
+        .map_err(InternalError::from_std)?
+        .normalize()
+        .map_err(InternalError::from_std)
+    }
+
+    fn embedding_size(&self) -> usize {
+        match self {
+            Self::Average(embedder) => embedder.embedding_size(),
+            Self::First(embedder) => embedder.embedding_size(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct SagemakerResponse {
     embeddings: Vec<NormalizedEmbedding>,
@@ -274,11 +469,14 @@ pub(crate) enum EmbeddingKind {
 
 impl Embedder {
     pub(crate) async fn load(config: &Config) -> Result<Self, SetupError> {
-        match config {
+        let embedder = match config {
             Config::Pipeline(config) => config.load(),
             Config::Sagemaker(config) => config.load().await,
             Config::OpenAi(config) => config.load(),
-        }
+        }?;
+        embedder.chunking.validate()?;
+
+        Ok(embedder)
     }
 
     pub(crate) async fn run(
@@ -295,26 +493,48 @@ impl Embedder {
                 },
             ) => content,
         };
-        let sequence = format!("{prefix}{sequence}");
 
+        if let (EmbeddingKind::Content, Some(chunks)) = (kind, self.chunking.split(sequence)) {
+            let mut embeddings = Vec::with_capacity(chunks.len());
+            for chunk in chunks {
+                embeddings.push(self.run_inner(&format!("{prefix}{chunk}")).await?);
+            }
+            return self.chunking.pool(embeddings);
+        }
+
+        self.run_inner(&format!("{prefix}{sequence}")).await
+    }
+
+    async fn run_inner(&self, sequence: &str) -> Result<NormalizedEmbedding, InternalError> {
         match &self.inner {
-            InnerEmbedder::Pipeline(embedder) => embedder
-                .run(sequence)
-                .map_err(InternalError::from_std)?
-                .normalize()
-                .map_err(InternalError::from_std),
+            InnerEmbedder::Pipeline { pipeline, workers } => {
+                Self::run_pipeline(pipeline, workers, sequence).await
+            }
             InnerEmbedder::Sagemaker {
                 client,
                 endpoint,
                 target_model,
                 ..
-            } => Self::run_sagemaker(client, endpoint, target_model.as_deref(), &sequence).await,
+            } => Self::run_sagemaker(client, endpoint, target_model.as_deref(), sequence).await,
             InnerEmbedder::OpenAi { client, url, .. } => {
-                Self::run_openai(client, url, &sequence).await
+                Self::run_openai(client, url, sequence).await
             }
         }
     }
 
+    async fn run_pipeline(
+        pipeline: &Arc<PipelineEmbedder>,
+        workers: &Semaphore,
+        sequence: &str,
+    ) -> Result<NormalizedEmbedding, InternalError> {
+        let _worker = workers.acquire().await.map_err(InternalError::from_std)?;
+        let pipeline = pipeline.clone();
+        let sequence = sequence.to_owned();
+        spawn_blocking(move || pipeline.run(&sequence))
+            .await
+            .map_err(InternalError::from_std)?
+    }
+
     async fn run_sagemaker(
         client: &aws_sdk_sagemakerruntime::Client,
         endpoint: &str,
@@ -389,7 +609,7 @@ impl Embedder {
 
     pub(crate) fn embedding_size(&self) -> usize {
         match &self.inner {
-            InnerEmbedder::Pipeline(embedder) => embedder.embedding_size(),
+            InnerEmbedder::Pipeline { pipeline, .. } => pipeline.embedding_size(),
             InnerEmbedder::Sagemaker { embedding_size, .. }
             | InnerEmbedder::OpenAi { embedding_size, .. } => *embedding_size,
         }
@@ -398,7 +618,10 @@ impl Embedder {
 
 #[cfg(test)]
 mod tests {
-    use xayn_test_utils::asset::{ort, xaynia};
+    use xayn_test_utils::{
+        assert_approx_eq,
+        asset::{ort, xaynia},
+    };
 
     use super::*;
 
@@ -412,4 +635,91 @@ mod tests {
         let embedder = Embedder::load(&config).await.unwrap();
         embedder.run(EmbeddingKind::Query, "test").await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_embedder_first_pooler() {
+        let config = |pooler| {
+            Config::Pipeline(Pipeline {
+                directory: xaynia().unwrap().into(),
+                runtime: ort().unwrap().into(),
+                pooler,
+                ..Pipeline::default()
+            })
+        };
+        let average = Embedder::load(&config(Pooler::Average)).await.unwrap();
+        let first = Embedder::load(&config(Pooler::First)).await.unwrap();
+        assert_eq!(average.embedding_size(), first.embedding_size());
+
+        let average = average.run(EmbeddingKind::Query, "test").await.unwrap();
+        let first = first.run(EmbeddingKind::Query, "test").await.unwrap();
+        assert!(average.dot_product(&first) < 1.);
+    }
+
+    #[test]
+    fn test_chunking_split() {
+        let chunking = Chunking {
+            enabled: true,
+            window: 4,
+            overlap: 1,
+            ..Chunking::default()
+        };
+        assert!(chunking.split("a b c d").is_none());
+        assert_eq!(
+            chunking.split("a b c d e f g h").unwrap(),
+            ["a b c d", "d e f g", "g h"],
+        );
+        assert_eq!(
+            chunking.split("a b c d e f g").unwrap(),
+            ["a b c d", "d e f g"],
+        );
+    }
+
+    #[test]
+    fn test_chunking_pool() {
+        let embeddings = || {
+            vec![
+                NormalizedEmbedding::try_from([1., 0., 0.]).unwrap(),
+                NormalizedEmbedding::try_from([0.6, 0.8, 0.]).unwrap(),
+            ]
+        };
+        let chunking = Chunking::default();
+
+        let pooled = chunking.pool(embeddings()).unwrap();
+        let expected = NormalizedEmbedding::try_from([1.6, 0.8, 0.]).unwrap();
+        assert_approx_eq!(f32, pooled, expected);
+
+        let pooled = Chunking {
+            pooling: ChunkPooling::Max,
+            ..chunking
+        }
+        .pool(embeddings())
+        .unwrap();
+        let expected = NormalizedEmbedding::try_from([1., 0.8, 0.]).unwrap();
+        assert_approx_eq!(f32, pooled, expected);
+    }
+
+    #[tokio::test]
+    async fn test_embedder_chunking() {
+        let config = Config::Pipeline(Pipeline {
+            directory: xaynia().unwrap().into(),
+            runtime: ort().unwrap().into(),
+            chunking: Chunking {
+                enabled: true,
+                window: 2,
+                overlap: 1,
+                ..Chunking::default()
+            },
+            ..Pipeline::default()
+        });
+        let embedder = Embedder::load(&config).await.unwrap();
+        let chunked = embedder
+            .run(EmbeddingKind::Content, "this is a test")
+            .await
+            .unwrap();
+        let whole = embedder
+            .run(EmbeddingKind::Query, "this is a test")
+            .await
+            .unwrap();
+        assert!(chunked.dot_product(&whole) < 1.);
+    }
 }
@@ -12,7 +12,17 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use actix_web::{body::BoxBody, http::StatusCode, HttpResponse, ResponseError};
+use std::time::Duration;
+
+use actix_web::{
+    body::BoxBody,
+    http::{
+        header::{HeaderValue, RETRY_AFTER},
+        StatusCode,
+    },
+    HttpResponse,
+    ResponseError,
+};
 use derive_more::{Deref, Display};
 use serde::Serialize;
 use serde_json::Value;
@@ -70,13 +80,22 @@ impl ResponseError for Error {
         // might want to have in the logs.
         application_event!(self.level(), error=%self.error);
         let request_id =
-            RequestId::extract_from_task_local_storage().unwrap_or(RequestId::missing());
-        JsonErrorResponseBuilder::render(
+            RequestId::extract_from_task_local_storage().unwrap_or_else(|_| RequestId::missing());
+        let mut response = JsonErrorResponseBuilder::render(
             self.error.kind(),
-            request_id,
+            &request_id,
             &self.error.encode_details(),
         )
-        .into_response(self.error.status_code())
+        .into_response(self.error.status_code());
+        if let Some(retry_after) = self.error.retry_after() {
+            // Hint: the header expects whole seconds, so partial seconds are rounded up
+            let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(seconds));
+        }
+
+        response
     }
 }
 
@@ -87,6 +106,11 @@ pub trait ApplicationError: std::error::Error + Send + Sync + 'static {
     fn encode_details(&self) -> Value {
         Value::Null
     }
+
+    /// The time after which the request can be retried, sent as `Retry-After` header.
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// Implements `ApplicationError` for given type using given http status code.
@@ -16,6 +16,7 @@ use std::{
     borrow::Cow,
     fmt::{Debug, Display},
     ops::{Bound, RangeBounds},
+    time::Duration,
 };
 
 use actix_web::http::StatusCode;
@@ -50,6 +51,12 @@ pub(crate) struct DocumentPropertyNotFound;
 
 impl_application_error!(DocumentPropertyNotFound => BAD_REQUEST, INFO);
 
+/// The requested boost rule was not found.
+#[derive(Debug, Error, Display, Serialize)]
+pub(crate) struct BoostRuleNotFound;
+
+impl_application_error!(BoostRuleNotFound => BAD_REQUEST, INFO);
+
 #[derive(Debug, Error, Display, Serialize)]
 #[cfg_attr(test, derive(PartialEq))]
 #[serde(rename_all = "snake_case")]
@@ -123,6 +130,32 @@ pub(crate) struct InvalidUserId(#[from] InvalidString);
 
 impl_application_error!(InvalidUserId => BAD_REQUEST, INFO);
 
+/// Malformed idempotency key: {0}
+#[derive(Debug, Error, Display, Serialize)]
+#[serde(transparent)]
+pub(crate) struct InvalidIdempotencyKey(#[from] InvalidString);
+
+impl_application_error!(InvalidIdempotencyKey => BAD_REQUEST, INFO);
+
+/// Malformed boost rule id: {0}
+#[derive(Debug, Error, Display, Serialize)]
+#[serde(transparent)]
+pub(crate) struct InvalidBoostRuleId(#[from] InvalidString);
+
+impl_application_error!(InvalidBoostRuleId => BAD_REQUEST, INFO);
+
+/// A request with the same idempotency key is still in progress.
+#[derive(Debug, Error, Display, Serialize)]
+pub(crate) struct IdempotencyKeyInUse;
+
+impl_application_error!(IdempotencyKeyInUse => CONFLICT, INFO);
+
+/// A previous request with the same idempotency key had a different body.
+#[derive(Debug, Error, Display, Serialize)]
+pub(crate) struct IdempotencyKeyMismatch;
+
+impl_application_error!(IdempotencyKeyMismatch => UNPROCESSABLE_ENTITY, INFO);
+
 /// Malformed document id: {0}
 #[derive(Debug, Error, Display, Serialize)]
 #[cfg_attr(test, derive(PartialEq))]
@@ -186,6 +219,20 @@ pub(crate) enum InvalidDocumentProperties {
 
 impl_application_error!(InvalidDocumentProperties => BAD_REQUEST, INFO);
 
+#[derive(Debug, Error, Display, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum InvalidFacetProperty {
+    /// The property {property_id} is not indexed.
+    Unindexed { property_id: DocumentPropertyId },
+    /// The property {property_id} of type {property_type:?} can't be counted by values.
+    UnsupportedType {
+        property_id: DocumentPropertyId,
+        property_type: IndexedPropertyType,
+    },
+}
+
+impl_application_error!(InvalidFacetProperty => BAD_REQUEST, INFO);
+
 /// Malformed document tag: {0}
 #[derive(Debug, Error, Display, Serialize)]
 #[cfg_attr(test, derive(PartialEq))]
@@ -203,6 +250,48 @@ pub(crate) struct InvalidDocumentTags {
 
 impl_application_error!(InvalidDocumentTags => BAD_REQUEST, INFO);
 
+/// Malformed document source: {0}
+#[derive(Debug, Error, Display, Serialize)]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(transparent)]
+pub(crate) struct InvalidDocumentSource(#[from] InvalidString);
+
+impl_application_error!(InvalidDocumentSource => BAD_REQUEST, INFO);
+
+/// To many sources. Got {size}, expect at most {max}.
+#[derive(Debug, Error, Display, Serialize)]
+pub(crate) struct InvalidSourcePreferences {
+    pub(crate) size: usize,
+    pub(crate) max: usize,
+}
+
+impl_application_error!(InvalidSourcePreferences => BAD_REQUEST, INFO);
+
+#[derive(Debug, Error, Display, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum InvalidPinnedDocuments {
+    /// To many pinned documents. Got {size}, expect at most {max}.
+    TooMany { size: usize, max: usize },
+    /// Invalid position of a pinned document. Got {position}, expect at most {max}.
+    Position { position: usize, max: usize },
+    /// Several documents are pinned to position {position}.
+    DuplicatePosition { position: usize },
+    /// The document {id} is pinned several times.
+    DuplicateDocument { id: DocumentId },
+    /// Some pinned documents don't exist: {documents:?}.
+    NotFound { documents: Vec<DocumentId> },
+}
+
+impl_application_error!(InvalidPinnedDocuments => BAD_REQUEST, INFO);
+
+/// Invalid boost rule weight. Got {weight}, expected a weight in -1..=1.
+#[derive(Debug, Error, Display, Serialize)]
+pub(crate) struct InvalidBoostRuleWeight {
+    pub(crate) weight: f32,
+}
+
+impl_application_error!(InvalidBoostRuleWeight => BAD_REQUEST, INFO);
+
 #[derive(Debug, Error, Display, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub(crate) enum InvalidDocumentSnippet {
@@ -255,6 +344,24 @@ pub(crate) struct InvalidDocumentCount {
 
 impl_application_error!(InvalidDocumentCount => BAD_REQUEST, INFO);
 
+/// Invalid score weights. Got {weights:?}, expected weights in 0..=1 with at least one > 0.
+#[derive(Debug, Error, Display, Serialize)]
+pub(crate) struct InvalidScoreWeights {
+    pub(crate) weights: [f32; 3],
+}
+
+impl_application_error!(InvalidScoreWeights => BAD_REQUEST, INFO);
+
+/// Invalid min similarity. Got {min_similarity}, expected a value in -1..=1.
+#[derive(Debug, Error, Display, Serialize)]
+// there are some false positives with clippy and displaydoc
+#[allow(clippy::doc_markdown)]
+pub(crate) struct InvalidMinSimilarity {
+    pub(crate) min_similarity: f32,
+}
+
+impl_application_error!(InvalidMinSimilarity => BAD_REQUEST, INFO);
+
 #[derive(Debug, Display, Error, Serialize)]
 pub(crate) enum ForbiddenDevOption {
     /// Dev options are not enabled for this tentant
@@ -271,6 +378,15 @@ pub(crate) struct FailedToDeleteSomeDocuments {
 
 impl_application_error!(FailedToDeleteSomeDocuments => BAD_REQUEST, INFO);
 
+/// The document was rejected by the {stage} moderation stage: {reason}.
+#[derive(Debug, Error, Display, Serialize)]
+pub(crate) struct DocumentRejected {
+    pub(crate) stage: &'static str,
+    pub(crate) reason: String,
+}
+
+impl_application_error!(DocumentRejected => BAD_REQUEST, INFO);
+
 /// The validation of some documents failed.
 #[derive(Debug, Error, Display, Serialize)]
 pub(crate) struct FailedToValidateDocuments {
@@ -295,6 +411,17 @@ impl DocumentInBatchError {
         }
     }
 }
+
+/// The document is a near duplicate of document {duplicate_of}.
+#[derive(Debug, Error, Display, Serialize)]
+// there are some false positives with clippy and displaydoc
+#[allow(clippy::doc_markdown)]
+pub(crate) struct DuplicateDocument {
+    pub(crate) duplicate_of: DocumentId,
+}
+
+impl_application_error!(DuplicateDocument => BAD_REQUEST, INFO);
+
 /// The ingestion of some documents failed.
 #[derive(Debug, Error, Display, Serialize)]
 pub(crate) struct FailedToIngestDocuments {
@@ -317,6 +444,14 @@ pub(crate) struct HistoryTooSmall;
 
 impl_application_error!(HistoryTooSmall => BAD_REQUEST, INFO);
 
+/// The config can't be reloaded due to changes of the non-reloadable sections {sections:?}.
+#[derive(Debug, Error, Display, Serialize)]
+pub(crate) struct NonReloadableConfigChanges {
+    pub(crate) sections: Vec<String>,
+}
+
+impl_application_error!(NonReloadableConfigChanges => CONFLICT, INFO);
+
 impl_application_error!(IncompatibleUpdate => BAD_REQUEST, INFO);
 
 /// Custom error for 400 Bad Request status code: {message}
@@ -343,9 +478,39 @@ impl From<String> for BadRequest {
     }
 }
 
+/// The service is temporarily unavailable, retry after {retry_after:?}.
+#[derive(Debug, Error, Display)]
+// there are some false positives with clippy and displaydoc
+#[allow(clippy::doc_markdown)]
+pub(crate) struct ServiceUnavailable {
+    pub(crate) retry_after: Duration,
+}
+
+impl ApplicationError for ServiceUnavailable {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+
+    fn kind(&self) -> &str {
+        "ServiceUnavailable"
+    }
+
+    fn level(&self) -> Level {
+        Level::WARN
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        Some(self.retry_after)
+    }
+}
+
 impl From<elastic::Error> for Error {
     fn from(error: elastic::Error) -> Self {
-        InternalError::from_std(error).into()
+        if let elastic::Error::Unavailable { retry_after } = error {
+            ServiceUnavailable { retry_after }.into()
+        } else {
+            InternalError::from_std(error).into()
+        }
     }
 }
 
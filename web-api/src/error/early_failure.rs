@@ -50,6 +50,6 @@ pub(crate) fn middleware_failure(
     let (request, _) = request.into_parts();
     ServiceResponse::new(
         request,
-        JsonErrorResponseBuilder::internal_server_error(request_id),
+        JsonErrorResponseBuilder::internal_server_error(&request_id),
     )
 }
@@ -30,12 +30,12 @@ pub(crate) struct JsonErrorResponseBuilder {
 }
 
 impl JsonErrorResponseBuilder {
-    pub(crate) fn internal_server_error(request_id: RequestId) -> HttpResponse {
+    pub(crate) fn internal_server_error(request_id: &RequestId) -> HttpResponse {
         JsonErrorResponseBuilder::render("InternalServerError", request_id, &Value::Null)
             .into_response(StatusCode::INTERNAL_SERVER_ERROR)
     }
 
-    pub(crate) fn render(kind: &str, request_id: RequestId, details: &Value) -> Self {
+    pub(crate) fn render(kind: &str, request_id: &RequestId, details: &Value) -> Self {
         match serde_json::to_vec(&json!({
             "kind": kind,
             "request_id": request_id,
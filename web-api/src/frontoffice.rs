@@ -24,8 +24,16 @@ use std::ops::RangeBounds;
 use anyhow::bail;
 use serde::{Deserialize, Serialize};
 
-pub use self::{rerank::bench_rerank, stateless::bench_derive_interests};
-use crate::app::SetupError;
+use self::shared::validate_score_weights;
+pub use self::{
+    rerank::{bench_diversify, bench_rerank},
+    stateless::bench_derive_interests,
+};
+use crate::{
+    app::SetupError,
+    models::{PersonalizedDocument, SnippetId},
+    rank_merge::{normalize_scores_min_max, normalize_scores_softmax},
+};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(default)]
@@ -44,10 +52,36 @@ pub(crate) struct PersonalizationConfig {
     /// Max number of cois to use in knn search.
     pub(crate) max_cois_for_knn: usize,
 
-    /// Weights for reranking of the scores. Each weight is in `[0, 1]` and they add up to `1`. The
-    /// order is `[interest_weight, tag_weight, elasticsearch_weight]`.
+    /// Max age in days of the documents considered for recommendations wrt their
+    /// `publication_date` property. If set, documents without a `publication_date` are not
+    /// considered.
+    pub(crate) max_document_age: Option<u32>,
+
+    /// Weights for reranking of the scores. Each weight is in `[0, 1]` and at least one of them is
+    /// positive, they determine the ratios of the scores. The order is
+    /// `[interest_weight, tag_weight, elasticsearch_weight]`.
     pub(crate) score_weights: [f32; 3],
 
+    /// Tradeoff between relevance and diversity for the maximal marginal relevance reranking. The
+    /// value is in `[0, 1]`, where `1` keeps the relevance ranking as is and `0` only considers the
+    /// diversity of the documents. Recommendations which are sorted by date aren't diversified.
+    pub(crate) mmr_lambda: f32,
+
+    /// Half-life in days of the interactions for the trending documents. Each interaction of any
+    /// user contributes to the trending score with an exponential decay wrt its age.
+    pub(crate) trending_half_life: u32,
+
+    /// Half-life in days of the publication date for the `freshness` sorting of recommendations.
+    /// The score of each document decays exponentially wrt its age.
+    pub(crate) freshness_half_life: u32,
+
+    /// Number of days for which the daily interaction counts of all users are kept.
+    pub(crate) interaction_count_retention: u32,
+
+    /// Number of days after which the dismissals of documents by a user expire. If not set, the
+    /// dismissals don't expire.
+    pub(crate) dismissal_expiration: Option<u32>,
+
     /// Whether to store the history of user interactions.
     pub(crate) store_user_history: bool,
 
@@ -56,6 +90,18 @@ pub(crate) struct PersonalizationConfig {
 
     /// The maximal number of history entries used when calculating CoIs from a stateless user history.
     pub(crate) max_stateless_history_for_cois: usize,
+
+    /// Min similarity in `[-1, 1]` of a document to the closest interest of the user. Less similar
+    /// documents are dropped from the recommendations. If not set, no documents are dropped.
+    pub(crate) min_similarity: Option<f32>,
+
+    /// Share in `[0, 1]` of the recommended documents which are replaced by trending documents to
+    /// explore beyond the interests of the user. The exploration is disabled for `0`.
+    ///
+    /// Only the trending documents are explored, i.e. documents which other users recently
+    /// interacted with. Hence there is no exploration as long as there are no interactions within
+    /// the `trending_half_life`, e.g. for new documents or new tenants.
+    pub(crate) exploration_share: f32,
 }
 
 impl Default for PersonalizationConfig {
@@ -66,10 +112,18 @@ impl Default for PersonalizationConfig {
             default_number_documents: 10,
             // FIXME: what is a default value we know works well with how we do knn?
             max_cois_for_knn: 10,
+            max_document_age: None,
             score_weights: [1., 1., 0.],
+            mmr_lambda: 1.,
+            trending_half_life: 7,
+            freshness_half_life: 1,
+            interaction_count_retention: 30,
+            dismissal_expiration: None,
             store_user_history: true,
             max_stateless_history_size: 200,
             max_stateless_history_for_cois: 20,
+            min_similarity: None,
+            exploration_share: 0.,
         }
     }
 }
@@ -83,6 +137,35 @@ impl PersonalizationConfig {
         if self.default_number_documents > self.max_number_documents {
             bail!("invalid PersonalizationConfig, default_number_documents must be <= max_number_documents");
         }
+        if self.max_document_age == Some(0) {
+            bail!("invalid PersonalizationConfig, max_document_age must be > 0");
+        }
+        if validate_score_weights(self.score_weights).is_err() {
+            bail!("invalid PersonalizationConfig, score_weights must be in [0, 1] and not all 0");
+        }
+        if !(0. ..=1.).contains(&self.mmr_lambda) {
+            bail!("invalid PersonalizationConfig, mmr_lambda must be in [0, 1]");
+        }
+        if self.trending_half_life == 0 {
+            bail!("invalid PersonalizationConfig, trending_half_life must be > 0");
+        }
+        if self.freshness_half_life == 0 {
+            bail!("invalid PersonalizationConfig, freshness_half_life must be > 0");
+        }
+        if self.interaction_count_retention == 0 {
+            bail!("invalid PersonalizationConfig, interaction_count_retention must be > 0");
+        }
+        if self.dismissal_expiration == Some(0) {
+            bail!("invalid PersonalizationConfig, dismissal_expiration must be > 0");
+        }
+        if self.min_similarity.map_or(false, |min_similarity| {
+            !(-1. ..=1.).contains(&min_similarity)
+        }) {
+            bail!("invalid PersonalizationConfig, min_similarity must be in [-1, 1]");
+        }
+        if !(0. ..=1.).contains(&self.exploration_share) {
+            bail!("invalid PersonalizationConfig, exploration_share must be in [0, 1]");
+        }
 
         Ok(())
     }
@@ -102,10 +185,15 @@ pub(crate) struct SemanticSearchConfig {
     /// Default number of documents to return.
     pub(crate) default_number_documents: usize,
 
-    /// Weights for reranking of the scores. Each weight is in `[0, 1]` and they add up to `1`. The
-    /// order is `[interest_weight, tag_weight, elasticsearch_weight]`.
+    /// Weights for reranking of the scores. Each weight is in `[0, 1]` and at least one of them is
+    /// positive, they determine the ratios of the scores. The order is
+    /// `[interest_weight, tag_weight, elasticsearch_weight]`.
     pub(crate) score_weights: [f32; 3],
 
+    /// Normalization of the final scores of the documents in the response. The order of the
+    /// documents is kept.
+    pub(crate) score_normalization: ScoreNormalization,
+
     /// Max number of bytes a query can have
     ///
     /// Hint: Use [`Self.query_size_bounds()`] to access this.
@@ -125,11 +213,49 @@ impl Default for SemanticSearchConfig {
             max_number_candidates: 100,
             default_number_documents: 10,
             score_weights: [1., 1., 0.5],
+            score_normalization: ScoreNormalization::default(),
             max_query_size: 512,
         }
     }
 }
 
+/// A normalization of the scores of documents.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ScoreNormalization {
+    /// The scores are kept as is.
+    #[default]
+    Identity,
+    /// The scores are normalized to the unit interval wrt their minimum and maximum.
+    MinMax,
+    /// The scores are normalized to a probability distribution.
+    Softmax,
+}
+
+impl ScoreNormalization {
+    /// Normalizes the scores of the documents.
+    ///
+    /// The normalizations are monotonic, hence the order of the documents is kept.
+    pub(crate) fn apply(self, documents: &mut [PersonalizedDocument]) {
+        let normalize = match self {
+            Self::Identity => return,
+            Self::MinMax => normalize_scores_min_max::<SnippetId>,
+            Self::Softmax => normalize_scores_softmax::<SnippetId>,
+        };
+        let scores = normalize(
+            documents
+                .iter()
+                .map(|document| (document.id.clone(), document.score))
+                .collect(),
+        );
+        for document in documents {
+            if let Some(&score) = scores.get(&document.id) {
+                document.score = score;
+            }
+        }
+    }
+}
+
 impl SemanticSearchConfig {
     pub(crate) fn validate(&self) -> Result<(), SetupError> {
         if self.max_number_documents > self.max_number_candidates {
@@ -139,6 +265,9 @@ impl SemanticSearchConfig {
         if self.default_number_documents > self.max_number_documents {
             bail!("invalid SemanticSearchConfig, default_number_documents must be <= max_number_documents");
         }
+        if validate_score_weights(self.score_weights).is_err() {
+            bail!("invalid SemanticSearchConfig, score_weights must be in [0, 1] and not all 0");
+        }
         if self.max_query_size < 1 {
             bail!("max_query_size needs to be at least 1");
         }
@@ -158,6 +287,20 @@ mod tests {
 
     #[test]
     fn test_validate_default_semantic_search_config() {
-        PersonalizationConfig::default().validate().unwrap();
+        SemanticSearchConfig::default().validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate_semantic_search_score_weights() {
+        let config = SemanticSearchConfig {
+            score_weights: [0., 0., 0.],
+            ..SemanticSearchConfig::default()
+        };
+        assert!(config.validate().is_err());
+        let config = SemanticSearchConfig {
+            score_weights: [1., 2., 0.],
+            ..SemanticSearchConfig::default()
+        };
+        assert!(config.validate().is_err());
     }
 }
@@ -41,6 +41,7 @@ pub(super) struct CoiSearch<'a, I> {
     pub(super) include_properties: bool,
     pub(super) include_snippet: bool,
     pub(super) filter: Option<&'a Filter>,
+    pub(super) published_after: Option<DateTime<Utc>>,
 }
 
 impl<'a, I> CoiSearch<'a, I>
@@ -85,6 +86,7 @@ where
                         include_properties: self.include_properties,
                         include_snippet: self.include_snippet,
                         filter: self.filter,
+                        published_after: self.published_after,
                         with_raw_scores: false,
                     },
                 )
@@ -164,6 +166,7 @@ mod tests {
             include_properties: false,
             include_snippet: false,
             filter: None,
+            published_after: None,
         }
         .run_on(&storage)
         .await
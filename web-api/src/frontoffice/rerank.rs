@@ -12,17 +12,18 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, hash::BuildHasher};
+use std::{cmp::Reverse, collections::HashMap, hash::BuildHasher, time::Duration};
 
 use chrono::{DateTime, Utc};
 use itertools::Itertools;
+use serde::Deserialize;
 use xayn_ai_bert::NormalizedEmbedding;
 use xayn_ai_coi::{Coi, CoiSystem};
 use xayn_web_api_shared::elastic::ScoreMap;
 
 use super::PersonalizationConfig;
 use crate::{
-    models::{DocumentTag, PersonalizedDocument, SnippetId},
+    models::{BoostRule, DocumentTag, DocumentTags, PersonalizedDocument, SnippetId},
     rank_merge::{rrf, DEFAULT_RRF_K},
 };
 
@@ -110,6 +111,183 @@ pub(crate) fn rerank(
     });
 }
 
+/// Boosts or buries the reranked documents by the editorial rules.
+///
+/// The weights of all rules which match a property of a document are summed up and scaled by the
+/// range of the scores before they are added to its score, ie a weight of `1` lifts the lowest
+/// scored document to the score of the highest scored one independent of the scale of the scores.
+/// Documents without loaded properties are not affected.
+pub(crate) fn boost(documents: &mut [PersonalizedDocument], rules: &[BoostRule]) {
+    if documents.is_empty() || rules.is_empty() {
+        return;
+    }
+
+    let (min, max) = documents.iter().fold(
+        (f32::INFINITY, f32::NEG_INFINITY),
+        |(min, max), document| (min.min(document.score), max.max(document.score)),
+    );
+    // Hint: equal scores are boosted as if they were normalized
+    let range = if max > min { max - min } else { 1. };
+    for document in documents.iter_mut() {
+        let Some(properties) = &document.properties else {
+            continue;
+        };
+        let weight = rules
+            .iter()
+            .filter(|rule| properties.get(&rule.property_id) == Some(&rule.value))
+            .map(|rule| rule.weight)
+            .sum::<f32>();
+        document.score += weight * range;
+    }
+
+    documents.sort_unstable_by(|d1, d2| {
+        d1.score
+            .total_cmp(&d2.score)
+            .then_with(|| d1.id.cmp(&d2.id))
+            .reverse()
+    });
+}
+
+/// Diversifies the ranking of documents by maximal marginal relevance.
+///
+/// The documents are greedily selected by their min-max normalized scores penalized with their
+/// maximal cosine similarity to the already selected documents. A `lambda` of `1` keeps the
+/// ranking as is, whereas a `lambda` of `0` only considers the diversity of the documents.
+///
+/// The documents are ordered by their selection and the scores are reassigned by position, such
+/// that they still decrease in the order of the documents.
+pub(crate) fn diversify(documents: &mut [PersonalizedDocument], lambda: f32) {
+    if documents.len() < 2 || lambda >= 1. {
+        return;
+    }
+
+    let (min, max) = documents.iter().fold(
+        (f32::INFINITY, f32::NEG_INFINITY),
+        |(min, max), document| (min.min(document.score), max.max(document.score)),
+    );
+    let range = max - min;
+    let relevances = documents
+        .iter()
+        .map(|document| {
+            if range > 0. {
+                (document.score - min) / range
+            } else {
+                1.
+            }
+        })
+        .collect_vec();
+
+    let mut similarities = vec![f32::NEG_INFINITY; documents.len()];
+    let mut ranks = vec![None; documents.len()];
+    for rank in 0..documents.len() {
+        let Some((selected, _)) = ranks
+            .iter()
+            .enumerate()
+            .filter(|(_, rank)| rank.is_none())
+            .map(|(idx, _)| {
+                let penalty = similarities[idx].max(0.);
+                (idx, lambda * relevances[idx] - (1. - lambda) * penalty)
+            })
+            .max_by(|(i1, s1), (i2, s2)| {
+                s1.total_cmp(s2)
+                    .then_with(|| documents[*i1].id.cmp(&documents[*i2].id))
+            })
+        else {
+            break;
+        };
+        ranks[selected] = Some(rank);
+        for (idx, similarity) in similarities.iter_mut().enumerate() {
+            if ranks[idx].is_none() {
+                *similarity = similarity.max(
+                    documents[idx]
+                        .embedding
+                        .dot_product(&documents[selected].embedding),
+                );
+            }
+        }
+    }
+
+    let mut scores = documents
+        .iter()
+        .map(|document| document.score)
+        .collect_vec();
+    scores.sort_unstable_by(|s1, s2| s1.total_cmp(s2).reverse());
+    let mut ranks = ranks
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .unwrap(/* each document has been selected */);
+    for idx in 0..documents.len() {
+        while ranks[idx] != idx {
+            let rank = ranks[idx];
+            documents.swap(idx, rank);
+            ranks.swap(idx, rank);
+        }
+    }
+    for (document, score) in documents.iter_mut().zip(scores) {
+        document.score = score;
+    }
+}
+
+/// The order of the recommended documents.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SortBy {
+    /// By the personalized scores.
+    #[default]
+    Score,
+    /// By the publication dates from the newest to the oldest one.
+    PublicationDate,
+    /// By the personalized scores decayed wrt the age of the publication dates.
+    Freshness,
+}
+
+/// Parses the `publication_date` property of the document, if any.
+pub(crate) fn publication_date(document: &PersonalizedDocument) -> Option<DateTime<Utc>> {
+    let date = document
+        .properties
+        .as_ref()?
+        .get("publication_date")?
+        .as_str()?;
+
+    DateTime::parse_from_rfc3339(date)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Sorts the reranked documents.
+///
+/// Documents without a valid `publication_date` property are sorted last by the publication date
+/// and their scores decay to zero for the freshness. The freshness halves the score of a document
+/// for each `half_life` of its age.
+pub(crate) fn sort(
+    documents: &mut [PersonalizedDocument],
+    sort_by: SortBy,
+    half_life: Duration,
+    time: DateTime<Utc>,
+) {
+    match sort_by {
+        SortBy::Score => {}
+        SortBy::PublicationDate => {
+            documents.sort_by_cached_key(|document| Reverse(publication_date(document)));
+        }
+        SortBy::Freshness => {
+            for document in documents.iter_mut() {
+                let decay = publication_date(document).map_or(0., |date| {
+                    let age = (time - date).to_std().unwrap_or_default();
+                    0.5_f32.powf(age.as_secs_f32() / half_life.as_secs_f32())
+                });
+                document.score *= decay;
+            }
+            documents.sort_unstable_by(|d1, d2| {
+                d1.score
+                    .total_cmp(&d2.score)
+                    .then_with(|| d1.id.cmp(&d2.id))
+                    .reverse()
+            });
+        }
+    }
+}
+
 #[doc(hidden)]
 pub fn bench_rerank<S>(
     coi_system: &CoiSystem,
@@ -154,6 +332,25 @@ pub fn bench_rerank<S>(
     );
 }
 
+#[doc(hidden)]
+pub fn bench_diversify(documents: Vec<(NormalizedEmbedding, f32)>, lambda: f32) {
+    // small allocation overhead, but we don't have to expose a lot of private items
+    let mut documents = documents
+        .into_iter()
+        .enumerate()
+        .map(|(id, (embedding, score))| PersonalizedDocument {
+            id: SnippetId::new(id.to_string().try_into().unwrap(), 0),
+            score,
+            embedding,
+            properties: None,
+            snippet: None,
+            tags: DocumentTags::default(),
+            dev: None,
+        })
+        .collect_vec();
+    diversify(&mut documents, lambda);
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -301,4 +498,192 @@ mod tests {
             assert_approx_eq!(f32, reranked[&&one], reranked[&&id]);
         }
     }
+
+    #[test]
+    fn test_sort_by_publication_date() {
+        let mut documents = mock_documents(3);
+        documents[0].properties = serde_json::from_value(
+            serde_json::json!({ "publication_date": "2023-01-01T00:00:00Z" }),
+        )
+        .unwrap();
+        documents[2].properties = serde_json::from_value(
+            serde_json::json!({ "publication_date": "2023-02-01T00:00:00Z" }),
+        )
+        .unwrap();
+
+        sort(
+            &mut documents,
+            SortBy::PublicationDate,
+            Duration::ZERO,
+            Utc::now(),
+        );
+        let ids = documents
+            .iter()
+            .map(|document| document.id.clone())
+            .collect_vec();
+        assert_eq!(
+            ids,
+            [
+                SnippetId::new("2".try_into().unwrap(), 0),
+                SnippetId::new("0".try_into().unwrap(), 0),
+                SnippetId::new("1".try_into().unwrap(), 0),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sort_by_freshness() {
+        let mut documents = mock_documents(3);
+        documents[0].properties = serde_json::from_value(
+            serde_json::json!({ "publication_date": "2023-01-01T00:00:00Z" }),
+        )
+        .unwrap();
+        documents[1].score = 0.6;
+        documents[1].properties = serde_json::from_value(
+            serde_json::json!({ "publication_date": "2023-01-03T00:00:00Z" }),
+        )
+        .unwrap();
+        let time = "2023-01-03T00:00:00Z".parse().unwrap();
+
+        sort(
+            &mut documents,
+            SortBy::Freshness,
+            Duration::from_secs(24 * 60 * 60),
+            time,
+        );
+        let ids = documents
+            .iter()
+            .map(|document| document.id.clone())
+            .collect_vec();
+        assert_eq!(
+            ids,
+            [
+                SnippetId::new("1".try_into().unwrap(), 0),
+                SnippetId::new("0".try_into().unwrap(), 0),
+                SnippetId::new("2".try_into().unwrap(), 0),
+            ],
+        );
+        assert_approx_eq!(f32, documents[0].score, 0.6);
+        assert_approx_eq!(f32, documents[1].score, 0.25);
+        assert_approx_eq!(f32, documents[2].score, 0.);
+    }
+
+    #[test]
+    fn test_boost() {
+        let mut documents = mock_documents(3);
+        for (document, category) in documents.iter_mut().zip(["sports", "politics", "sports"]) {
+            document.properties =
+                serde_json::from_value(serde_json::json!({ "category": category })).unwrap();
+        }
+        documents[2].score = 0.5;
+        let rule = |id: &str, category: &str, weight| BoostRule {
+            id: id.try_into().unwrap(),
+            property_id: "category".try_into().unwrap(),
+            value: serde_json::from_value(serde_json::json!(category)).unwrap(),
+            weight,
+            expires_at: None,
+        };
+
+        boost(
+            &mut documents,
+            &[rule("a", "sports", 0.1), rule("b", "politics", -1.)],
+        );
+        let ids = documents
+            .iter()
+            .map(|document| document.id.clone())
+            .collect_vec();
+        assert_eq!(
+            ids,
+            [
+                SnippetId::new("0".try_into().unwrap(), 0),
+                SnippetId::new("2".try_into().unwrap(), 0),
+                SnippetId::new("1".try_into().unwrap(), 0),
+            ],
+        );
+        assert_approx_eq!(f32, documents[0].score, 1.05);
+        assert_approx_eq!(f32, documents[1].score, 0.55);
+        assert_approx_eq!(f32, documents[2].score, 0.5);
+    }
+
+    #[test]
+    fn test_boost_relative_to_scores() {
+        let mut documents = mock_documents(3);
+        for ((document, category), score) in documents
+            .iter_mut()
+            .zip(["sports", "politics", "sports"])
+            .zip([-0.2, -0.4, -0.6])
+        {
+            document.properties =
+                serde_json::from_value(serde_json::json!({ "category": category })).unwrap();
+            document.score = score;
+        }
+        let rule = BoostRule {
+            id: "a".try_into().unwrap(),
+            property_id: "category".try_into().unwrap(),
+            value: serde_json::from_value(serde_json::json!("sports")).unwrap(),
+            weight: 0.75,
+            expires_at: None,
+        };
+
+        boost(&mut documents, &[rule]);
+        let ids = documents
+            .iter()
+            .map(|document| document.id.clone())
+            .collect_vec();
+        assert_eq!(
+            ids,
+            [
+                SnippetId::new("0".try_into().unwrap(), 0),
+                SnippetId::new("2".try_into().unwrap(), 0),
+                SnippetId::new("1".try_into().unwrap(), 0),
+            ],
+        );
+        assert_approx_eq!(f32, documents[0].score, 0.1);
+        assert_approx_eq!(f32, documents[1].score, -0.3);
+        assert_approx_eq!(f32, documents[2].score, -0.4);
+    }
+
+    #[test]
+    fn test_diversify_without_tradeoff() {
+        let mut documents = mock_documents(5);
+        for (i, document) in documents.iter_mut().enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let score = 1. / (i + 1) as f32;
+            document.score = score;
+        }
+
+        diversify(&mut documents, 1.);
+        for (i, document) in documents.iter().enumerate() {
+            assert_eq!(
+                document.id,
+                SnippetId::new(i.to_string().try_into().unwrap(), 0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_diversify_with_duplicates() {
+        let mut documents = mock_documents(3);
+        documents[1].embedding = documents[0].embedding.clone();
+        documents[0].score = 1.;
+        documents[1].score = 0.9;
+        documents[2].score = 0.8;
+
+        diversify(&mut documents, 0.5);
+        let ids = documents
+            .iter()
+            .map(|document| document.id.clone())
+            .collect_vec();
+        assert_eq!(
+            ids,
+            [
+                SnippetId::new("0".try_into().unwrap(), 0),
+                SnippetId::new("2".try_into().unwrap(), 0),
+                SnippetId::new("1".try_into().unwrap(), 0),
+            ],
+        );
+        assert_approx_eq!(f32, documents[0].score, 1.);
+        assert_approx_eq!(f32, documents[1].score, 0.9);
+        assert_approx_eq!(f32, documents[2].score, 0.8);
+    }
 }
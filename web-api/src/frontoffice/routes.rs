@@ -16,21 +16,56 @@ use actix_web::{
     web::{self, ServiceConfig},
     Responder,
 };
-use interactions::interactions;
-use recommendations::{recommendations, user_recommendations};
-use semantic_search::semantic_search;
+use dismissals::dismiss_documents;
+use facets::get_facets;
+use interactions::{delete_interaction_history, impressions, interaction_history, interactions};
+use interests::interests;
+use pinned_documents::{delete_pinned_documents, get_pinned_documents, pin_documents};
+pub(crate) use recommendations::{
+    personalize_documents_for_user,
+    UnvalidatedPersonalizedDocumentsRequest,
+};
+use recommendations::{recommendations, trending_documents, user_recommendations};
+pub(crate) use semantic_search::PersonalizedDocumentData;
+use semantic_search::{related_documents, semantic_search};
+use sources::{delete_sources, get_sources, put_sources};
 
 use super::{PersonalizationConfig, SemanticSearchConfig};
 use crate::utils::deprecate;
 
+mod dismissals;
+mod facets;
 mod interactions;
+mod interests;
+mod pinned_documents;
 mod recommendations;
 mod semantic_search;
+mod sources;
 
 pub(crate) fn configure_service(config: &mut ServiceConfig) {
     let users = web::scope("/users/{user_id}")
-        .service(web::resource("interactions").route(web::patch().to(interactions)))
+        .service(
+            web::resource("interactions")
+                .route(web::get().to(interaction_history))
+                .route(web::patch().to(interactions))
+                .route(web::delete().to(delete_interaction_history)),
+        )
+        .service(web::resource("dismissed_documents").route(web::post().to(dismiss_documents)))
+        .service(web::resource("impressions").route(web::post().to(impressions)))
+        .service(web::resource("interests").route(web::get().to(interests)))
+        .service(
+            web::resource("pinned_documents")
+                .route(web::get().to(get_pinned_documents))
+                .route(web::post().to(pin_documents))
+                .route(web::delete().to(delete_pinned_documents)),
+        )
         .service(web::resource("recommendations").route(web::post().to(user_recommendations)))
+        .service(
+            web::resource("sources")
+                .route(web::get().to(get_sources))
+                .route(web::put().to(put_sources))
+                .route(web::delete().to(delete_sources)),
+        )
         .service(
             web::resource("personalized_documents")
                 .route(web::post().to(deprecate!(user_recommendations(
@@ -44,9 +79,16 @@ pub(crate) fn configure_service(config: &mut ServiceConfig) {
     let semantic_search = web::resource("/semantic_search").route(web::post().to(semantic_search));
     let recommendations_service =
         web::resource("/recommendations").route(web::post().to(recommendations));
+    let trending = web::resource("/documents/_trending").route(web::get().to(trending_documents));
+    let facets = web::resource("/documents/_facets").route(web::get().to(get_facets));
+    let related =
+        web::resource("/documents/{document_id}/related").route(web::get().to(related_documents));
 
     config
         .service(users)
         .service(semantic_search)
-        .service(recommendations_service);
+        .service(recommendations_service)
+        .service(trending)
+        .service(facets)
+        .service(related);
 }
@@ -0,0 +1,71 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use actix_web::{
+    web::{Data, Json, Path},
+    HttpResponse,
+    Responder,
+};
+use chrono::{Duration, Utc};
+use itertools::Itertools;
+use serde::Deserialize;
+use tracing::instrument;
+
+use crate::{
+    app::{AppState, TenantState},
+    models::DocumentId,
+    storage,
+    Error,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UnvalidatedDismissedDocument {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct UnvalidatedDismissedDocumentsRequest {
+    documents: Vec<UnvalidatedDismissedDocument>,
+}
+
+impl UnvalidatedDismissedDocumentsRequest {
+    fn validate(self) -> Result<Vec<DocumentId>, Error> {
+        self.documents
+            .into_iter()
+            .map(|document| DocumentId::try_from(document.id).map_err(Into::into))
+            .try_collect()
+    }
+}
+
+#[instrument(skip(state, storage))]
+pub(super) async fn dismiss_documents(
+    state: Data<AppState>,
+    user_id: Path<String>,
+    Json(body): Json<UnvalidatedDismissedDocumentsRequest>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    let documents = body.validate()?;
+    let time = Utc::now();
+    let expiration = state
+        .config()
+        .personalization
+        .dismissal_expiration
+        .map(|days| time - Duration::days(days.into()));
+    storage::Dismissal::dismiss(&storage, &user_id, &documents, time, expiration).await?;
+
+    Ok(HttpResponse::NoContent())
+}
@@ -0,0 +1,81 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use actix_web::{
+    web::{Json, Query},
+    Responder,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    app::TenantState,
+    error::common::{InvalidDocumentCount, InvalidFacetProperty},
+    models::{DocumentPropertyId, PropertyValueCount},
+    storage::{self, property_filter::IndexedPropertyType},
+    Error,
+};
+
+/// Default number of property values to count.
+const DEFAULT_NUMBER_FACETS: usize = 10;
+
+/// Max number of property values to count.
+const MAX_NUMBER_FACETS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct FacetsQuery {
+    by: String,
+    count: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct FacetsResponse {
+    facets: Vec<PropertyValueCount>,
+}
+
+#[instrument(skip(storage))]
+pub(super) async fn get_facets(
+    Query(params): Query<FacetsQuery>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let property_id = DocumentPropertyId::try_from(params.by)?;
+    let count = params.count.unwrap_or(DEFAULT_NUMBER_FACETS);
+    if !(1..=MAX_NUMBER_FACETS).contains(&count) {
+        return Err(InvalidDocumentCount {
+            count,
+            min: 1,
+            max: MAX_NUMBER_FACETS,
+        }
+        .into());
+    }
+
+    let schema = storage::IndexedProperties::load_schema(&storage).await?;
+    let Some(definition) = schema.get(&property_id) else {
+        return Err(InvalidFacetProperty::Unindexed { property_id }.into());
+    };
+    let property_type = definition.r#type;
+    if property_type == IndexedPropertyType::Date {
+        return Err(InvalidFacetProperty::UnsupportedType {
+            property_id,
+            property_type,
+        }
+        .into());
+    }
+
+    let facets =
+        storage::Facets::count_by_property(&storage, &property_id, property_type, count).await?;
+
+    Ok(Json(FacetsResponse { facets }))
+}
@@ -13,25 +13,36 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use actix_web::{
-    web::{Data, Json, Path},
+    web::{Data, Json, Path, Query},
     HttpResponse,
     Responder,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use itertools::Itertools;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use xayn_ai_coi::CoiId;
 
 use crate::{
     app::{AppState, TenantState},
-    frontoffice::shared::{update_interactions, UnvalidatedSnippetOrDocumentId},
-    models::SnippetOrDocumentId,
+    frontoffice::shared::{update_interactions, validate_count, UnvalidatedSnippetOrDocumentId},
+    models::{DocumentId, InteractionType, SnippetId, SnippetOrDocumentId, UserInteraction},
+    storage,
     Error,
 };
 
+/// Default number of interactions to return.
+const DEFAULT_NUMBER_INTERACTIONS: usize = 100;
+
+/// Max number of interactions to return.
+const MAX_NUMBER_INTERACTIONS: usize = 1000;
+
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct UnvalidatedUserInteraction {
     id: UnvalidatedSnippetOrDocumentId,
+    #[serde(default, rename = "type")]
+    interaction_type: InteractionType,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,31 +52,143 @@ pub(super) struct UnvalidatedUserInteractionRequest {
 }
 
 impl UnvalidatedUserInteractionRequest {
-    fn validate(self) -> Result<Vec<SnippetOrDocumentId>, Error> {
+    fn validate(self) -> Result<Vec<(SnippetOrDocumentId, InteractionType)>, Error> {
         self.documents
             .into_iter()
-            .map(|document| document.id.validate())
+            .map(|document| Ok((document.id.validate()?, document.interaction_type)))
             .try_collect()
     }
 }
 
+#[derive(Debug, Serialize)]
+struct IgnoredInteraction {
+    id: DocumentId,
+    snippet_id: SnippetId,
+}
+
+#[derive(Debug, Serialize)]
+struct UserInteractionResponse {
+    ignored: Vec<IgnoredInteraction>,
+}
+
 pub(super) async fn interactions(
     state: Data<AppState>,
     user_id: Path<String>,
     Json(body): Json<UnvalidatedUserInteractionRequest>,
     TenantState(storage, _): TenantState,
-) -> Result<impl Responder, Error> {
+) -> Result<HttpResponse, Error> {
     let user_id = user_id.into_inner().try_into()?;
     let interactions = body.validate()?;
-    update_interactions(
+    let config = state.config();
+    let time = Utc::now();
+    let ignored = update_interactions(
         &storage,
         &state.coi,
         &user_id,
         interactions,
-        state.config.personalization.store_user_history,
-        Utc::now(),
+        config.personalization.store_user_history,
+        time,
+    )
+    .await?;
+
+    // Hint: the interactions of existing documents are applied even if some documents are ignored
+    if ignored.is_empty() {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(HttpResponse::Ok().json(UserInteractionResponse {
+            ignored: ignored
+                .into_iter()
+                .map(|snippet_id| IgnoredInteraction {
+                    id: snippet_id.document_id().clone(),
+                    snippet_id,
+                })
+                .collect(),
+        }))
+    }
+}
+
+#[instrument(skip(state, storage))]
+pub(super) async fn impressions(
+    state: Data<AppState>,
+    user_id: Path<String>,
+    Json(body): Json<UnvalidatedUserInteractionRequest>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    // Hint: impressions don't have a type
+    let impressions = body.validate()?.into_iter().map(|(id, _)| id).collect();
+    storage::Interaction::user_seen(&storage, &user_id, Utc::now()).await?;
+    storage::Interaction::update_impressions(
+        &storage,
+        &user_id,
+        impressions,
+        |interests, embedding| state.coi.log_user_impression(interests, embedding).cloned(),
     )
     .await?;
 
     Ok(HttpResponse::NoContent())
 }
+
+#[instrument(skip(storage))]
+pub(super) async fn delete_interaction_history(
+    user_id: Path<String>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    storage::Interaction::delete_history(&storage, &user_id).await?;
+
+    Ok(HttpResponse::NoContent())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct UnvalidatedInteractionHistoryQuery {
+    count: Option<usize>,
+    #[serde(default)]
+    offset: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct InteractionData {
+    id: DocumentId,
+    snippet_id: SnippetId,
+    #[serde(rename = "type")]
+    interaction_type: InteractionType,
+    timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coi_id: Option<CoiId>,
+}
+
+impl From<UserInteraction> for InteractionData {
+    fn from(interaction: UserInteraction) -> Self {
+        Self {
+            id: interaction.snippet_id.document_id().clone(),
+            snippet_id: interaction.snippet_id,
+            interaction_type: interaction.interaction_type,
+            timestamp: interaction.time,
+            coi_id: interaction.coi_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct InteractionHistoryResponse {
+    interactions: Vec<InteractionData>,
+}
+
+#[instrument(skip(storage))]
+pub(super) async fn interaction_history(
+    user_id: Path<String>,
+    Query(params): Query<UnvalidatedInteractionHistoryQuery>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    let count = params.count.unwrap_or(DEFAULT_NUMBER_INTERACTIONS);
+    validate_count(count, MAX_NUMBER_INTERACTIONS, MAX_NUMBER_INTERACTIONS)?;
+    let interactions =
+        storage::Interaction::get_history(&storage, &user_id, params.offset, count).await?;
+
+    Ok(Json(InteractionHistoryResponse {
+        interactions: interactions.into_iter().map_into().collect(),
+    }))
+}
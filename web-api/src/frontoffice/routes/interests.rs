@@ -0,0 +1,70 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use actix_web::{
+    web::{Data, Json, Path},
+    Responder,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::instrument;
+use xayn_ai_coi::{compute_coi_weights, CoiId};
+
+use crate::{
+    app::{AppState, TenantState},
+    storage,
+    Error,
+};
+
+#[derive(Debug, Serialize)]
+struct InterestData {
+    id: CoiId,
+    view_count: usize,
+    last_view: DateTime<Utc>,
+    weight: f32,
+}
+
+#[derive(Debug, Serialize)]
+struct InterestsResponse {
+    interests: Vec<InterestData>,
+}
+
+#[instrument(skip(state, storage))]
+pub(super) async fn interests(
+    state: Data<AppState>,
+    user_id: Path<String>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    let interests = storage::Interest::get(&storage, &user_id).await?;
+    let weights = compute_coi_weights(&interests, state.coi.config().horizon(), Utc::now());
+    let mut interests = interests
+        .into_iter()
+        .zip(weights)
+        .map(|(coi, weight)| InterestData {
+            id: coi.id,
+            view_count: coi.stats.view_count,
+            last_view: coi.stats.last_view,
+            weight,
+        })
+        .collect::<Vec<_>>();
+    interests.sort_unstable_by(|interest1, interest2| {
+        interest2
+            .weight
+            .total_cmp(&interest1.weight)
+            .then_with(|| interest1.id.cmp(&interest2.id))
+    });
+
+    Ok(Json(InterestsResponse { interests }))
+}
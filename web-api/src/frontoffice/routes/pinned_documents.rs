@@ -0,0 +1,133 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use actix_web::{
+    web::{Data, Json, Path},
+    HttpResponse,
+    Responder,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    app::{AppState, TenantState},
+    error::common::InvalidPinnedDocuments,
+    models::{DocumentId, PinnedDocument},
+    storage,
+    Error,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UnvalidatedPinnedDocument {
+    id: String,
+    position: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct UnvalidatedPinnedDocumentsRequest {
+    documents: Vec<UnvalidatedPinnedDocument>,
+}
+
+impl UnvalidatedPinnedDocumentsRequest {
+    const MAX_PINNED_DOCUMENTS: usize = 10;
+
+    fn validate(self, max_position: usize) -> Result<Vec<PinnedDocument>, Error> {
+        let size = self.documents.len();
+        if size > Self::MAX_PINNED_DOCUMENTS {
+            return Err(InvalidPinnedDocuments::TooMany {
+                size,
+                max: Self::MAX_PINNED_DOCUMENTS,
+            }
+            .into());
+        }
+
+        let mut ids = HashSet::with_capacity(size);
+        let mut positions = HashSet::with_capacity(size);
+        self.documents
+            .into_iter()
+            .map(|document| {
+                if document.position >= max_position {
+                    return Err(InvalidPinnedDocuments::Position {
+                        position: document.position,
+                        max: max_position.saturating_sub(1),
+                    }
+                    .into());
+                }
+                if !positions.insert(document.position) {
+                    return Err(InvalidPinnedDocuments::DuplicatePosition {
+                        position: document.position,
+                    }
+                    .into());
+                }
+                let id = DocumentId::try_from(document.id)?;
+                if !ids.insert(id.clone()) {
+                    return Err(InvalidPinnedDocuments::DuplicateDocument { id }.into());
+                }
+                Ok(PinnedDocument {
+                    id,
+                    position: document.position,
+                })
+            })
+            .try_collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PinnedDocumentsResponse {
+    documents: Vec<PinnedDocument>,
+}
+
+#[instrument(skip(storage))]
+pub(super) async fn get_pinned_documents(
+    user_id: Path<String>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    let documents = storage::Pin::get(&storage, &user_id).await?;
+
+    Ok(Json(PinnedDocumentsResponse { documents }))
+}
+
+#[instrument(skip(state, storage))]
+pub(super) async fn pin_documents(
+    state: Data<AppState>,
+    user_id: Path<String>,
+    Json(body): Json<UnvalidatedPinnedDocumentsRequest>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    let documents = body.validate(state.config().personalization.max_number_documents)?;
+    let missing = storage::Pin::replace(&storage, &user_id, &documents).await?;
+    if !missing.is_empty() {
+        return Err(InvalidPinnedDocuments::NotFound { documents: missing }.into());
+    }
+
+    Ok(HttpResponse::NoContent())
+}
+
+#[instrument(skip(storage))]
+pub(super) async fn delete_pinned_documents(
+    user_id: Path<String>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    storage::Pin::delete(&storage, &user_id).await?;
+
+    Ok(HttpResponse::NoContent())
+}
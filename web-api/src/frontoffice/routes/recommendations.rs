@@ -12,16 +12,19 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::time::Duration as StdDuration;
+
 use actix_web::{
     http::StatusCode,
     web::{Data, Json, Path, Query},
     Either,
     Responder,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use itertools::Itertools;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::instrument;
+use xayn_ai_coi::Coi;
 
 use super::{PersonalizationConfig, SemanticSearchConfig};
 use crate::{
@@ -30,12 +33,13 @@ use crate::{
     frontoffice::{
         filter::Filter,
         knn,
-        rerank::rerank,
-        routes::semantic_search::SemanticSearchResponse,
+        rerank::{boost, diversify, publication_date, rerank, sort, SortBy},
+        routes::semantic_search::{PersonalizedDocumentData, SemanticSearchResponse},
         shared::{
             default_include_properties,
             personalized_exclusions,
             validate_count,
+            validate_min_similarity,
             InputUser,
             Personalize,
             PersonalizedDocumentsError,
@@ -43,8 +47,8 @@ use crate::{
         },
         stateless::{derive_interests_and_tag_weights, load_history, trim_history},
     },
-    models::UserId,
-    storage::{self, Storage},
+    models::{DocumentSource, PersonalizedDocument, PinnedDocument, SnippetId, UserId},
+    storage::{self, Exclusions, Storage},
     tenants,
     utils::deprecate,
     Error,
@@ -56,6 +60,8 @@ struct RecommendationRequest {
     include_properties: bool,
     include_snippet: bool,
     filter: Option<Filter>,
+    sort_by: SortBy,
+    min_similarity: Option<f32>,
     is_deprecated: bool,
 }
 
@@ -70,6 +76,9 @@ pub(super) struct UnvalidatedRecommendationRequest {
     #[serde(default)]
     include_snippet: bool,
     filter: Option<Filter>,
+    #[serde(default)]
+    sort_by: SortBy,
+    min_similarity: Option<f32>,
 }
 
 impl UnvalidatedRecommendationRequest {
@@ -88,9 +97,12 @@ impl UnvalidatedRecommendationRequest {
             include_properties,
             include_snippet,
             filter,
+            sort_by,
+            min_similarity,
         } = self;
 
         let semantic_search_config: &SemanticSearchConfig = config.as_ref();
+        let personalization_config: &PersonalizationConfig = config.as_ref();
 
         let count = count.unwrap_or(semantic_search_config.default_number_documents);
         validate_count(
@@ -105,6 +117,10 @@ impl UnvalidatedRecommendationRequest {
         if let Some(filter) = &filter {
             filter.validate(&storage.load_schema().await?)?;
         }
+        let min_similarity = min_similarity.or(personalization_config.min_similarity);
+        if let Some(min_similarity) = min_similarity {
+            validate_min_similarity(min_similarity)?;
+        }
         let is_deprecated = published_after.is_some();
 
         Ok(RecommendationRequest {
@@ -113,6 +129,8 @@ impl UnvalidatedRecommendationRequest {
             include_properties,
             include_snippet,
             filter,
+            sort_by,
+            min_similarity,
             is_deprecated,
         })
     }
@@ -120,7 +138,7 @@ impl UnvalidatedRecommendationRequest {
 
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub(super) struct UnvalidatedPersonalizedDocumentsRequest {
+pub(crate) struct UnvalidatedPersonalizedDocumentsRequest {
     count: Option<usize>,
     published_after: Option<DateTime<Utc>>,
     filter: Option<Filter>,
@@ -128,6 +146,9 @@ pub(super) struct UnvalidatedPersonalizedDocumentsRequest {
     include_properties: bool,
     #[serde(default)]
     include_snippet: bool,
+    #[serde(default)]
+    sort_by: SortBy,
+    min_similarity: Option<f32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -140,6 +161,9 @@ pub(super) struct UnvalidatedPersonalizedDocumentsQuery {
     include_properties: bool,
     #[serde(default)]
     include_snippet: bool,
+    #[serde(default)]
+    sort_by: SortBy,
+    min_similarity: Option<f32>,
 }
 
 impl UnvalidatedPersonalizedDocumentsRequest {
@@ -155,6 +179,8 @@ impl UnvalidatedPersonalizedDocumentsRequest {
             filter,
             include_properties,
             include_snippet,
+            sort_by,
+            min_similarity,
         } = self;
         let config = config.as_ref();
 
@@ -168,6 +194,10 @@ impl UnvalidatedPersonalizedDocumentsRequest {
         if let Some(filter) = &filter {
             filter.validate(&storage.load_schema().await?)?;
         }
+        let min_similarity = min_similarity.or(config.min_similarity);
+        if let Some(min_similarity) = min_similarity {
+            validate_min_similarity(min_similarity)?;
+        }
         let is_deprecated = published_after.is_some();
 
         let personalize = Personalize {
@@ -181,6 +211,8 @@ impl UnvalidatedPersonalizedDocumentsRequest {
             include_properties,
             include_snippet,
             filter,
+            sort_by,
+            min_similarity,
             is_deprecated,
         })
     }
@@ -195,7 +227,7 @@ pub(super) async fn recommendations(
     // TODO: actually return non-empty warnings in the response
     let mut warnings = Vec::new();
     let request = body
-        .validate_and_resolve_defaults(&state.config, &storage, &mut warnings)
+        .validate_and_resolve_defaults(&*state.config(), &storage, &mut warnings)
         .await?;
 
     recommendations_inner(state, request, storage).await
@@ -206,80 +238,419 @@ async fn recommendations_inner(
     request: RecommendationRequest,
     storage: Storage,
 ) -> Result<impl Responder, Error> {
+    let is_deprecated = request.is_deprecated;
+    let Some(Recommendations {
+        documents,
+        truncated,
+        exploration,
+    }) = personalize_documents(&state, request, &storage).await?
+    else {
+        return Ok(Either::Left((
+            deprecate!(if is_deprecated {
+                Json(PersonalizedDocumentsError::NotEnoughInteractions)
+            }),
+            StatusCode::CONFLICT,
+        )));
+    };
+
+    Ok(Either::Right(deprecate!(if is_deprecated {
+        Json(RecommendationResponse {
+            documents: documents
+                .into_iter()
+                .map(|document| {
+                    let is_explored = exploration.contains(&document.id);
+                    let document = PersonalizedDocumentData::from(document);
+                    if is_explored {
+                        document.explored()
+                    } else {
+                        document
+                    }
+                })
+                .collect(),
+            truncated,
+        })
+    })))
+}
+
+#[derive(Serialize)]
+struct RecommendationResponse {
+    documents: Vec<PersonalizedDocumentData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated: Option<TruncationReason>,
+}
+
+/// The reason why fewer documents than requested are recommended.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TruncationReason {
+    /// Documents were dropped because they are less similar than the min similarity.
+    MinSimilarity,
+}
+
+struct Recommendations {
+    documents: Vec<PersonalizedDocument>,
+    truncated: Option<TruncationReason>,
+    /// The ids of the blended in exploration documents.
+    exploration: Vec<SnippetId>,
+}
+
+/// Personalizes the documents for the request.
+///
+/// Returns `None` if the user doesn't have enough interests yet.
+#[allow(clippy::too_many_lines)]
+async fn personalize_documents(
+    state: &AppState,
+    request: RecommendationRequest,
+    storage: &Storage,
+) -> Result<Option<Recommendations>, Error> {
     let RecommendationRequest {
         count,
         personalize,
         include_properties,
         include_snippet,
         filter,
-        is_deprecated,
+        sort_by,
+        min_similarity,
+        is_deprecated: _,
     } = request;
 
+    let config = state.config();
     let time = Utc::now();
-    let exclusions = personalized_exclusions(&storage, state.config.as_ref(), &personalize).await?;
+    let exclusions =
+        personalized_exclusions(storage, &config.personalization, &personalize, time).await?;
 
-    let (interests, tag_weights) = match personalize.user {
+    let (interests, tag_weights, excluded_sources, pinned) = match personalize.user {
         InputUser::Ref { id } => {
-            storage::Interaction::user_seen(&storage, &id, time).await?;
+            storage::Interaction::user_seen(storage, &id, time).await?;
             (
-                storage::Interest::get(&storage, &id).await?,
-                storage::Tag::get(&storage, &id).await?,
+                storage::Interest::get(storage, &id).await?,
+                storage::Tag::get(storage, &id).await?,
+                storage::Source::get(storage, &id).await?.excluded,
+                storage::Pin::get(storage, &id).await?,
             )
         }
         InputUser::Inline { history } => {
             let history = trim_history(
                 history,
-                state.config.personalization.max_stateless_history_for_cois,
+                config.personalization.max_stateless_history_for_cois,
             );
-            let history = load_history(&storage, history).await?;
-            derive_interests_and_tag_weights(&state.coi, &history)
+            let history = load_history(storage, history).await?;
+            let (interests, tag_weights) = derive_interests_and_tag_weights(&state.coi, &history);
+            (interests, tag_weights, Vec::new(), Vec::new())
         }
     };
 
+    let pinned_documents = load_pinned_documents(
+        storage,
+        &pinned,
+        &exclusions,
+        &excluded_sources,
+        include_properties,
+        include_snippet,
+    )
+    .await?;
     if interests.len() < state.coi.config().min_cois() {
-        return Ok(Either::Left((
-            deprecate!(if is_deprecated {
-                Json(PersonalizedDocumentsError::NotEnoughInteractions)
-            }),
-            StatusCode::CONFLICT,
-        )));
+        // Hint: the pinned documents don't depend on the interests of the user
+        if pinned_documents.is_empty() {
+            return Ok(None);
+        }
+        let mut documents = Vec::with_capacity(pinned_documents.len());
+        insert_pinned_documents(&mut documents, &pinned, pinned_documents);
+        documents.truncate(count);
+        return Ok(Some(Recommendations {
+            documents,
+            truncated: None,
+            exploration: Vec::new(),
+        }));
     }
 
+    let boost_rules = storage::BoostRules::get_active(storage, time).await?;
+    let published_after = config
+        .personalization
+        .max_document_age
+        .map(|days| time - Duration::days(days.into()));
     let mut documents = knn::CoiSearch {
         interests: &interests,
         excluded: &exclusions,
         horizon: state.coi.config().horizon(),
-        max_cois: state.config.personalization.max_cois_for_knn,
+        max_cois: config.personalization.max_cois_for_knn,
         count,
-        num_candidates: state.config.personalization.max_number_candidates,
+        num_candidates: config.personalization.max_number_candidates,
         time,
-        include_properties,
+        // Hint: the properties are needed to filter the excluded sources, to apply the boost
+        //       rules and to sort by date
+        include_properties: include_properties
+            || !excluded_sources.is_empty()
+            || !boost_rules.is_empty()
+            || sort_by != SortBy::Score,
         include_snippet,
         filter: filter.as_ref(),
+        published_after,
     }
-    .run_on(&storage)
+    .run_on(storage)
     .await?;
+    if !excluded_sources.is_empty() {
+        exclude_sources(&mut documents, &excluded_sources);
+    }
+    let dropped_dissimilar = min_similarity.map_or(false, |min_similarity| {
+        drop_dissimilar(&mut documents, &interests, min_similarity)
+    });
 
     rerank(
         &state.coi,
         &mut documents,
         &interests,
         &tag_weights,
-        state.config.personalization.score_weights,
+        config.personalization.score_weights,
         time,
     );
+    boost(&mut documents, &boost_rules);
+    // Hint: sorting by date would undo the diversification
+    if sort_by == SortBy::Score {
+        diversify(&mut documents, config.personalization.mmr_lambda);
+    }
+    let half_life = StdDuration::from_secs(
+        u64::from(config.personalization.freshness_half_life) * 24 * 60 * 60,
+    );
+    sort(&mut documents, sort_by, half_life, time);
+    // Hint: the trending candidates can't be filtered, hence there is no exploration for filters
+    let exploration = if config.personalization.exploration_share > 0. && filter.is_none() {
+        explore(
+            storage,
+            &mut documents,
+            &exclusions,
+            &excluded_sources,
+            &pinned_documents,
+            published_after,
+            &config.personalization,
+            count,
+            time,
+            include_snippet,
+        )
+        .await?
+    } else {
+        Vec::new()
+    };
+    insert_pinned_documents(&mut documents, &pinned, pinned_documents);
 
     if documents.len() > count {
         // due to ceiling the number of documents we fetch per COI
         // we might end up with more documents than we want
         documents.truncate(count);
     }
+    if !include_properties {
+        for document in &mut documents {
+            document.properties = None;
+        }
+    }
+    let truncated =
+        (dropped_dissimilar && documents.len() < count).then_some(TruncationReason::MinSimilarity);
 
-    Ok(Either::Right(deprecate!(if is_deprecated {
-        Json(SemanticSearchResponse {
-            documents: documents.into_iter().map_into().collect(),
+    Ok(Some(Recommendations {
+        documents,
+        truncated,
+        exploration,
+    }))
+}
+
+/// Blends trending documents into the recommendations to explore beyond the interests of the user.
+///
+/// Up to the `exploration_share` of the `count` documents are replaced by trending documents, which
+/// are spread evenly over the recommendations. Room is left for the pinned documents, so that the
+/// blended in documents aren't truncated after pinning. The blended in documents keep their
+/// trending scores, which aren't comparable to the scores of the recommendations. Returns the ids
+/// of the blended in documents.
+#[allow(clippy::too_many_arguments)]
+async fn explore(
+    storage: &Storage,
+    documents: &mut Vec<PersonalizedDocument>,
+    exclusions: &Exclusions,
+    excluded_sources: &[DocumentSource],
+    pinned_documents: &[PersonalizedDocument],
+    published_after: Option<DateTime<Utc>>,
+    config: &PersonalizationConfig,
+    count: usize,
+    time: DateTime<Utc>,
+    include_snippet: bool,
+) -> Result<Vec<SnippetId>, Error> {
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    let share = (count as f32 * config.exploration_share).round() as usize;
+    let count = count.saturating_sub(pinned_documents.len());
+    let share = share.min(count);
+    if share == 0 {
+        return Ok(Vec::new());
+    }
+    documents.retain(|document| {
+        pinned_documents
+            .iter()
+            .all(|pinned| pinned.id.document_id() != document.id.document_id())
+    });
+
+    let half_life = StdDuration::from_secs(u64::from(config.trending_half_life) * 24 * 60 * 60);
+    // Hint: more candidates are requested to make up for the recommended and excluded ones
+    let scores = storage::Interaction::get_trending(
+        storage,
+        time,
+        half_life,
+        share
+            + documents.len()
+            + pinned_documents.len()
+            + exclusions.documents.len()
+            + exclusions.snippets.len(),
+    )
+    .await?;
+    let ids = scores
+        .keys()
+        .filter(|&id| {
+            !exclusions.snippets.contains(id)
+                && !exclusions.documents.contains(id.document_id())
+                && documents
+                    .iter()
+                    .chain(pinned_documents)
+                    .all(|document| document.id.document_id() != id.document_id())
         })
-    })))
+        .collect_vec();
+    // Hint: the properties are needed to filter the excluded sources and the publication dates
+    let mut candidates =
+        storage::Document::get_personalized(storage, ids, true, include_snippet).await?;
+    exclude_sources(&mut candidates, excluded_sources);
+    if let Some(published_after) = published_after {
+        candidates.retain(|candidate| {
+            publication_date(candidate).map_or(false, |date| date >= published_after)
+        });
+    }
+    for candidate in &mut candidates {
+        candidate.score = scores.get(&candidate.id).copied().unwrap_or_default();
+    }
+    candidates.sort_unstable_by(|candidate1, candidate2| {
+        candidate2
+            .score
+            .total_cmp(&candidate1.score)
+            .then_with(|| candidate1.id.cmp(&candidate2.id))
+    });
+    candidates.truncate(share);
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    documents.truncate(count - candidates.len());
+    let step = count / candidates.len();
+    let exploration = candidates
+        .iter()
+        .map(|candidate| candidate.id.clone())
+        .collect_vec();
+    for (idx, candidate) in candidates.into_iter().enumerate() {
+        let position = ((idx + 1) * step - 1).min(documents.len());
+        documents.insert(position, candidate);
+    }
+
+    Ok(exploration)
+}
+
+/// Removes the documents which are less similar than `min_similarity` to each of the interests.
+///
+/// Returns whether any documents were removed.
+fn drop_dissimilar(
+    documents: &mut Vec<PersonalizedDocument>,
+    interests: &[Coi],
+    min_similarity: f32,
+) -> bool {
+    let len = documents.len();
+    documents.retain(|document| {
+        interests
+            .iter()
+            .any(|interest| document.embedding.dot_product(&interest.point) >= min_similarity)
+    });
+
+    documents.len() < len
+}
+
+/// Loads the pinned documents which aren't excluded for the user.
+///
+/// Documents which the user interacted with, dismissed or whose source is excluded aren't shown,
+/// even if they are pinned. The filter, the published after date and the max document age of the
+/// recommendations deliberately don't apply to the pinned documents.
+async fn load_pinned_documents(
+    storage: &Storage,
+    pinned: &[PinnedDocument],
+    exclusions: &Exclusions,
+    excluded_sources: &[DocumentSource],
+    include_properties: bool,
+    include_snippet: bool,
+) -> Result<Vec<PersonalizedDocument>, Error> {
+    let ids = pinned
+        .iter()
+        .filter(|document| !exclusions.documents.contains(&document.id))
+        .map(|document| SnippetId::new(document.id.clone(), 0))
+        .filter(|id| !exclusions.snippets.contains(id))
+        .collect_vec();
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Hint: the properties are needed to filter the excluded sources
+    let mut documents = storage::Document::get_personalized(
+        storage,
+        &ids,
+        include_properties || !excluded_sources.is_empty(),
+        include_snippet,
+    )
+    .await?;
+    if !excluded_sources.is_empty() {
+        exclude_sources(&mut documents, excluded_sources);
+    }
+    if !include_properties {
+        for document in &mut documents {
+            document.properties = None;
+        }
+    }
+
+    Ok(documents)
+}
+
+/// Moves the pinned documents to their positions.
+///
+/// Pinned documents which aren't candidates or which are excluded are ignored.
+fn insert_pinned_documents(
+    documents: &mut Vec<PersonalizedDocument>,
+    pinned: &[PinnedDocument],
+    mut pinned_documents: Vec<PersonalizedDocument>,
+) {
+    documents.retain(|document| {
+        pinned
+            .iter()
+            .all(|pinned| &pinned.id != document.id.document_id())
+    });
+    // Hint: `pinned` is ordered by position, inserting in order keeps the earlier positions
+    for pinned in pinned {
+        if let Some(index) = pinned_documents
+            .iter()
+            .position(|document| document.id.document_id() == &pinned.id)
+        {
+            let document = pinned_documents.swap_remove(index);
+            documents.insert(pinned.position.min(documents.len()), document);
+        }
+    }
+}
+
+/// Removes the documents whose `source` property is one of the excluded sources.
+fn exclude_sources(documents: &mut Vec<PersonalizedDocument>, excluded_sources: &[DocumentSource]) {
+    documents.retain(|document| {
+        document
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.get("source"))
+            .and_then(|source| source.as_str())
+            .map_or(true, |source| {
+                !excluded_sources
+                    .iter()
+                    .any(|excluded| excluded.as_str() == source)
+            })
+    });
 }
 
 pub(super) async fn user_recommendations(
@@ -291,7 +662,7 @@ pub(super) async fn user_recommendations(
 ) -> Result<impl Responder, Error> {
     let user_id = user_id.into_inner().try_into()?;
     let request: RecommendationRequest = if let Some(Json(body)) = body {
-        body.validate_and_resolve_defaults(&state.config, &storage, user_id)
+        body.validate_and_resolve_defaults(&*state.config(), &storage, user_id)
             .await?
     } else {
         UnvalidatedPersonalizedDocumentsRequest {
@@ -303,12 +674,14 @@ pub(super) async fn user_recommendations(
                 .transpose()?,
             include_properties: params.include_properties,
             include_snippet: params.include_snippet,
+            sort_by: params.sort_by,
+            min_similarity: params.min_similarity,
         }
-        .validate_and_resolve_defaults(&state.config, &storage, user_id)
+        .validate_and_resolve_defaults(&*state.config(), &storage, user_id)
         .await?
         // TODO: once the deprecated params are removed use this instead in case of no request body
         // PersonalizedDocumentsRequest {
-        //     count: state.config.personalization.default_number_documents,
+        //     count: state.config().personalization.default_number_documents,
         //     filter: None,
         //     include_properties: default_include_properties(),
         //     is_deprecated: false,
@@ -316,3 +689,198 @@ pub(super) async fn user_recommendations(
     };
     recommendations_inner(state, request, storage).await
 }
+
+/// Recommends documents to a user outside of an actix request.
+///
+/// This is the same as `POST /users/{user_id}/recommendations` with the given request body, but
+/// returns `None` instead of a conflict if the user doesn't have enough interests yet.
+pub(crate) async fn personalize_documents_for_user(
+    state: &AppState,
+    storage: &Storage,
+    user_id: UserId,
+    request: UnvalidatedPersonalizedDocumentsRequest,
+) -> Result<Option<Vec<PersonalizedDocument>>, Error> {
+    let request = request
+        .validate_and_resolve_defaults(&*state.config(), storage, user_id)
+        .await?;
+
+    Ok(personalize_documents(state, request, storage)
+        .await?
+        .map(|recommendations| recommendations.documents))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct UnvalidatedTrendingDocumentsQuery {
+    count: Option<usize>,
+    #[serde(default = "default_include_properties")]
+    include_properties: bool,
+    #[serde(default)]
+    include_snippet: bool,
+}
+
+#[instrument(skip(state, storage))]
+pub(super) async fn trending_documents(
+    state: Data<AppState>,
+    Query(params): Query<UnvalidatedTrendingDocumentsQuery>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let config = state.config();
+    let config = &config.personalization;
+    let count = params.count.unwrap_or(config.default_number_documents);
+    validate_count(
+        count,
+        config.max_number_documents,
+        config.max_number_candidates,
+    )?;
+
+    let half_life = StdDuration::from_secs(u64::from(config.trending_half_life) * 24 * 60 * 60);
+    let scores = storage::Interaction::get_trending(&storage, Utc::now(), half_life, count).await?;
+    let mut documents = storage::Document::get_personalized(
+        &storage,
+        scores.keys(),
+        params.include_properties,
+        params.include_snippet,
+    )
+    .await?;
+    for document in &mut documents {
+        if let Some(score) = scores.get(&document.id) {
+            document.score = *score;
+        }
+    }
+    documents.sort_unstable_by(|document1, document2| {
+        document2
+            .score
+            .total_cmp(&document1.score)
+            .then_with(|| document1.id.cmp(&document2.id))
+    });
+
+    Ok(Json(SemanticSearchResponse {
+        documents: documents.into_iter().map_into().collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use xayn_ai_bert::Embedding1;
+    use xayn_ai_coi::{CoiId, CoiStats};
+
+    use super::*;
+    use crate::models::DocumentTags;
+
+    fn mock_document(id: &str, embedding: Vec<f32>) -> PersonalizedDocument {
+        PersonalizedDocument {
+            id: SnippetId::new(id.try_into().unwrap(), 0),
+            score: 1.,
+            embedding: Embedding1::from(embedding).normalize().unwrap(),
+            properties: None,
+            snippet: None,
+            tags: DocumentTags::default(),
+            dev: None,
+        }
+    }
+
+    fn mock_coi(point: Vec<f32>) -> Coi {
+        Coi {
+            id: CoiId::new(),
+            point: Embedding1::from(point).normalize().unwrap(),
+            stats: CoiStats {
+                view_count: 1,
+                view_time: StdDuration::ZERO,
+                last_view: Utc::now(),
+            },
+        }
+    }
+
+    fn ids(documents: &[PersonalizedDocument]) -> Vec<&str> {
+        documents
+            .iter()
+            .map(|document| document.id.document_id().as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_drop_dissimilar() {
+        let mut documents = vec![
+            mock_document("d0", vec![1., 0., 0.]),
+            mock_document("d1", vec![0., 1., 0.]),
+            mock_document("d2", vec![1., 1., 0.]),
+            mock_document("d3", vec![0., 0., 1.]),
+        ];
+        let interests = [mock_coi(vec![1., 0., 0.]), mock_coi(vec![0., 1., 0.])];
+
+        assert!(!drop_dissimilar(&mut documents, &interests, -1.));
+        assert_eq!(ids(&documents), ["d0", "d1", "d2", "d3"]);
+        assert!(!drop_dissimilar(&mut documents, &interests, 0.));
+        assert_eq!(ids(&documents), ["d0", "d1", "d2", "d3"]);
+        assert!(drop_dissimilar(&mut documents, &interests, 0.5));
+        assert_eq!(ids(&documents), ["d0", "d1", "d2"]);
+        assert!(drop_dissimilar(&mut documents, &interests, 0.9));
+        assert_eq!(ids(&documents), ["d0", "d1"]);
+    }
+
+    #[test]
+    fn test_drop_dissimilar_without_interests() {
+        let mut documents = vec![mock_document("d0", vec![1., 0.])];
+
+        assert!(drop_dissimilar(&mut documents, &[], -1.));
+        assert!(documents.is_empty());
+    }
+
+    fn mock_pinned(id: &str, position: usize) -> PinnedDocument {
+        PinnedDocument {
+            id: id.try_into().unwrap(),
+            position,
+        }
+    }
+
+    #[test]
+    fn test_insert_pinned_documents() {
+        let mut documents = vec![
+            mock_document("d0", vec![1., 0.]),
+            mock_document("d1", vec![1., 0.]),
+            mock_document("d2", vec![1., 0.]),
+            mock_document("d3", vec![1., 0.]),
+        ];
+        let pinned = [
+            mock_pinned("d3", 0),
+            mock_pinned("p0", 2),
+            mock_pinned("p1", 3),
+            mock_pinned("p2", 9),
+        ];
+        // Hint: p1 isn't a candidate
+        let pinned_documents = vec![
+            mock_document("p2", vec![1., 0.]),
+            mock_document("d3", vec![1., 0.]),
+            mock_document("p0", vec![1., 0.]),
+        ];
+
+        insert_pinned_documents(&mut documents, &pinned, pinned_documents);
+        assert_eq!(ids(&documents), ["d3", "d0", "p0", "d1", "d2", "p2"]);
+    }
+
+    #[test]
+    fn test_insert_pinned_documents_without_recommendations() {
+        let mut documents = Vec::new();
+        let pinned = [mock_pinned("p0", 1), mock_pinned("p1", 5)];
+        let pinned_documents = vec![
+            mock_document("p1", vec![1., 0.]),
+            mock_document("p0", vec![1., 0.]),
+        ];
+
+        insert_pinned_documents(&mut documents, &pinned, pinned_documents);
+        assert_eq!(ids(&documents), ["p0", "p1"]);
+    }
+
+    #[test]
+    fn test_insert_pinned_documents_removes_excluded() {
+        let mut documents = vec![
+            mock_document("d0", vec![1., 0.]),
+            mock_document("p0", vec![1., 0.]),
+        ];
+        let pinned = [mock_pinned("p0", 0)];
+
+        insert_pinned_documents(&mut documents, &pinned, Vec::new());
+        assert_eq!(ids(&documents), ["d0"]);
+    }
+}
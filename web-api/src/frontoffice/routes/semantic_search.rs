@@ -13,7 +13,7 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use actix_web::{
-    web::{Data, Json},
+    web::{Data, Json, Path, Query},
     Responder,
 };
 use chrono::{DateTime, Utc};
@@ -40,6 +40,7 @@ use crate::{
         default_include_properties,
         personalized_exclusions,
         validate_count,
+        validate_score_weights,
         InputUser,
         Personalize,
         UnvalidatedPersonalize,
@@ -91,7 +92,7 @@ impl DevOption {
 }
 
 #[derive(Debug, Serialize)]
-pub(super) struct PersonalizedDocumentData {
+pub(crate) struct PersonalizedDocumentData {
     id: DocumentId,
     snippet_id: SnippetId,
     score: f32,
@@ -101,6 +102,18 @@ pub(super) struct PersonalizedDocumentData {
     snippet: Option<DocumentSnippet>,
     #[serde(skip_serializing_if = "Option::is_none")]
     dev: Option<DocumentDevData>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    exploration: bool,
+}
+
+impl PersonalizedDocumentData {
+    /// Marks the document as an exploration candidate.
+    pub(super) fn explored(self) -> Self {
+        Self {
+            exploration: true,
+            ..self
+        }
+    }
 }
 
 impl From<PersonalizedDocument> for PersonalizedDocumentData {
@@ -112,6 +125,7 @@ impl From<PersonalizedDocument> for PersonalizedDocumentData {
             properties: document.properties,
             snippet: document.snippet,
             dev: document.dev,
+            exploration: false,
         }
     }
 }
@@ -123,6 +137,7 @@ struct SemanticSearchRequest {
     count: usize,
     num_candidates: usize,
     personalize: Option<Personalize>,
+    score_weights: [f32; 3],
     enable_hybrid_search: bool,
     dev_hybrid_search: Option<DevHybrid>,
     dev_show_raw_scores: Option<bool>,
@@ -144,6 +159,7 @@ pub(super) struct UnvalidatedSemanticSearchRequest {
     count: Option<usize>,
     published_after: Option<DateTime<Utc>>,
     personalize: Option<UnvalidatedPersonalize>,
+    score_weights: Option<[f32; 3]>,
     #[serde(default)]
     enable_hybrid_search: bool,
     #[serde(default, rename = "_dev")]
@@ -169,6 +185,7 @@ impl UnvalidatedSemanticSearchRequest {
             count,
             published_after,
             personalize,
+            score_weights,
             enable_hybrid_search,
             dev,
             include_properties,
@@ -192,6 +209,10 @@ impl UnvalidatedSemanticSearchRequest {
         let personalize = personalize
             .map(|personalize| personalize.validate(config.as_ref(), warnings))
             .transpose()?;
+        if let Some(score_weights) = score_weights {
+            validate_score_weights(score_weights)?;
+        }
+        let score_weights = score_weights.unwrap_or(semantic_search_config.score_weights);
         let dev_hybrid_search = dev.hybrid;
         let dev_show_raw_scores = dev.show_raw_scores;
         let filter = Filter::insert_published_after(filter, published_after);
@@ -205,6 +226,7 @@ impl UnvalidatedSemanticSearchRequest {
             count,
             num_candidates,
             personalize,
+            score_weights,
             enable_hybrid_search,
             dev_hybrid_search,
             dev_show_raw_scores,
@@ -273,11 +295,13 @@ pub(super) async fn semantic_search(
 ) -> Result<impl Responder, Error> {
     // TODO: actually return non-empty warnings in the response
     let mut warnings = Vec::new();
+    let config = state.config();
     let SemanticSearchRequest {
         document,
         count,
         num_candidates,
         personalize,
+        score_weights,
         enable_hybrid_search,
         dev_hybrid_search,
         dev_show_raw_scores,
@@ -286,11 +310,11 @@ pub(super) async fn semantic_search(
         filter,
         is_deprecated,
     } = body
-        .validate_and_resolve_defaults(&state.config, &storage, &mut warnings)
+        .validate_and_resolve_defaults(&*config, &storage, &mut warnings)
         .await?;
 
     let mut exclusions = if let Some(personalize) = &personalize {
-        personalized_exclusions(&storage, state.config.as_ref(), personalize).await?
+        personalized_exclusions(&storage, &config.personalization, personalize, Utc::now()).await?
     } else {
         Exclusions::default()
     };
@@ -329,6 +353,7 @@ pub(super) async fn semantic_search(
             include_properties,
             include_snippet,
             filter: filter.as_ref(),
+            published_after: None,
             with_raw_scores: dev_show_raw_scores.unwrap_or(false),
         },
     )
@@ -337,13 +362,18 @@ pub(super) async fn semantic_search(
     if let Some(personalize) = personalize {
         personalize_knn_search_result(
             &storage,
-            &state.config,
+            &*config,
             &state.coi,
             personalize,
+            score_weights,
             &mut documents,
         )
         .await?;
     }
+    config
+        .semantic_search
+        .score_normalization
+        .apply(&mut documents);
 
     Ok(deprecate!(if is_deprecated {
         Json(SemanticSearchResponse {
@@ -352,11 +382,97 @@ pub(super) async fn semantic_search(
     }))
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct UnvalidatedRelatedDocumentsQuery {
+    count: Option<usize>,
+    user_id: Option<String>,
+    #[serde(default = "default_include_properties")]
+    include_properties: bool,
+    #[serde(default)]
+    include_snippet: bool,
+}
+
+#[instrument(skip(state, storage))]
+pub(super) async fn related_documents(
+    state: Data<AppState>,
+    document_id: Path<String>,
+    Query(params): Query<UnvalidatedRelatedDocumentsQuery>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let config = state.config();
+    let document_id = DocumentId::try_from(document_id.into_inner())?;
+    let count = params
+        .count
+        .unwrap_or(config.semantic_search.default_number_documents);
+    let num_candidates = config.semantic_search.max_number_candidates;
+    validate_count(
+        count,
+        config.semantic_search.max_number_documents,
+        num_candidates,
+    )?;
+    let personalize = params
+        .user_id
+        .map(|id| {
+            Ok::<_, Error>(Personalize {
+                exclude_seen: true,
+                user: InputUser::Ref { id: id.try_into()? },
+            })
+        })
+        .transpose()?;
+
+    let mut exclusions = if let Some(personalize) = &personalize {
+        personalized_exclusions(&storage, &config.personalization, personalize, Utc::now()).await?
+    } else {
+        Exclusions::default()
+    };
+    // TODO[pmk/ET-4933] how to handle by document search with multi-snippet documents
+    let id = SnippetId::new(document_id, 0);
+    let embedding = storage::Document::get_embedding(&storage, &id)
+        .await?
+        .ok_or(DocumentNotFound)?;
+    exclusions.documents.push(id.into_document_id());
+
+    let mut documents = storage::Document::get_by_embedding(
+        &storage,
+        KnnSearchParams {
+            excluded: &exclusions,
+            embedding: &embedding,
+            count,
+            num_candidates,
+            strategy: SearchStrategy::Knn,
+            include_properties: params.include_properties,
+            include_snippet: params.include_snippet,
+            filter: None,
+            published_after: None,
+            with_raw_scores: false,
+        },
+    )
+    .await?;
+
+    if let Some(personalize) = personalize {
+        personalize_knn_search_result(
+            &storage,
+            &*config,
+            &state.coi,
+            personalize,
+            config.semantic_search.score_weights,
+            &mut documents,
+        )
+        .await?;
+    }
+
+    Ok(Json(SemanticSearchResponse {
+        documents: documents.into_iter().map_into().collect(),
+    }))
+}
+
 async fn personalize_knn_search_result(
     storage: &(impl storage::Interest + storage::Tag + storage::Document),
-    config: &(impl AsRef<CoiConfig> + AsRef<SemanticSearchConfig> + AsRef<PersonalizationConfig>),
+    config: &(impl AsRef<CoiConfig> + AsRef<PersonalizationConfig>),
     coi_system: &CoiSystem,
     personalize: Personalize,
+    score_weights: [f32; 3],
     documents: &mut [PersonalizedDocument],
 ) -> Result<(), Error> {
     let (interests, tag_weights) = match personalize.user {
@@ -380,7 +496,7 @@ async fn personalize_knn_search_result(
             documents,
             &interests,
             &tag_weights,
-            AsRef::<SemanticSearchConfig>::as_ref(config).score_weights,
+            score_weights,
             Utc::now(),
         );
     }
@@ -0,0 +1,114 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use actix_web::{
+    web::{Json, Path},
+    HttpResponse,
+    Responder,
+};
+use itertools::Itertools;
+use serde::Deserialize;
+use tracing::instrument;
+
+use crate::{
+    app::TenantState,
+    error::common::InvalidSourcePreferences,
+    models::{DocumentSource, SourcePreferences},
+    storage,
+    Error,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct UnvalidatedSourcePreferences {
+    #[serde(default)]
+    trusted: Vec<String>,
+    #[serde(default)]
+    excluded: Vec<String>,
+}
+
+impl UnvalidatedSourcePreferences {
+    const MAX_SOURCES: usize = 100;
+
+    /// Validates the sources.
+    ///
+    /// Duplicates are removed and excluded sources take precedence over trusted sources.
+    fn validate(self) -> Result<SourcePreferences, Error> {
+        let size = self.trusted.len() + self.excluded.len();
+        if size > Self::MAX_SOURCES {
+            return Err(InvalidSourcePreferences {
+                size,
+                max: Self::MAX_SOURCES,
+            }
+            .into());
+        }
+
+        let excluded = self
+            .excluded
+            .into_iter()
+            .map(DocumentSource::try_from)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .sorted_unstable()
+            .dedup()
+            .collect_vec();
+        let trusted = self
+            .trusted
+            .into_iter()
+            .map(DocumentSource::try_from)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|source| excluded.binary_search(source).is_err())
+            .sorted_unstable()
+            .dedup()
+            .collect_vec();
+
+        Ok(SourcePreferences { trusted, excluded })
+    }
+}
+
+#[instrument(skip(storage))]
+pub(super) async fn get_sources(
+    user_id: Path<String>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    let sources = storage::Source::get(&storage, &user_id).await?;
+
+    Ok(Json(sources))
+}
+
+#[instrument(skip(storage))]
+pub(super) async fn put_sources(
+    user_id: Path<String>,
+    Json(body): Json<UnvalidatedSourcePreferences>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    let sources = body.validate()?;
+    storage::Source::put(&storage, &user_id, &sources).await?;
+
+    Ok(HttpResponse::NoContent())
+}
+
+#[instrument(skip(storage))]
+pub(super) async fn delete_sources(
+    user_id: Path<String>,
+    TenantState(storage, _): TenantState,
+) -> Result<impl Responder, Error> {
+    let user_id = user_id.into_inner().try_into()?;
+    storage::Source::delete(&storage, &user_id).await?;
+
+    Ok(HttpResponse::NoContent())
+}
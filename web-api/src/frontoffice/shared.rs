@@ -12,7 +12,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use xayn_ai_coi::CoiSystem;
@@ -23,10 +23,10 @@ use super::{
 };
 use crate::{
     error::{
-        common::{BadRequest, InvalidDocumentCount},
+        common::{BadRequest, InvalidDocumentCount, InvalidMinSimilarity, InvalidScoreWeights},
         warning::Warning,
     },
-    models::{SnippetId, SnippetOrDocumentId, UserId},
+    models::{InteractionType, SnippetId, SnippetOrDocumentId, UserId},
     storage::{self, Exclusions},
     Error,
 };
@@ -157,52 +157,85 @@ pub(super) fn validate_count(
     Ok(())
 }
 
+pub(super) fn validate_score_weights(weights: [f32; 3]) -> Result<(), InvalidScoreWeights> {
+    if weights.iter().any(|weight| !(0. ..=1.).contains(weight))
+        || weights.iter().all(|weight| *weight == 0.)
+    {
+        return Err(InvalidScoreWeights { weights });
+    }
+
+    Ok(())
+}
+
+pub(super) fn validate_min_similarity(min_similarity: f32) -> Result<(), InvalidMinSimilarity> {
+    if !(-1. ..=1.).contains(&min_similarity) {
+        return Err(InvalidMinSimilarity { min_similarity });
+    }
+
+    Ok(())
+}
+
+/// Gets the documents and snippets which are excluded from the personalized results.
+///
+/// Documents dismissed by a user are always excluded, seen documents only if requested.
 pub(super) async fn personalized_exclusions(
-    storage: &impl storage::Interaction,
+    storage: &(impl storage::Dismissal + storage::Interaction),
     config: &PersonalizationConfig,
     personalize: &Personalize,
+    time: DateTime<Utc>,
 ) -> Result<Exclusions, Error> {
-    if !personalize.exclude_seen {
-        return Ok(Exclusions::default());
-    }
-
-    Ok(match &personalize.user {
-        InputUser::Ref { id } => {
-            //FIXME move optimization into storage abstraction
-            if config.store_user_history {
-                let documents = storage::Interaction::get(storage, id).await?;
+    let mut exclusions = if personalize.exclude_seen {
+        match &personalize.user {
+            InputUser::Ref { id } => {
+                //FIXME move optimization into storage abstraction
+                if config.store_user_history {
+                    let documents = storage::Interaction::get(storage, id).await?;
+                    Exclusions {
+                        documents,
+                        snippets: Vec::new(),
+                    }
+                } else {
+                    Exclusions::default()
+                }
+            }
+            InputUser::Inline { history } => {
+                let (documents, snippets) =
+                    history
+                        .iter()
+                        .partition_map(|entry| match entry.id.clone() {
+                            SnippetOrDocumentId::SnippetId(id) => either::Either::Right(id),
+                            SnippetOrDocumentId::DocumentId(id) => either::Either::Left(id),
+                        });
                 Exclusions {
                     documents,
-                    snippets: Vec::new(),
+                    snippets,
                 }
-            } else {
-                Exclusions::default()
-            }
-        }
-        InputUser::Inline { history } => {
-            let (documents, snippets) =
-                history
-                    .iter()
-                    .partition_map(|entry| match entry.id.clone() {
-                        SnippetOrDocumentId::SnippetId(id) => either::Either::Right(id),
-                        SnippetOrDocumentId::DocumentId(id) => either::Either::Left(id),
-                    });
-            Exclusions {
-                documents,
-                snippets,
             }
         }
-    })
+    } else {
+        Exclusions::default()
+    };
+
+    if let InputUser::Ref { id } = &personalize.user {
+        let since = config
+            .dismissal_expiration
+            .map(|days| time - Duration::days(days.into()));
+        exclusions
+            .documents
+            .extend(storage::Dismissal::get(storage, id, since).await?);
+    }
+
+    Ok(exclusions)
 }
 
 pub(crate) async fn update_interactions(
     storage: &(impl storage::Document + storage::Interaction + storage::Interest + storage::Tag),
     coi: &CoiSystem,
     user_id: &UserId,
-    interactions: Vec<SnippetOrDocumentId>,
+    interactions: Vec<(SnippetOrDocumentId, InteractionType)>,
     store_user_history: bool,
     time: DateTime<Utc>,
-) -> Result<(), Error> {
+) -> Result<storage::Warning<SnippetId>, Error> {
     storage::Interaction::user_seen(storage, user_id, time).await?;
 
     storage::Interaction::update_interactions(
@@ -221,9 +254,7 @@ pub(crate) async fn update_interactions(
                 .clone()
         },
     )
-    .await?;
-
-    Ok(())
+    .await
 }
 
 #[cfg(test)]
@@ -268,6 +299,7 @@ pub(crate) async fn personalize_documents_by(
                 include_properties,
                 include_snippet,
                 filter,
+                published_after: None,
             }
             .run_on(storage)
             .await?
@@ -0,0 +1,421 @@
+// Copyright 2023 Xayn AG
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Optional grpc interface for ingestion and personalization.
+//!
+//! The services mirror the corresponding REST endpoints and share their validation and logic. The
+//! generated messages are converted to and from the REST models through their json mapping.
+
+pub mod proto {
+    #![allow(clippy::pedantic, unreachable_pub, unused_qualifications)]
+
+    tonic::include_proto!("xayn.web_api.v1");
+    include!(concat!(env!("OUT_DIR"), "/xayn.web_api.v1.serde.rs"));
+}
+
+use std::{
+    future::Future,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    sync::Arc,
+};
+
+use actix_web::http::StatusCode;
+use anyhow::anyhow;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tokio_util::{sync::CancellationToken, task::LocalPoolHandle};
+use tonic::{
+    metadata::MetadataMap,
+    transport::{server::TcpIncoming, Server},
+    Request,
+    Response,
+    Status,
+    Streaming,
+};
+use tracing::{info, instrument::WithSubscriber};
+use xayn_web_api_shared::request::TenantId;
+
+use self::proto::{
+    ingestion_server::{Ingestion, IngestionServer},
+    personalization_server::{Personalization, PersonalizationServer},
+    UpsertDocumentsRequest,
+    UpsertDocumentsResponse,
+    UserRecommendationsRequest,
+    UserRecommendationsResponse,
+};
+use crate::{
+    app::{AppState, SetupError, TenantState},
+    backoffice::routes::{ingest, UnvalidatedDocumentForIngestion},
+    error::application::application_event,
+    frontoffice::routes::{
+        personalize_documents_for_user,
+        PersonalizedDocumentData,
+        UnvalidatedPersonalizedDocumentsRequest,
+    },
+    Error,
+};
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub(crate) struct Config {
+    /// Whether to serve the grpc interface.
+    pub(crate) enabled: bool,
+
+    /// Address to which the grpc server should bind.
+    pub(crate) bind_to: SocketAddr,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_to: SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 4253).into(),
+        }
+    }
+}
+
+/// A handle to the running grpc server.
+pub(crate) struct Handle {
+    address: SocketAddr,
+    shutdown: CancellationToken,
+    server: JoinHandle<Result<(), tonic::transport::Error>>,
+}
+
+impl Handle {
+    /// Returns the address the server is listening on.
+    pub(crate) fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Stops the server gracefully.
+    pub(crate) fn stop(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// Waits for the server to have stopped and returns it's return value.
+    pub(crate) async fn wait_for_termination(self) -> Result<(), anyhow::Error> {
+        self.server.await??;
+        Ok(())
+    }
+}
+
+/// Starts the grpc server in the background.
+pub(crate) async fn start(config: Config, state: Arc<AppState>) -> Result<Handle, SetupError> {
+    let listener = TcpListener::bind(config.bind_to).await?;
+    let address = listener.local_addr()?;
+    info!(grpc_bound_to = %address);
+    let incoming =
+        TcpIncoming::from_listener(listener, true, None).map_err(|error| anyhow!(error))?;
+
+    let service = Service {
+        state,
+        // Hint: storage futures are not `Send`, they are run on a pool of single threaded runtimes
+        // like the actix workers do
+        pool: LocalPoolHandle::new(num_cpus::get()),
+    };
+    let shutdown = CancellationToken::new();
+    let server = Server::builder()
+        .add_service(IngestionServer::new(service.clone()))
+        .add_service(PersonalizationServer::new(service))
+        .serve_with_incoming_shutdown(incoming, {
+            let shutdown = shutdown.clone();
+            async move { shutdown.cancelled().await }
+        });
+    let server = tokio::spawn(server.with_current_subscriber());
+
+    Ok(Handle {
+        address,
+        shutdown,
+        server,
+    })
+}
+
+// Hint: lowercase as required by grpc metadata, this is the same as the `X-Xayn-Tenant-Id` header
+const TENANT_ID_METADATA: &str = "x-xayn-tenant-id";
+
+#[derive(Clone)]
+struct Service {
+    state: Arc<AppState>,
+    pool: LocalPoolHandle,
+}
+
+impl Service {
+    fn tenant_id(&self, metadata: &MetadataMap) -> Result<TenantId, Status> {
+        if let Some(id) = self.state.legacy_tenant() {
+            return Ok(id.clone());
+        }
+
+        let Some(value) = metadata.get(TENANT_ID_METADATA) else {
+            return Err(Status::invalid_argument(format!(
+                "{TENANT_ID_METADATA} metadata missing"
+            )));
+        };
+        value
+            .to_str()
+            .ok()
+            .and_then(|value| TenantId::try_parse_ascii(value.trim().as_bytes()).ok())
+            .ok_or_else(|| Status::invalid_argument(format!("invalid {TENANT_ID_METADATA}")))
+    }
+
+    /// Runs the request for the tenant on the local pool.
+    async fn run<F, Fut, T>(&self, metadata: &MetadataMap, run: F) -> Result<T, Status>
+    where
+        F: FnOnce(Arc<AppState>, TenantState) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, Error>> + 'static,
+        T: Send + 'static,
+    {
+        let tenant_id = self.tenant_id(metadata)?;
+        let state = self.state.clone();
+        self.pool
+            .spawn_pinned(move || async move {
+                let tenant_state = state.tenant_state(tenant_id).await?;
+                run(state, tenant_state).await
+            })
+            .await
+            .map_err(|error| Status::internal(error.to_string()))?
+            .map_err(|error| into_status(&error))
+    }
+
+    async fn upsert(
+        &self,
+        metadata: &MetadataMap,
+        request: UpsertDocumentsRequest,
+    ) -> Result<(), Status> {
+        let documents = request
+            .documents
+            .into_iter()
+            .map(from_proto::<UnvalidatedDocumentForIngestion>)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.run(
+            metadata,
+            move |state, TenantState(storage, embedder)| async move {
+                ingest(&state, &storage, &embedder, documents).await
+            },
+        )
+        .await
+    }
+}
+
+fn into_status(error: &Error) -> Status {
+    application_event!(error.level(), %error);
+    let message = error.to_string();
+    match error.status_code() {
+        StatusCode::BAD_REQUEST | StatusCode::PAYLOAD_TOO_LARGE => {
+            Status::invalid_argument(message)
+        }
+        StatusCode::NOT_FOUND => Status::not_found(message),
+        StatusCode::CONFLICT => Status::failed_precondition(message),
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => {
+            Status::unavailable(message)
+        }
+        // Hint: like the REST api only the kind of internal errors is exposed
+        _ => Status::internal(error.kind()),
+    }
+}
+
+/// Converts a generated message into the corresponding REST model.
+///
+/// The conversion fails like the deserialization of an invalid REST request body.
+fn from_proto<T>(message: impl Serialize) -> Result<T, Status>
+where
+    T: DeserializeOwned,
+{
+    serde_json::to_value(message)
+        .and_then(serde_json::from_value)
+        .map_err(|error| Status::invalid_argument(error.to_string()))
+}
+
+/// Converts a REST model into the corresponding generated message.
+fn into_proto<T>(model: impl Serialize) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    serde_json::to_value(model)
+        .and_then(serde_json::from_value)
+        .map_err(Into::into)
+}
+
+#[tonic::async_trait]
+impl Ingestion for Service {
+    async fn upsert_documents(
+        &self,
+        request: Request<UpsertDocumentsRequest>,
+    ) -> Result<Response<UpsertDocumentsResponse>, Status> {
+        let (metadata, _, request) = request.into_parts();
+        self.upsert(&metadata, request).await?;
+
+        Ok(Response::new(UpsertDocumentsResponse {}))
+    }
+
+    async fn upsert_documents_stream(
+        &self,
+        request: Request<Streaming<UpsertDocumentsRequest>>,
+    ) -> Result<Response<UpsertDocumentsResponse>, Status> {
+        let (metadata, _, mut requests) = request.into_parts();
+        while let Some(request) = requests.message().await? {
+            self.upsert(&metadata, request).await?;
+        }
+
+        Ok(Response::new(UpsertDocumentsResponse {}))
+    }
+}
+
+#[tonic::async_trait]
+impl Personalization for Service {
+    async fn user_recommendations(
+        &self,
+        request: Request<UserRecommendationsRequest>,
+    ) -> Result<Response<UserRecommendationsResponse>, Status> {
+        let (metadata, _, request) = request.into_parts();
+        let user_id = request.user_id;
+        // Hint: like an empty REST request body, which is also deserialized into the defaults
+        let request = from_proto::<UnvalidatedPersonalizedDocumentsRequest>(
+            request.request.unwrap_or_default(),
+        )?;
+        let documents = self
+            .run(
+                &metadata,
+                move |state, TenantState(storage, _)| async move {
+                    let Some(documents) = personalize_documents_for_user(
+                        &state,
+                        &storage,
+                        user_id.try_into()?,
+                        request,
+                    )
+                    .await?
+                    else {
+                        return Ok(None);
+                    };
+                    documents
+                        .into_iter()
+                        .map(|document| into_proto(PersonalizedDocumentData::from(document)))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map(Some)
+                },
+            )
+            .await?
+            .ok_or_else(|| Status::failed_precondition("not enough interactions"))?;
+
+        Ok(Response::new(UserRecommendationsResponse { documents }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use xayn_ai_bert::Embedding1;
+    use xayn_test_utils::assert_approx_eq;
+
+    use super::*;
+    use crate::{
+        backoffice::routes::InputDataRequest,
+        models::{
+            DocumentProperties,
+            DocumentSnippet,
+            DocumentTags,
+            PersonalizedDocument,
+            SnippetId,
+        },
+    };
+
+    #[test]
+    fn test_document_from_proto() {
+        let document = proto::Document {
+            id: "d1".into(),
+            data: Some(proto::document::Data::File(b"raw".to_vec())),
+            properties: Some(pbjson_types::Struct {
+                fields: [(
+                    "title".to_string(),
+                    pbjson_types::Value {
+                        kind: Some(pbjson_types::value::Kind::StringValue("a title".into())),
+                    },
+                )]
+                .into(),
+            }),
+            tags: vec!["tag".into()],
+            is_candidate: Some(false),
+            default_is_candidate: Some(true),
+            summarize: true,
+            split: Some(false),
+        };
+        let document = from_proto::<UnvalidatedDocumentForIngestion>(document).unwrap();
+
+        assert_eq!(document.id, "d1");
+        assert!(matches!(document.data, InputDataRequest::File(file) if file == "cmF3"));
+        assert_eq!(
+            document.properties,
+            [("title".into(), json!("a title"))].into()
+        );
+        assert_eq!(document.tags, ["tag"]);
+        assert_eq!(document.is_candidate, Some(false));
+        assert_eq!(document.default_is_candidate, Some(true));
+        assert!(document.summarize);
+        assert_eq!(document.split, Some(false));
+    }
+
+    #[test]
+    fn test_invalid_filter_from_proto() {
+        let request = proto::RecommendationRequest {
+            filter: Some(pbjson_types::Struct {
+                fields: [(
+                    "unknown".to_string(),
+                    pbjson_types::Value {
+                        kind: Some(pbjson_types::value::Kind::BoolValue(true)),
+                    },
+                )]
+                .into(),
+            }),
+            ..proto::RecommendationRequest::default()
+        };
+        let status = from_proto::<UnvalidatedPersonalizedDocumentsRequest>(request).unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_personalized_document_into_proto() {
+        let properties =
+            serde_json::from_value::<DocumentProperties>(json!({ "title": "a title" })).unwrap();
+        let document = PersonalizedDocument {
+            id: SnippetId::new("d1".try_into().unwrap(), 2),
+            score: 0.5,
+            embedding: Embedding1::from(vec![1., 0.]).normalize().unwrap(),
+            properties: Some(properties),
+            snippet: Some(DocumentSnippet::new_with_length_constraint("a snippet", 1..).unwrap()),
+            tags: DocumentTags::default(),
+            dev: None,
+        };
+        let document =
+            into_proto::<proto::PersonalizedDocument>(PersonalizedDocumentData::from(document))
+                .unwrap();
+
+        assert_eq!(document.id, "d1");
+        assert_eq!(
+            document.snippet_id,
+            Some(proto::SnippetId {
+                document_id: "d1".into(),
+                sub_id: 2,
+            }),
+        );
+        assert_approx_eq!(f32, document.score, 0.5);
+        assert_eq!(
+            serde_json::to_value(document.properties).unwrap(),
+            json!({ "title": "a title" }),
+        );
+        assert_eq!(document.snippet.as_deref(), Some("a snippet"));
+        assert!(!document.exploration);
+    }
+}
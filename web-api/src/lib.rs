@@ -38,6 +38,7 @@ mod embedding;
 mod error;
 pub mod extractor;
 mod frontoffice;
+pub mod grpc;
 pub mod logging;
 mod middleware;
 #[cfg(test)]
@@ -51,9 +52,9 @@ mod utils;
 mod web_api;
 
 pub use crate::{
-    app::{start, Application, SetupError},
+    app::{migrate, reembed, start, Application, SetupError},
     error::application::{ApplicationError, Error},
-    frontoffice::{bench_derive_interests, bench_rerank},
+    frontoffice::{bench_derive_interests, bench_diversify, bench_rerank},
     net::AppHandle,
     web_api::WebApi,
 };
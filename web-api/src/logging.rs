@@ -14,14 +14,23 @@
 
 //! Setup tracing on different platforms.
 
-use std::{fs::OpenOptions, path::Path};
+use std::{
+    fs::OpenOptions,
+    path::Path,
+    sync::{Mutex, PoisonError},
+};
 
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
 use serde::{Deserialize, Serialize};
 use tracing::{error, Dispatch, Level};
 use tracing_subscriber::{
     filter::{LevelFilter, Targets},
     layer::SubscriberExt,
+    reload::{self, Handle},
     util::{SubscriberInitExt, TryInitError},
+    Registry,
 };
 
 use crate::utils::RelativePathBuf;
@@ -53,6 +62,9 @@ mod serde_level_filter {
     }
 }
 
+/// The handle to change the level of the global logging.
+static LEVEL: Mutex<Option<Handle<LevelFilter, Registry>>> = Mutex::new(None);
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 #[cfg_attr(test, serde(deny_unknown_fields))]
@@ -61,6 +73,7 @@ pub struct Config {
     #[serde(with = "serde_level_filter")]
     pub level: LevelFilter,
     pub install_panic_hook: bool,
+    pub otlp: OtlpConfig,
 }
 
 impl Default for Config {
@@ -69,6 +82,30 @@ impl Default for Config {
             file: None,
             level: LevelFilter::INFO,
             install_panic_hook: true,
+            otlp: OtlpConfig::default(),
+        }
+    }
+}
+
+/// Export of the spans to an `OpenTelemetry` collector.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub struct OtlpConfig {
+    /// Whether to export the spans.
+    pub enabled: bool,
+    /// The grpc endpoint of the collector.
+    pub endpoint: String,
+    /// The name of the service the spans are attributed to.
+    pub service_name: String,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://localhost:4317".into(),
+            service_name: "web-api".into(),
         }
     }
 }
@@ -79,20 +116,48 @@ impl Default for Config {
 ///
 /// Even though this returns an error if logging was already initialized you
 /// should only call this function when you expect it to succeed.
+///
+/// If the export of spans is enabled, this must be called within a tokio runtime.
 pub fn initialize_global(config: &Config) -> Result<(), TryInitError> {
-    let dispatch = create_trace_dispatch(
+    let (dispatch, level) = create_trace_dispatch(
         config.level,
         config.file.as_ref().map(|f| f.relative()).as_deref(),
+        config.otlp.enabled.then_some(&config.otlp),
     );
     dispatch.try_init()?;
+    *LEVEL.lock().unwrap_or_else(PoisonError::into_inner) = Some(level);
     if config.install_panic_hook {
         init_panic_logging();
     }
     Ok(())
 }
 
-fn create_trace_dispatch(level: LevelFilter, file: Option<&Path>) -> Dispatch {
-    let subscriber = tracing_subscriber::registry();
+/// Changes the level of the global logging.
+///
+/// This is a no-op if the logging wasn't initialized with [`initialize_global()`].
+pub(crate) fn reload_level(level: LevelFilter) -> Result<(), reload::Error> {
+    if let Some(handle) = &*LEVEL.lock().unwrap_or_else(PoisonError::into_inner) {
+        handle.reload(level)?;
+    }
+
+    Ok(())
+}
+
+/// Flushes the exported spans.
+///
+/// This should be called before the application terminates.
+pub fn shutdown_global() {
+    global::shutdown_tracer_provider();
+}
+
+fn create_trace_dispatch(
+    level: LevelFilter,
+    file: Option<&Path>,
+    otlp: Option<&OtlpConfig>,
+) -> (Dispatch, Handle<LevelFilter, Registry>) {
+    // the level is the only reloadable part and must be layered directly on the registry
+    let (level, handle) = reload::Layer::new(level);
+    let subscriber = tracing_subscriber::registry().with(level);
 
     let stdout_log = tracing_subscriber::fmt::layer()
         .json()
@@ -100,7 +165,7 @@ fn create_trace_dispatch(level: LevelFilter, file: Option<&Path>) -> Dispatch {
         .with_current_span(false);
 
     let sqlx_query_no_info = Targets::new()
-        .with_default(level)
+        .with_default(Level::TRACE)
         .with_target("sqlx::query", Level::WARN);
 
     let file_log = file
@@ -124,12 +189,37 @@ fn create_trace_dispatch(level: LevelFilter, file: Option<&Path>) -> Dispatch {
         })
         .ok();
 
-    subscriber
+    let otlp_log = otlp
+        .map(|config| {
+            opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(&config.endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new([KeyValue::new(
+                    "service.name",
+                    config.service_name.clone(),
+                )])))
+                .install_batch(runtime::Tokio)
+                .map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer))
+        })
+        .transpose()
+        .map_err(|error| {
+            eprintln!("Setup otlp export failed: {error}");
+        })
+        .ok()
+        .flatten();
+
+    let dispatch = subscriber
         .with(stdout_log)
         .with(sqlx_query_no_info)
         .with(file_log)
-        .with(level)
-        .into()
+        .with(otlp_log)
+        .into();
+
+    (dispatch, handle)
 }
 
 fn init_panic_logging() {
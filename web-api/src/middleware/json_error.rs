@@ -41,7 +41,7 @@ where
     B: MessageBody + Debug + 'static,
 {
     let request_id = match RequestContext::try_extract_from_request(request.request(), |context| {
-        context.request_id
+        context.request_id.clone()
     }) {
         Ok(id) => id,
         Err(error) => {
@@ -60,7 +60,10 @@ where
     Either::Right(
         service
             .call(request)
-            .map_ok(move |resp| wrap_service_response(resp, request_id))
+            .map_ok({
+                let request_id = request_id.clone();
+                move |resp| wrap_service_response(resp, &request_id)
+            })
             // note that endpoints _directly_ turn any `Err(..)` into an `Ok(err_resp)`,
             // so we will only see middleware errors here, never endpoint errors
             .map_err(move |resp| WrappedMiddlewareError::wrap(resp, request_id)),
@@ -69,7 +72,7 @@ where
 
 fn wrap_service_response<B: MessageBody + Debug + 'static>(
     response: ServiceResponse<B>,
-    request_id: RequestId,
+    request_id: &RequestId,
 ) -> ServiceResponse<BoxBody> {
     if is_wrappable_error(response.response()) {
         let (request, response) = response.into_parts();
@@ -142,7 +145,7 @@ impl ResponseError for WrappedMiddlewareError {
 
             JsonErrorResponseBuilder::render(
                 self.status_code().as_str(),
-                self.request_id,
+                &self.request_id,
                 &msg.map_or(Value::Null, |msg| json!({ "message": msg })),
             )
             .apply_to(response)
@@ -12,11 +12,12 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use std::{future::Future, str, sync::Arc};
+use std::{future::Future, str, sync::Arc, time::Instant};
 
 use actix_web::{
     body::BoxBody,
     dev::{Service, ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
     HttpMessage,
     HttpRequest,
 };
@@ -25,10 +26,10 @@ use futures_util::{
     future::{self, Either},
     FutureExt,
 };
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 use thiserror::Error;
 use tokio::{task::futures::TaskLocalFuture, task_local};
-use tracing::{error_span, instrument, trace, Instrument, Level};
+use tracing::{error_span, info, instrument, trace, Instrument, Level};
 use uuid::Uuid;
 use xayn_web_api_shared::request::TenantId;
 
@@ -64,9 +65,17 @@ pub(crate) struct AccessError {
     method: &'static str,
 }
 
-#[derive(Clone, Copy, Debug, derive_more::Display, Serialize)]
-#[serde(transparent)]
-pub(crate) struct RequestId(Uuid);
+#[derive(Clone, Debug, derive_more::Display)]
+pub(crate) struct RequestId(Arc<str>);
+
+impl Serialize for RequestId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
 
 task_local! {
     static CURRENT_REQUEST_ID: RequestId;
@@ -74,11 +83,30 @@ task_local! {
 
 impl RequestId {
     pub(crate) fn generate() -> Self {
-        Self(Uuid::new_v4())
+        Self(Uuid::new_v4().to_string().into())
+    }
+
+    pub(crate) fn missing() -> Self {
+        Self(Uuid::nil().to_string().into())
+    }
+
+    /// Parses a client provided request id.
+    ///
+    /// Any non-empty sequence of visible ascii characters up to [`MAX_REQUEST_ID_LENGTH`] bytes
+    /// is accepted, surrounding whitespace is trimmed.
+    fn try_parse_ascii(ascii: &[u8]) -> Option<Self> {
+        let ascii = trim_ascii(ascii);
+        if ascii.is_empty()
+            || ascii.len() > MAX_REQUEST_ID_LENGTH
+            || !ascii.iter().all(u8::is_ascii_graphic)
+        {
+            return None;
+        }
+        str::from_utf8(ascii).ok().map(|id| Self(id.into()))
     }
 
-    pub(crate) const fn missing() -> Self {
-        Self(Uuid::nil())
+    fn header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.0).unwrap(/* request ids only contain visible ascii */)
     }
 
     pub(crate) fn wrap_future<F>(self, future: F) -> TaskLocalFuture<RequestId, F>
@@ -90,7 +118,7 @@ impl RequestId {
 
     pub(crate) fn extract_from_task_local_storage() -> Result<RequestId, AccessError> {
         CURRENT_REQUEST_ID
-            .try_with(|id| *id)
+            .try_with(RequestId::clone)
             .map_err(|_| AccessError {
                 method: "extract_from_task_local_storage",
             })
@@ -111,19 +139,20 @@ where
     S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = actix_web::Error>,
     S::Future: 'static,
 {
-    let request_id = RequestId::generate();
+    let request_id = extract_request_id(&request);
 
     let tenant_id = match extract_tenant_id(legacy_tenant, &request) {
         Ok(id) => id,
         Err(error) => {
-            let response = middleware_failure(
+            let mut response = middleware_failure(
                 "setup_request_context",
                 request,
-                Some(request_id),
+                Some(request_id.clone()),
                 None,
                 error,
                 Level::ERROR,
             );
+            insert_request_id_header(&mut response, &request_id);
             return Either::Left(future::ok(response));
         }
     };
@@ -142,21 +171,69 @@ where
 
     let context = Arc::new(RequestContext {
         tenant_id,
-        request_id,
+        request_id: request_id.clone(),
     });
 
     request.extensions_mut().insert(context);
+    let http_request = request.request().clone();
 
+    let start = Instant::now();
     Either::Right(
-        request_id.wrap_future(
-            service
-                .call(request)
-                .instrument(span.clone())
-                .inspect(|_| trace!(parent: span, "request processed")),
-        ),
+        request_id
+            .clone()
+            .wrap_future(
+                service
+                    .call(request)
+                    .instrument(span.clone())
+                    .map(move |result| match result {
+                        Ok(mut response) => {
+                            info!(
+                                parent: &span,
+                                status = response.status().as_u16(),
+                                elapsed = ?start.elapsed(),
+                                "request processed",
+                            );
+                            insert_request_id_header(&mut response, &request_id);
+                            Ok(response)
+                        }
+                        Err(error) => {
+                            info!(
+                                parent: &span,
+                                %error,
+                                elapsed = ?start.elapsed(),
+                                "request failed",
+                            );
+                            // Hint: render the error here, otherwise the response wouldn't have the header
+                            let mut response = ServiceResponse::from_err(error, http_request);
+                            insert_request_id_header(&mut response, &request_id);
+                            Ok(response)
+                        }
+                    }),
+            ),
     )
 }
 
+// Hint: lowercase as required by `HeaderName::from_static`, header names are case insensitive
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Extracts the request id from the header or generates a new one if it is missing or invalid.
+fn extract_request_id(request: &ServiceRequest) -> RequestId {
+    request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| RequestId::try_parse_ascii(value.as_bytes()))
+        .unwrap_or_else(RequestId::generate)
+}
+
+fn insert_request_id_header(response: &mut ServiceResponse<BoxBody>, request_id: &RequestId) {
+    response.headers_mut().insert(
+        HeaderName::from_static(REQUEST_ID_HEADER),
+        request_id.header_value(),
+    );
+}
+
 const TENANT_ID_HEADER: &str = "X-Xayn-Tenant-Id";
 
 fn extract_tenant_id(
@@ -242,4 +319,27 @@ mod tests {
         TenantId::try_parse_ascii(b"abcdefghijklmnopqrstuvwxyz").unwrap();
         TenantId::try_parse_ascii(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ").unwrap();
     }
+
+    #[test]
+    fn test_parsing_request_id_from_ascii() {
+        assert!(RequestId::try_parse_ascii(b"").is_none());
+        assert!(RequestId::try_parse_ascii(b"  ").is_none());
+        assert!(RequestId::try_parse_ascii(b"ab cd").is_none());
+        assert!(RequestId::try_parse_ascii("äb".as_bytes()).is_none());
+        assert!(RequestId::try_parse_ascii(&[65u8; MAX_REQUEST_ID_LENGTH]).is_some());
+        assert!(RequestId::try_parse_ascii(&[65u8; MAX_REQUEST_ID_LENGTH + 1]).is_none());
+
+        assert_eq!(
+            RequestId::try_parse_ascii(b" trace-1:a/b ")
+                .unwrap()
+                .to_string(),
+            "trace-1:a/b",
+        );
+        assert_eq!(
+            RequestId::try_parse_ascii(b"67e55044-10b1-426f-9247-bb680e5fe0c8")
+                .unwrap()
+                .to_string(),
+            "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        );
+    }
 }
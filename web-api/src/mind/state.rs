@@ -36,6 +36,7 @@ use crate::{
         DocumentForIngestion,
         DocumentId,
         DocumentProperties,
+        InteractionType,
         PreprocessingStep,
         Sha256Hash,
         SnippetOrDocumentId,
@@ -119,7 +120,7 @@ impl State {
                 &self.storage,
                 &self.coi,
                 user,
-                vec![id],
+                vec![(id, InteractionType::default())],
                 self.personalization.store_user_history,
                 time,
             )
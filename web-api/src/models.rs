@@ -20,7 +20,7 @@ use std::{
     str::FromStr,
 };
 
-use chrono::DateTime;
+use chrono::{DateTime, NaiveDate, Utc};
 use derive_more::{Deref, DerefMut, Display, Into};
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -33,10 +33,11 @@ use sqlx::{
     Type,
 };
 use xayn_ai_bert::NormalizedEmbedding;
-use xayn_ai_coi::Document as AiDocument;
+use xayn_ai_coi::{CoiId, Document as AiDocument};
 
 use crate::{
     error::common::{
+        InvalidBoostRuleId,
         InvalidDocumentId,
         InvalidDocumentProperties,
         InvalidDocumentProperty,
@@ -44,9 +45,11 @@ use crate::{
         InvalidDocumentPropertyReason,
         InvalidDocumentQuery,
         InvalidDocumentSnippet,
+        InvalidDocumentSource,
         InvalidDocumentTag,
         InvalidDocumentTags,
         InvalidEsSnippetIdFormat,
+        InvalidIdempotencyKey,
         InvalidString,
         InvalidUserId,
         RangeBoundsInError,
@@ -188,6 +191,12 @@ string_wrapper! {
     pub(crate) DocumentQuery, InvalidDocumentQuery, GENERIC_STRING_SYNTAX;
     /// A document snippet.
     pub(crate) DocumentSnippet, InvalidDocumentSnippet, GENERIC_STRING_SYNTAX;
+    /// A client defined key to deduplicate retried requests.
+    pub(crate) IdempotencyKey, InvalidIdempotencyKey, GENERIC_ID_SYNTAX, 1..=256;
+    /// A document source, e.g. the domain of a publisher.
+    pub(crate) DocumentSource, InvalidDocumentSource, GENERIC_STRING_SYNTAX, 1..=256;
+    /// A unique boost rule identifier.
+    pub(crate) BoostRuleId, InvalidBoostRuleId, GENERIC_ID_SYNTAX, 1..=256;
 }
 
 /// Id pointing to a specific snippet in a document.
@@ -398,16 +407,36 @@ impl IntoIterator for DocumentProperties {
 #[sqlx(transparent)]
 pub(crate) struct DocumentTags(Vec<DocumentTag>);
 
+impl DocumentTags {
+    const MAX: usize = 10;
+
+    /// Appends the tag if the max number of tags isn't exceeded.
+    pub(crate) fn try_push(&mut self, tag: DocumentTag) -> Result<(), InvalidDocumentTags> {
+        let size = self.0.len() + 1;
+        if size <= Self::MAX {
+            self.0.push(tag);
+            Ok(())
+        } else {
+            Err(InvalidDocumentTags {
+                size,
+                max: Self::MAX,
+            })
+        }
+    }
+}
+
 impl TryFrom<Vec<DocumentTag>> for DocumentTags {
     type Error = InvalidDocumentTags;
 
     fn try_from(tags: Vec<DocumentTag>) -> Result<Self, Self::Error> {
         let size = tags.len();
-        let max = 10;
-        if size <= max {
+        if size <= Self::MAX {
             Ok(Self(tags))
         } else {
-            Err(InvalidDocumentTags { size, max })
+            Err(InvalidDocumentTags {
+                size,
+                max: Self::MAX,
+            })
         }
     }
 }
@@ -421,6 +450,87 @@ impl<'a> IntoIterator for &'a DocumentTags {
     }
 }
 
+/// The source preferences of a user.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct SourcePreferences {
+    /// Sources which are trusted by the user.
+    pub(crate) trusted: Vec<DocumentSource>,
+
+    /// Sources whose documents are excluded from the recommendations for the user.
+    pub(crate) excluded: Vec<DocumentSource>,
+}
+
+/// A document which is pinned to a position in the recommendations of a user.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct PinnedDocument {
+    pub(crate) id: DocumentId,
+
+    /// The zero-based position in the recommendations.
+    pub(crate) position: usize,
+}
+
+/// The number of candidates with a value of an indexed property.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct PropertyValueCount {
+    pub(crate) value: Value,
+    pub(crate) count: usize,
+}
+
+/// An editorial rule which boosts or buries documents with a property value for all users.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct BoostRule {
+    pub(crate) id: BoostRuleId,
+
+    /// The property which is compared with the value.
+    pub(crate) property_id: DocumentPropertyId,
+
+    /// The value of the property of the affected documents.
+    pub(crate) value: DocumentProperty,
+
+    /// The weight which is added to the score of the affected documents, negative to bury them.
+    pub(crate) weight: f32,
+
+    /// The time after which the rule isn't applied anymore.
+    pub(crate) expires_at: Option<DateTime<Utc>>,
+}
+
+/// The type of an interaction of a user with a snippet.
+///
+/// All types are positive reactions and update the interests of the user alike, the type is only
+/// stored as part of the interaction history.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "interaction_type", rename_all = "snake_case")]
+pub(crate) enum InteractionType {
+    #[default]
+    Positive,
+    Click,
+    Read,
+    Like,
+    Share,
+}
+
+/// A stored interaction of a user with a snippet.
+#[derive(Clone, Debug)]
+pub(crate) struct UserInteraction {
+    pub(crate) snippet_id: SnippetId,
+    pub(crate) interaction_type: InteractionType,
+    pub(crate) time: DateTime<Utc>,
+    /// The coi which has been updated by the interaction.
+    ///
+    /// Interactions stored before this has been tracked don't have a coi.
+    pub(crate) coi_id: Option<CoiId>,
+}
+
+/// The aggregated number of interactions of all users with a snippet on a day.
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub(crate) struct InteractionCount {
+    pub(crate) snippet_id: SnippetId,
+    pub(crate) day: NaiveDate,
+    pub(crate) count: u32,
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct SnippetForInteraction {
     pub(crate) id: SnippetId,
@@ -540,6 +650,13 @@ pub(crate) struct ExcerptedDocument {
     pub(crate) is_candidate: bool,
 }
 
+/// A document in the search index.
+#[derive(Debug)]
+pub(crate) struct IndexedDocument {
+    pub(crate) id: DocumentId,
+    pub(crate) properties: DocumentProperties,
+}
+
 /// The preprocessing step used on the raw document.
 // Note: The same input parameter (e.g. split) can over time
 //       map to different variants, e.g. now it maps to `CuttersSplit`
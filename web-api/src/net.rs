@@ -42,10 +42,13 @@ use tracing::{
 };
 use xayn_web_api_shared::{request::TenantId, serde::serde_duration_as_seconds};
 
-use crate::middleware::{
-    json_error::wrap_non_json_errors,
-    request_context::setup_request_context,
-    tracing::new_http_server_with_subscriber,
+use crate::{
+    grpc,
+    middleware::{
+        json_error::wrap_non_json_errors,
+        request_context::setup_request_context,
+        tracing::new_http_server_with_subscriber,
+    },
 };
 
 /// Configuration for roughly network/connection layer specific configurations.
@@ -64,6 +67,15 @@ pub struct Config {
     /// Client request timeout in seconds
     #[serde(with = "serde_duration_as_seconds")]
     pub(crate) client_request_timeout: Duration,
+
+    /// Maximum size of json request bodies in bytes.
+    ///
+    /// Larger requests are rejected with `413 Payload Too Large`. If not set, limits are left to
+    /// the infrastructure.
+    pub(crate) max_body_size: Option<usize>,
+
+    /// Configuration of the optional grpc interface.
+    pub(crate) grpc: grpc::Config,
 }
 
 impl Default for Config {
@@ -72,6 +84,8 @@ impl Default for Config {
             bind_to: SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 4252).into(),
             keep_alive: Duration::from_secs(61),
             client_request_timeout: Duration::from_secs(0),
+            max_body_size: None,
+            grpc: grpc::Config::default(),
         }
     }
 }
@@ -85,8 +99,9 @@ pub(crate) fn start_actix_server(
     attach_ops: impl Fn(&mut ServiceConfig) + Send + Clone + 'static,
     on_shutdown: Box<dyn FnOnce() -> BoxFuture<'static, ()>>,
 ) -> Result<AppHandle, anyhow::Error> {
-    // limits are handled by the infrastructure
-    let json_config = JsonConfig::default().limit(u32::MAX as usize);
+    // limits are handled by the infrastructure if not configured
+    let json_config =
+        JsonConfig::default().limit(net_config.max_body_size.unwrap_or(u32::MAX as usize));
     let subscriber = dispatcher::get_default(Dispatch::clone);
     let server = new_http_server_with_subscriber!(subscriber, move || {
         let legacy_tenant = legacy_tenant.clone();
@@ -139,6 +154,7 @@ pub(crate) fn start_actix_server(
         server_handle,
         addresses,
         term_handle,
+        grpc: None,
     })
 }
 
@@ -153,9 +169,16 @@ pub struct AppHandle {
     server_handle: ServerHandle,
     addresses: Vec<SocketAddr>,
     term_handle: JoinHandle<Result<(), io::Error>>,
+    grpc: Option<grpc::Handle>,
 }
 
 impl AppHandle {
+    /// Attaches the grpc server so that it is stopped together with the app.
+    pub(crate) fn with_grpc(mut self, grpc: grpc::Handle) -> Self {
+        self.grpc = Some(grpc);
+        self
+    }
+
     /// Returns an [`Url`] under which this app should be reachable.
     #[allow(clippy::missing_panics_doc)]
     pub fn url(&self) -> Url {
@@ -175,6 +198,11 @@ impl AppHandle {
         &self.addresses
     }
 
+    /// Returns the address the grpc server is listening on, if it is enabled.
+    pub fn grpc_address(&self) -> Option<SocketAddr> {
+        self.grpc.as_ref().map(grpc::Handle::address)
+    }
+
     /// Stops the app gracefully and escalates to non-graceful stopping on timeout, then awaits the apps result.
     #[instrument(skip(self))]
     pub async fn stop_and_wait(self) -> Result<(), anyhow::Error> {
@@ -190,6 +218,9 @@ impl AppHandle {
     /// afterwards.
     #[instrument(name = "stop_actix_server", skip(self))]
     pub async fn stop(&self) {
+        if let Some(grpc) = &self.grpc {
+            grpc.stop();
+        }
         self.server_handle.stop(false).await;
     }
 
@@ -201,6 +232,11 @@ impl AppHandle {
         self.term_handle
             .instrument(info_span!("awaiting termination"))
             .await??;
+        if let Some(grpc) = self.grpc {
+            grpc.wait_for_termination()
+                .instrument(info_span!("awaiting grpc termination"))
+                .await?;
+        }
         (self.on_shutdown)()
             .instrument(info_span!("on_shutdown_callback"))
             .await;
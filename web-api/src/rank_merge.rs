@@ -54,6 +54,55 @@ where
     scores
 }
 
+/// Normalizes the scores to the unit interval wrt their minimum and maximum.
+///
+/// If all scores are equal, then they are normalized to one.
+pub(crate) fn normalize_scores_min_max<K>(mut scores: ScoreMap<K>) -> ScoreMap<K>
+where
+    K: Eq + Hash,
+{
+    let (min, max) = scores
+        .values()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), score| {
+            (min.min(*score), max.max(*score))
+        });
+    let range = max - min;
+
+    for score in scores.values_mut() {
+        *score = if range > 0. {
+            (*score - min) / range
+        } else {
+            1.
+        };
+    }
+
+    scores
+}
+
+/// Normalizes the scores to a probability distribution.
+pub(crate) fn normalize_scores_softmax<K>(mut scores: ScoreMap<K>) -> ScoreMap<K>
+where
+    K: Eq + Hash,
+{
+    let max = scores
+        .values()
+        .max_by(|l, r| l.total_cmp(r))
+        .copied()
+        .unwrap_or_default();
+    for score in scores.values_mut() {
+        *score = (*score - max).exp();
+    }
+
+    let sum = scores.values().sum::<f32>();
+    if sum > 0. {
+        for score in scores.values_mut() {
+            *score /= sum;
+        }
+    }
+
+    scores
+}
+
 pub(crate) fn merge_scores_average_duplicates_only<K>(
     mut scores_1: ScoreMap<K>,
     scores_2: ScoreMap<K>,
@@ -140,8 +189,36 @@ where
 
 #[cfg(test)]
 mod tests {
+    use xayn_test_utils::assert_approx_eq;
+
     use super::*;
 
+    #[test]
+    fn test_normalize_scores_min_max() {
+        let scores: ScoreMap<&'static str> = [("foo", 2.), ("bar", 1.), ("baz", 5.)].into();
+        let scores = normalize_scores_min_max(scores);
+        assert_approx_eq!(f32, scores["foo"], 0.25);
+        assert_approx_eq!(f32, scores["bar"], 0.);
+        assert_approx_eq!(f32, scores["baz"], 1.);
+
+        let scores: ScoreMap<&'static str> = [("foo", 3.), ("bar", 3.)].into();
+        assert_eq!(
+            normalize_scores_min_max(scores),
+            [("foo", 1.), ("bar", 1.)].into(),
+        );
+    }
+
+    #[test]
+    fn test_normalize_scores_softmax() {
+        let scores: ScoreMap<&'static str> = [("foo", 1.), ("bar", 0.), ("baz", 1.)].into();
+        let scores = normalize_scores_softmax(scores);
+        let sum = 2. * 1_f32.exp() + 1.;
+        assert_approx_eq!(f32, scores["foo"], 1_f32.exp() / sum);
+        assert_approx_eq!(f32, scores["bar"], 1. / sum);
+        assert_approx_eq!(f32, scores["baz"], 1_f32.exp() / sum);
+        assert_approx_eq!(f32, scores.values().sum::<f32>(), 1.);
+    }
+
     #[test]
     fn test_rrf_parameters_are_used() {
         let left: ScoreMap<&'static str> = [("foo", 2.), ("bar", 1.), ("baz", 3.)].into();
@@ -19,25 +19,34 @@ pub(crate) mod postgres;
 pub(crate) mod property_filter;
 mod utils;
 
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use derive_more::{Deref, DerefMut, From};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use xayn_ai_bert::NormalizedEmbedding;
 use xayn_ai_coi::Coi;
 use xayn_web_api_db_ctrl::{tenant::Tenant, LegacyTenantInfo, Silo};
-use xayn_web_api_shared::{postgres as postgres_shared, request::TenantId};
+use xayn_web_api_shared::{elastic::ScoreMap, postgres as postgres_shared, request::TenantId};
 
-use self::property_filter::{IndexedPropertiesSchema, IndexedPropertiesSchemaUpdate};
+use self::property_filter::{
+    IndexedPropertiesSchema,
+    IndexedPropertiesSchemaUpdate,
+    IndexedPropertyType,
+};
 use crate::{
     app::SetupError,
     backoffice::IngestionConfig,
     frontoffice::filter::Filter,
     models::{
         self,
+        BoostRule,
+        BoostRuleId,
         DocumentForIngestion,
         DocumentId,
         DocumentPropertyId,
@@ -45,11 +54,20 @@ use crate::{
         DocumentTag,
         DocumentTags,
         ExcerptedDocument,
+        IdempotencyKey,
+        IndexedDocument,
+        InteractionCount,
+        InteractionType,
         PersonalizedDocument,
+        PinnedDocument,
+        PropertyValueCount,
+        Sha256Hash,
         SnippetForInteraction,
         SnippetId,
         SnippetOrDocumentId,
+        SourcePreferences,
         UserId,
+        UserInteraction,
     },
     tenants,
     Error,
@@ -66,6 +84,8 @@ pub(crate) struct KnnSearchParams<'a> {
     pub(super) include_properties: bool,
     pub(super) include_snippet: bool,
     pub(super) filter: Option<&'a Filter>,
+    /// Only documents with a `publication_date` property at or after this are considered.
+    pub(super) published_after: Option<DateTime<Utc>>,
     pub(super) with_raw_scores: bool,
 }
 
@@ -96,6 +116,8 @@ pub(crate) enum NormalizationFn {
     Identity,
     Normalize,
     NormalizeIfMaxGt1,
+    MinMax,
+    Softmax,
 }
 
 #[derive(Copy, Clone, Debug, Deserialize)]
@@ -180,6 +202,16 @@ pub(crate) trait Document {
         &self,
         ids: impl IntoIterator<IntoIter = impl Clone + ExactSizeIterator<Item = &DocumentId>>,
     ) -> Result<Warning<DocumentId>, Error>;
+
+    /// Lists the documents in the search index ordered by their ids.
+    ///
+    /// Only candidates are in the search index. Only documents with an id greater than `after` are
+    /// listed.
+    async fn list_indexed(
+        &self,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<Vec<IndexedDocument>, Error>;
 }
 
 #[async_trait(?Send)]
@@ -255,18 +287,62 @@ pub(crate) struct InteractionUpdateContext<'s, 'l> {
 
 #[async_trait(?Send)]
 pub(crate) trait Interaction {
+    /// Gets the documents a user interacted with from the most recent to the oldest one.
     async fn get(&self, user_id: &UserId) -> Result<Vec<DocumentId>, Error>;
 
+    /// Gets the stored interactions of a user from the most recent to the oldest one.
+    async fn get_history(
+        &self,
+        user_id: &UserId,
+        offset: usize,
+        count: usize,
+    ) -> Result<Vec<UserInteraction>, Error>;
+
+    /// Gets the most interacted candidate snippets of all users.
+    ///
+    /// The daily interaction counts are weighted by an exponential decay wrt their age with the
+    /// given half-life.
+    async fn get_trending(
+        &self,
+        time: DateTime<Utc>,
+        half_life: Duration,
+        count: usize,
+    ) -> Result<ScoreMap<SnippetId>, Error>;
+
+    /// Gets the daily interaction counts of all users with the snippets since the given day.
+    async fn get_interaction_counts(
+        &self,
+        ids: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = &SnippetId>>,
+        since: NaiveDate,
+    ) -> Result<Vec<InteractionCount>, Error>;
+
+    /// Deletes the daily interaction counts before the given day.
+    async fn prune_interaction_counts(&self, before: NaiveDate) -> Result<(), Error>;
+
     async fn user_seen(&self, id: &UserId, time: DateTime<Utc>) -> Result<(), Error>;
 
+    /// Updates the interests of a user for the interacted snippets.
+    ///
+    /// All updates are applied atomically, the snippets which don't exist are ignored and returned.
     async fn update_interactions(
         &self,
         user_id: &UserId,
-        interactions: Vec<SnippetOrDocumentId>,
+        interactions: Vec<(SnippetOrDocumentId, InteractionType)>,
         store_user_history: bool,
         time: DateTime<Utc>,
         update_logic: impl for<'a, 'b> FnMut(InteractionUpdateContext<'a, 'b>) -> Coi,
+    ) -> Result<Warning<SnippetId>, Error>;
+
+    /// Updates the interests of a user for snippets which were shown but not interacted with.
+    async fn update_impressions(
+        &self,
+        user_id: &UserId,
+        impressions: Vec<SnippetOrDocumentId>,
+        update_logic: impl for<'a, 'b> FnMut(&'a mut Vec<Coi>, &'b NormalizedEmbedding) -> Option<Coi>,
     ) -> Result<(), Error>;
+
+    /// Deletes the stored interactions of a user, the interests of the user are kept.
+    async fn delete_history(&self, user_id: &UserId) -> Result<(), Error>;
 }
 
 pub(crate) type TagWeights = HashMap<DocumentTag, usize>;
@@ -281,6 +357,74 @@ pub(crate) trait Tag {
         -> Result<Option<()>, Error>;
 }
 
+#[async_trait(?Send)]
+pub(crate) trait Dismissal {
+    /// Gets the documents which have been dismissed by a user at or after the given time.
+    async fn get(
+        &self,
+        user_id: &UserId,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<DocumentId>, Error>;
+
+    /// Dismisses the existing documents for a user.
+    ///
+    /// Dismissals of the user before the expiration time are removed beforehand.
+    async fn dismiss(
+        &self,
+        user_id: &UserId,
+        ids: &[DocumentId],
+        time: DateTime<Utc>,
+        expiration: Option<DateTime<Utc>>,
+    ) -> Result<(), Error>;
+}
+
+#[async_trait(?Send)]
+pub(crate) trait Pin {
+    /// Gets the pinned documents of a user ordered by position.
+    async fn get(&self, user_id: &UserId) -> Result<Vec<PinnedDocument>, Error>;
+
+    /// Replaces the pinned documents of a user.
+    ///
+    /// Returns the ids of the documents which don't exist, in which case nothing is changed.
+    async fn replace(
+        &self,
+        user_id: &UserId,
+        documents: &[PinnedDocument],
+    ) -> Result<Vec<DocumentId>, Error>;
+
+    /// Unpins all documents of a user.
+    async fn delete(&self, user_id: &UserId) -> Result<(), Error>;
+}
+
+#[async_trait(?Send)]
+pub(crate) trait BoostRules {
+    /// Gets all boost rules ordered by id, including the expired ones.
+    async fn get_all(&self) -> Result<Vec<BoostRule>, Error>;
+
+    /// Gets the boost rules which aren't expired at the given time.
+    async fn get_active(&self, time: DateTime<Utc>) -> Result<Vec<BoostRule>, Error>;
+
+    /// Inserts or replaces a boost rule.
+    async fn put(&self, rule: &BoostRule) -> Result<(), Error>;
+
+    /// Deletes a boost rule.
+    ///
+    /// Returns `None` if the rule doesn't exist.
+    async fn delete(&self, id: &BoostRuleId) -> Result<Option<()>, Error>;
+}
+
+#[async_trait(?Send)]
+pub(crate) trait Source {
+    /// Gets the source preferences of a user.
+    async fn get(&self, user_id: &UserId) -> Result<SourcePreferences, Error>;
+
+    /// Replaces the source preferences of a user.
+    async fn put(&self, user_id: &UserId, sources: &SourcePreferences) -> Result<(), Error>;
+
+    /// Deletes the source preferences of a user.
+    async fn delete(&self, user_id: &UserId) -> Result<(), Error>;
+}
+
 #[async_trait(?Send)]
 pub(crate) trait Size {
     /// Gets the size in bytes of the json value.
@@ -298,6 +442,138 @@ pub(crate) trait IndexedProperties {
     ) -> Result<IndexedPropertiesSchema, Error>;
 }
 
+#[async_trait(?Send)]
+pub(crate) trait Facets {
+    /// Counts the candidates per value of an indexed property.
+    ///
+    /// Returns up to `count` values ordered from the most to the least frequent one.
+    async fn count_by_property(
+        &self,
+        property_id: &DocumentPropertyId,
+        property_type: IndexedPropertyType,
+        count: usize,
+    ) -> Result<Vec<PropertyValueCount>, Error>;
+}
+
+#[async_trait(?Send)]
+pub(crate) trait Reembedding {
+    /// Gets up to `count` documents ordered by id which come after the given id.
+    async fn get_after(
+        &self,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<Vec<DocumentForIngestion>, Error>;
+
+    /// Extends the es mapping with the indexed properties schema of the tenant.
+    async fn copy_schema(&self, ingestion_config: &IngestionConfig) -> Result<(), Error>;
+
+    /// Stages the re-embedded documents.
+    ///
+    /// The documents are indexed in the es index of the storage, their snippets in postgres are
+    /// kept unchanged until the staged snippets are applied.
+    async fn stage(
+        &self,
+        documents: Vec<DocumentForIngestion>,
+    ) -> Result<Warning<DocumentId>, Error>;
+
+    /// Gets up to `count` documents with snippets which haven't been staged yet.
+    async fn get_unstaged(&self, count: usize) -> Result<Vec<DocumentForIngestion>, Error>;
+
+    /// Discards all staged snippets.
+    async fn discard(&self) -> Result<(), Error>;
+
+    /// Applies the staged snippets and switches the tenant to the es index of the storage.
+    ///
+    /// Both happen in the same transaction, which fails if any snippet hasn't been staged. The
+    /// interests are in the embedding space of the previous model and can't be compared with the
+    /// re-embedded snippets anymore, they are deleted in the same transaction and the number of
+    /// deleted interests is returned.
+    async fn apply(&self) -> Result<u64, Error>;
+}
+
+/// The result of comparing a page of documents in postgres with elastic.
+pub(crate) struct ConsistencyReport {
+    /// Documents which are out of sync until elastic confirms their update.
+    pub(crate) pending: Vec<DocumentId>,
+    /// Candidates of the page which are missing in elastic.
+    pub(crate) missing: Vec<DocumentId>,
+    /// Non-candidates of the page which are still in elastic.
+    pub(crate) unexpected: Vec<DocumentId>,
+    /// The last document of the page, if any.
+    pub(crate) last: Option<DocumentId>,
+    /// Number of candidates in postgres.
+    pub(crate) candidates: usize,
+    /// Number of documents in elastic.
+    pub(crate) indexed: usize,
+}
+
+#[async_trait(?Send)]
+pub(crate) trait Consistency {
+    /// Compares up to `count` documents in postgres which come after the given id with elastic.
+    ///
+    /// The pending documents are reported up to `count` from the oldest to the most recent update.
+    async fn check(
+        &self,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<ConsistencyReport, Error>;
+
+    /// Gets the given documents which are pending and the ones which are in elastic.
+    async fn get_sync_state(
+        &self,
+        ids: &[&DocumentId],
+    ) -> Result<(HashSet<DocumentId>, HashSet<DocumentId>), Error>;
+
+    /// Marks the documents as pending until they are synced with elastic.
+    async fn mark_pending(&self, ids: impl IntoIterator<Item = &DocumentId>) -> Result<(), Error>;
+
+    /// Syncs up to `count` pending documents which have been updated before the given time with
+    /// elastic and reports the ones which failed again.
+    async fn apply_pending(
+        &self,
+        before: DateTime<Utc>,
+        count: usize,
+    ) -> Result<Warning<DocumentId>, Error>;
+}
+
+/// The state of a request with an idempotency key.
+pub(crate) enum IdempotencyState {
+    /// The key has been reserved for a new request.
+    Reserved,
+    /// A previous request with the key is still in progress.
+    InProgress,
+    /// A previous request with the key had a different body.
+    Mismatch,
+    /// A previous request with the key has been completed with the response status and body.
+    Completed { status: u16, body: Vec<u8> },
+}
+
+#[async_trait(?Send)]
+pub(crate) trait Idempotency {
+    /// Reserves the key for a new request or gets the state of a previous request with it.
+    ///
+    /// The reservation is leased until the given time, afterwards an unfinished request with the
+    /// same body can take it over. Keys which have been created before the expiration time are
+    /// treated as unused.
+    async fn reserve(
+        &self,
+        key: &IdempotencyKey,
+        fingerprint: &Sha256Hash,
+        time: DateTime<Utc>,
+        lease: DateTime<Utc>,
+        expiration: DateTime<Utc>,
+    ) -> Result<IdempotencyState, Error>;
+
+    /// Stores the response status and body of the request with the key.
+    async fn complete(&self, key: &IdempotencyKey, status: u16, body: &[u8]) -> Result<(), Error>;
+
+    /// Releases the key of a failed request, so that it can be retried.
+    async fn release(&self, key: &IdempotencyKey) -> Result<(), Error>;
+
+    /// Deletes the keys which have been created before the expiration time.
+    async fn prune(&self, expiration: DateTime<Utc>) -> Result<(), Error>;
+}
+
 #[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(default)]
 #[cfg_attr(test, serde(deny_unknown_fields))]
@@ -331,10 +607,14 @@ impl Storage {
 // FIXME: long term this should be run by the control plane,
 //        in a different binary/lambda or similar before we
 //        start updating the instances.
+/// Initializes the silo and reports the tenants with mismatching embedding dims.
+///
+/// The tenant which is re-embedded, if any, is skipped in the report.
 pub(crate) async fn initialize_silo(
     config: &Config,
     tenant_config: &tenants::Config,
     embedding_sizes: HashMap<String, usize>,
+    reembedded: Option<&TenantId>,
 ) -> Result<(Silo, Option<TenantId>), SetupError> {
     let silo = Silo::new(
         config.postgres.clone(),
@@ -354,6 +634,8 @@ pub(crate) async fn initialize_silo(
     silo.admin_as_mt_user_hack().await?;
 
     let legacy_tenant = silo.initialize().await?;
+    silo.verify_embedding_dims(reembedded).await?;
+
     Ok((silo, legacy_tenant))
 }
 
@@ -378,6 +660,18 @@ impl StorageBuilder {
         })
     }
 
+    /// Builds the storage for the tenant but with a different es index.
+    pub(crate) async fn build_for_index(
+        &self,
+        tenant_id: TenantId,
+        es_index_name: String,
+    ) -> Result<Storage, Error> {
+        let mut storage = self.build_for(tenant_id).await?;
+        storage.tenant.es_index_name = es_index_name;
+        storage.elastic = self.elastic.build_for(&storage.tenant);
+        Ok(storage)
+    }
+
     pub(crate) async fn close(&self) {
         self.postgres.close().await;
     }
@@ -385,4 +679,8 @@ impl StorageBuilder {
     pub(crate) fn legacy_tenant(&self) -> Option<&TenantId> {
         self.postgres.legacy_tenant()
     }
+
+    pub(crate) fn postgres_pool_metrics(&self) -> postgres::PoolMetrics {
+        self.postgres.pool_metrics()
+    }
 }
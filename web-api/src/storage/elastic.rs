@@ -23,7 +23,7 @@ use itertools::Itertools;
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 use xayn_ai_bert::NormalizedEmbedding;
 pub(crate) use xayn_web_api_shared::elastic::{BulkInstruction, Config};
 use xayn_web_api_shared::{
@@ -40,6 +40,7 @@ use super::{
 };
 use crate::{
     app::SetupError,
+    frontoffice::filter::Filter,
     models::{
         self,
         DocumentContent,
@@ -57,11 +58,13 @@ use crate::{
         merge_scores_weighted,
         normalize_scores,
         normalize_scores_if_max_gt_1,
+        normalize_scores_min_max,
+        normalize_scores_softmax,
         rrf,
         take_highest_n_scores,
         DEFAULT_RRF_K,
     },
-    storage::{property_filter::IndexedPropertyType, KnnSearchParams, Warning},
+    storage::{property_filter::IndexedPropertyType, Exclusions, KnnSearchParams, Warning},
     Error,
 };
 
@@ -75,6 +78,43 @@ impl Client {
     pub(super) async fn get_by_embedding<'a>(
         &self,
         params: KnnSearchParams<'a>,
+    ) -> Result<(ScoreMap<SnippetId>, RawScores), Error> {
+        // Hint: any further excluded documents are filtered from the search results instead, which
+        // bounds the request size for users with a long history. The excluded documents are
+        // expected to be ordered by relevance, e.g. the most recent interactions first.
+        let max_excluded_documents = self.max_excluded_documents();
+        if params.excluded.documents.len() <= max_excluded_documents {
+            return self.search_by_embedding(params).await;
+        }
+
+        let (excluded, post_excluded) = params.excluded.documents.split_at(max_excluded_documents);
+        let excluded = Exclusions {
+            documents: excluded.to_vec(),
+            snippets: params.excluded.snippets.clone(),
+        };
+        let post_excluded = post_excluded.iter().collect::<HashSet<_>>();
+        let count = params.count;
+        // Hint: fetch additional documents to compensate for the filtered ones
+        let extended_count = count + post_excluded.len().min(count);
+        let (scores, raw_scores) = self
+            .search_by_embedding(KnnSearchParams {
+                excluded: &excluded,
+                count: extended_count,
+                num_candidates: params.num_candidates.max(extended_count),
+                ..params
+            })
+            .await?;
+        let scores = scores
+            .into_iter()
+            .filter(|(id, _)| !post_excluded.contains(id.document_id()))
+            .collect();
+
+        Ok((take_highest_n_scores(count, scores), raw_scores))
+    }
+
+    async fn search_by_embedding<'a>(
+        &self,
+        params: KnnSearchParams<'a>,
     ) -> Result<(ScoreMap<SnippetId>, RawScores), Error> {
         match params.strategy {
             SearchStrategy::Knn => self.knn_search(params).await,
@@ -193,10 +233,10 @@ impl Client {
         &self,
         documents: impl IntoIterator<Item = &models::DocumentForIngestion>,
     ) -> Result<Warning<DocumentId>, Error> {
-        let mut snippets = documents
+        let (ids, snippets): (Vec<_>, Vec<_>) = documents
             .into_iter()
             .flat_map(|document| {
-                document.snippets.iter().enumerate().flat_map(
+                document.snippets.iter().enumerate().map(
                     |(idx, DocumentContent { snippet, embedding })| {
                         #[allow(clippy::cast_possible_truncation)]
                         let id = SnippetId::new(document.id.clone(), idx as _);
@@ -210,18 +250,28 @@ impl Client {
                             parent: id.document_id(),
                         });
 
-                        [header, data]
+                        (&document.id, [header, data])
                     },
                 )
             })
-            .peekable();
+            .unzip();
 
-        if snippets.peek().is_none() {
+        if snippets.is_empty() {
             return Ok(Warning::default());
         }
 
-        let response = self.bulk_request(snippets).await?;
-        Ok(response.failed_documents(false, "created").into())
+        let mut failed = Vec::new();
+        for chunk in self.chunked_bulk_request(snippets).await? {
+            match chunk.response {
+                Ok(response) => failed.extend(response.failed_documents(false, "created")),
+                Err(error) => {
+                    error!({ %error }, "Elastic failed to bulk index a chunk of documents.");
+                    failed.extend(ids[chunk.operations].iter().copied().cloned());
+                }
+            }
+        }
+
+        Ok(failed.into_iter().unique().collect())
     }
 
     pub(super) async fn delete_by_parents(
@@ -255,6 +305,165 @@ impl Client {
         Ok(())
     }
 
+    /// Gets the ids of the given documents which are stored in elastic.
+    pub(super) async fn get_indexed(
+        &self,
+        ids: &[&DocumentId],
+    ) -> Result<HashSet<DocumentId>, Error> {
+        #[derive(Deserialize)]
+        struct Response {
+            aggregations: Aggregations,
+        }
+
+        #[derive(Deserialize)]
+        struct Aggregations {
+            parents: Terms,
+        }
+
+        #[derive(Deserialize)]
+        struct Terms {
+            buckets: Vec<Bucket>,
+        }
+
+        #[derive(Deserialize)]
+        struct Bucket {
+            key: DocumentId,
+        }
+
+        if ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let body = json!({
+            "size": 0,
+            "track_total_hits": false,
+            "query": {
+                "terms": {
+                    "parent": ids,
+                }
+            },
+            "aggs": {
+                "parents": {
+                    "terms": {
+                        "field": "parent",
+                        "size": ids.len(),
+                    }
+                }
+            }
+        });
+        let url = self.create_url(["_search"], []);
+        let response = self
+            .query_with_json::<_, Response>(Method::POST, url, Some(body))
+            .await?;
+
+        Ok(response
+            .aggregations
+            .parents
+            .buckets
+            .into_iter()
+            .map(|bucket| bucket.key)
+            .collect())
+    }
+
+    /// Lists up to `count` documents which are stored in elastic ordered by their ids.
+    ///
+    /// The snippets are collapsed by their parent document, whose id is the stable sort key of the
+    /// `search_after` cursor. Only documents with an id greater than `after` are listed.
+    pub(super) async fn list_documents(
+        &self,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<Vec<models::IndexedDocument>, Error> {
+        #[derive(Deserialize)]
+        struct Response {
+            hits: Hits,
+        }
+
+        #[derive(Deserialize)]
+        struct Hits {
+            hits: Vec<Hit>,
+        }
+
+        #[derive(Deserialize)]
+        struct Hit {
+            #[serde(rename = "_source")]
+            source: Source,
+            sort: (DocumentId,),
+        }
+
+        #[derive(Deserialize)]
+        struct Source {
+            #[serde(default)]
+            properties: DocumentProperties,
+        }
+
+        // https://www.elastic.co/guide/en/elasticsearch/reference/current/paginate-search-results.html#search-after
+        let mut body = json!({
+            "size": count,
+            "track_total_hits": false,
+            "_source": ["properties"],
+            "collapse": { "field": "parent" },
+            "sort": [{ "parent": "asc" }],
+        });
+        if let Some(after) = after {
+            body["search_after"] = json!([after]);
+        }
+        let url = self.create_url(["_search"], []);
+        let response = self
+            .query_with_json::<_, Response>(Method::POST, url, Some(body))
+            .await?;
+
+        Ok(response
+            .hits
+            .hits
+            .into_iter()
+            .map(|hit| models::IndexedDocument {
+                id: hit.sort.0,
+                properties: hit.source.properties,
+            })
+            .collect())
+    }
+
+    /// Counts the documents which are stored in elastic.
+    ///
+    /// The count is exact up to 40k documents and approximate above.
+    pub(super) async fn count_indexed(&self) -> Result<usize, Error> {
+        #[derive(Deserialize)]
+        struct Response {
+            aggregations: Aggregations,
+        }
+
+        #[derive(Deserialize)]
+        struct Aggregations {
+            documents: Cardinality,
+        }
+
+        #[derive(Deserialize)]
+        struct Cardinality {
+            value: usize,
+        }
+
+        // https://www.elastic.co/guide/en/elasticsearch/reference/current/search-aggregations-metrics-cardinality-aggregation.html
+        let body = json!({
+            "size": 0,
+            "track_total_hits": false,
+            "aggs": {
+                "documents": {
+                    "cardinality": {
+                        "field": "parent",
+                        "precision_threshold": 40_000,
+                    }
+                }
+            }
+        });
+        let url = self.create_url(["_search"], []);
+        let response = self
+            .query_with_json::<_, Response>(Method::POST, url, Some(body))
+            .await?;
+
+        Ok(response.aggregations.documents.value)
+    }
+
     pub(super) async fn insert_document_properties(
         &self,
         document_id: &DocumentId,
@@ -417,6 +626,81 @@ impl Client {
         Ok(())
     }
 
+    pub(super) async fn count_by_property(
+        &self,
+        property_id: &DocumentPropertyId,
+        property_type: IndexedPropertyType,
+        count: usize,
+    ) -> Result<Vec<models::PropertyValueCount>, Error> {
+        #[derive(Deserialize)]
+        struct Response {
+            aggregations: Aggregations,
+        }
+
+        #[derive(Deserialize)]
+        struct Aggregations {
+            values: Terms,
+        }
+
+        #[derive(Deserialize)]
+        struct Terms {
+            buckets: Vec<Bucket>,
+        }
+
+        #[derive(Deserialize)]
+        struct Bucket {
+            key: Value,
+            documents: Cardinality,
+        }
+
+        #[derive(Deserialize)]
+        struct Cardinality {
+            value: usize,
+        }
+
+        // https://www.elastic.co/guide/en/elasticsearch/reference/current/search-aggregations-bucket-terms-aggregation.html
+        let body = json!({
+            "size": 0,
+            "track_total_hits": false,
+            "aggs": {
+                "values": {
+                    "terms": {
+                        "field": format!("properties.{property_id}"),
+                        "size": count,
+                        "order": { "documents": "desc" }
+                    },
+                    "aggs": {
+                        // snippets of split documents are counted once per document
+                        "documents": { "cardinality": { "field": "parent" } }
+                    }
+                }
+            }
+        });
+        let url = self.create_url(["_search"], []);
+        let response = self
+            .query_with_json::<_, Response>(Method::POST, url, Some(body))
+            .await?;
+
+        Ok(response
+            .aggregations
+            .values
+            .buckets
+            .into_iter()
+            .map(|bucket| {
+                let value = if property_type == IndexedPropertyType::Boolean {
+                    // Hint: es represents the boolean keys as 1 and 0
+                    Value::Bool(bucket.key == json!(1))
+                } else {
+                    bucket.key
+                };
+                models::PropertyValueCount {
+                    value,
+                    count: bucket.documents.value,
+                }
+            })
+            .collect())
+    }
+
     async fn update_indices(&self, config: &IndexUpdateConfig) -> Result<(), Error> {
         let wait_for_completion = match config.method {
             IndexUpdateMethod::Background => false,
@@ -513,8 +797,9 @@ struct KnnSearchParts {
 
 impl KnnSearchParams<'_> {
     fn create_common_knn_search_parts(&self) -> KnnSearchParts {
+        let filter = Filter::insert_published_after(self.filter.cloned(), self.published_after);
         let Ok(Value::Object(inner_filter)) =
-            serde_json::to_value(Clauses::new(self.filter, self.excluded))
+            serde_json::to_value(Clauses::new(filter.as_ref(), self.excluded))
         else {
             unreachable!(/* filter clauses is valid json object */);
         };
@@ -554,6 +839,8 @@ impl NormalizationFn {
             NormalizationFn::Identity => Box::new(identity),
             NormalizationFn::Normalize => Box::new(normalize_scores),
             NormalizationFn::NormalizeIfMaxGt1 => Box::new(normalize_scores_if_max_gt_1),
+            NormalizationFn::MinMax => Box::new(normalize_scores_min_max),
+            NormalizationFn::Softmax => Box::new(normalize_scores_softmax),
         }
     }
 }
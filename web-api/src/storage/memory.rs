@@ -21,11 +21,12 @@ use std::{
     collections::{HashMap, HashSet},
     fmt,
     mem,
+    time::Duration,
 };
 
 use async_trait::async_trait;
 use bincode::{deserialize, serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use derive_more::{AsRef, Deref};
 use instant_distance::{Builder as HnswBuilder, HnswMap, Point, Search};
 use itertools::Itertools;
@@ -34,6 +35,7 @@ use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Seri
 use tokio::sync::RwLock;
 use xayn_ai_bert::NormalizedEmbedding;
 use xayn_ai_coi::Coi;
+use xayn_web_api_shared::elastic::ScoreMap;
 
 use super::{Document as _, InteractionUpdateContext, TagWeights};
 use crate::{
@@ -52,6 +54,9 @@ use crate::{
         DocumentTag,
         DocumentTags,
         ExcerptedDocument,
+        IndexedDocument,
+        InteractionCount,
+        InteractionType,
         PersonalizedDocument,
         PreprocessingStep,
         Sha256Hash,
@@ -59,6 +64,7 @@ use crate::{
         SnippetId,
         SnippetOrDocumentId,
         UserId,
+        UserInteraction,
     },
     storage::{self, KnnSearchParams, Warning},
 };
@@ -232,6 +238,7 @@ pub(crate) struct Storage {
     interests: RwLock<HashMap<UserId, Vec<Coi>>>,
     #[allow(clippy::type_complexity)]
     interactions: RwLock<HashMap<UserId, HashSet<(DocumentId, DateTime<Utc>)>>>,
+    interaction_counts: RwLock<HashMap<DocumentId, HashMap<NaiveDate, u32>>>,
     users: RwLock<HashMap<UserId, DateTime<Utc>>>,
     tags: RwLock<HashMap<UserId, HashMap<DocumentTag, usize>>>,
 }
@@ -332,7 +339,7 @@ impl storage::Document for Storage {
         &self,
         params: KnnSearchParams<'a>,
     ) -> Result<Vec<PersonalizedDocument>, Error> {
-        if params.filter.is_some() {
+        if params.filter.is_some() || params.published_after.is_some() {
             unimplemented!(/* we don't need it for memory.rs */);
         }
 
@@ -406,12 +413,14 @@ impl storage::Document for Storage {
     ) -> Result<Warning<DocumentId>, Error> {
         let mut documents = self.documents.write().await;
         let mut interactions = self.interactions.write().await;
+        let mut interaction_counts = self.interaction_counts.write().await;
 
         let mut ids = ids.into_iter().collect::<HashSet<_>>();
         interactions.retain(|_, interactions| {
             interactions.retain(|(id, _)| !ids.contains(id));
             !interactions.is_empty()
         });
+        interaction_counts.retain(|id, _| !ids.contains(id));
         documents.0.retain(|id, _| !ids.contains(id));
         let mut embeddings = mem::take(&mut documents.1).into_heads().map;
         embeddings.retain(|id, _| !ids.remove(id));
@@ -419,6 +428,31 @@ impl storage::Document for Storage {
 
         Ok(ids.into_iter().cloned().collect())
     }
+
+    async fn list_indexed(
+        &self,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<Vec<IndexedDocument>, Error> {
+        let documents = self
+            .documents
+            .read()
+            .await
+            .0
+            .iter()
+            .filter(|(id, document)| {
+                document.is_candidate && after.map_or(true, |after| *id > after)
+            })
+            .sorted_by(|(id1, _), (id2, _)| id1.cmp(id2))
+            .take(count)
+            .map(|(id, document)| IndexedDocument {
+                id: id.clone(),
+                properties: document.properties.clone(),
+            })
+            .collect();
+
+        Ok(documents)
+    }
 }
 
 #[async_trait(?Send)]
@@ -616,7 +650,11 @@ impl storage::Interaction for Storage {
             .map(|interactions| {
                 interactions
                     .iter()
+                    .sorted_by(|(id1, time1), (id2, time2)| {
+                        time2.cmp(time1).then_with(|| id1.cmp(id2))
+                    })
                     .map(|(document_id, _)| document_id.clone())
+                    .unique()
                     .collect()
             })
             .unwrap_or_default();
@@ -624,6 +662,115 @@ impl storage::Interaction for Storage {
         Ok(document_ids)
     }
 
+    async fn get_history(
+        &self,
+        user_id: &UserId,
+        offset: usize,
+        count: usize,
+    ) -> Result<Vec<UserInteraction>, Error> {
+        let history = self
+            .interactions
+            .read()
+            .await
+            .get(user_id)
+            .map(|interactions| {
+                interactions
+                    .iter()
+                    .sorted_by(|(id1, time1), (id2, time2)| {
+                        time2.cmp(time1).then_with(|| id1.cmp(id2))
+                    })
+                    .skip(offset)
+                    .take(count)
+                    // Hint: the interaction types aren't tracked here
+                    .map(|(document_id, time)| UserInteraction {
+                        snippet_id: SnippetId::new(document_id.clone(), 0),
+                        interaction_type: InteractionType::default(),
+                        time: *time,
+                        coi_id: None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(history)
+    }
+
+    async fn get_trending(
+        &self,
+        time: DateTime<Utc>,
+        half_life: Duration,
+        count: usize,
+    ) -> Result<ScoreMap<SnippetId>, Error> {
+        let documents = self.documents.read().await;
+        let interaction_counts = self.interaction_counts.read().await;
+        let mut scores = HashMap::<_, f32>::new();
+        for (document_id, counts) in &*interaction_counts {
+            if documents
+                .0
+                .get(document_id)
+                .map_or(false, |document| document.is_candidate)
+            {
+                let half_life = half_life.as_secs_f64() / (24. * 60. * 60.);
+                #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+                let score = counts
+                    .iter()
+                    .map(|(day, count)| {
+                        let age = (time.date_naive() - *day).num_days() as f64;
+                        f64::from(*count) * 0.5_f64.powf(age / half_life)
+                    })
+                    .sum::<f64>() as f32;
+                scores.insert(document_id, score);
+            }
+        }
+        let trending = scores
+            .into_iter()
+            .sorted_by(|(id1, score1), (id2, score2)| {
+                score2.total_cmp(score1).then_with(|| id1.cmp(id2))
+            })
+            .take(count)
+            .map(|(document_id, score)| (SnippetId::new(document_id.clone(), 0), score))
+            .collect();
+
+        Ok(trending)
+    }
+
+    async fn get_interaction_counts(
+        &self,
+        ids: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = &SnippetId>>,
+        since: NaiveDate,
+    ) -> Result<Vec<InteractionCount>, Error> {
+        let interaction_counts = self.interaction_counts.read().await;
+        let counts = ids
+            .into_iter()
+            .filter_map(|id| {
+                interaction_counts
+                    .get(id.document_id())
+                    .map(|counts| (id, counts))
+            })
+            .flat_map(|(id, counts)| {
+                counts
+                    .iter()
+                    .filter(|(day, _)| **day >= since)
+                    .map(|(day, count)| InteractionCount {
+                        snippet_id: id.clone(),
+                        day: *day,
+                        count: *count,
+                    })
+            })
+            .collect();
+
+        Ok(counts)
+    }
+
+    async fn prune_interaction_counts(&self, before: NaiveDate) -> Result<(), Error> {
+        self.interaction_counts.write().await.retain(|_, counts| {
+            counts.retain(|day, _| *day >= before);
+            !counts.is_empty()
+        });
+
+        Ok(())
+    }
+
     async fn user_seen(&self, id: &UserId, time: DateTime<Utc>) -> Result<(), Error> {
         self.users.write().await.insert(id.clone(), time);
 
@@ -633,15 +780,15 @@ impl storage::Interaction for Storage {
     async fn update_interactions(
         &self,
         user_id: &UserId,
-        interactions: Vec<SnippetOrDocumentId>,
+        interactions: Vec<(SnippetOrDocumentId, InteractionType)>,
         store_user_history: bool,
         time: DateTime<Utc>,
         mut update_logic: impl for<'a, 'b> FnMut(InteractionUpdateContext<'a, 'b>) -> Coi,
-    ) -> Result<(), Error> {
+    ) -> Result<Warning<SnippetId>, Error> {
         // TODO[pmk/ET-4851] properly support interactions to multi-snippet document
         let interactions = interactions
             .into_iter()
-            .map(|id| match id {
+            .map(|(id, _)| match id {
                 SnippetOrDocumentId::SnippetId(id) => id,
                 SnippetOrDocumentId::DocumentId(id) => SnippetId::new(id, 0),
             })
@@ -650,9 +797,14 @@ impl storage::Interaction for Storage {
         let documents = self
             .get_snippets_for_interaction(interactions.iter())
             .await?;
+        let not_found = interactions
+            .into_iter()
+            .filter(|id| documents.iter().all(|document| &document.id != id))
+            .collect();
         let mut interests = self.interests.write().await;
         let mut interactions = self.interactions.write().await;
         let interactions = interactions.entry(user_id.clone()).or_default();
+        let mut interaction_counts = self.interaction_counts.write().await;
         let mut tags = self.tags.write().await;
         let tags = tags.entry(user_id.clone()).or_default();
 
@@ -674,6 +826,11 @@ impl storage::Interaction for Storage {
             if store_user_history {
                 interactions.insert((document.id.document_id().clone(), updated.stats.last_view));
             }
+            *interaction_counts
+                .entry(document.id.document_id().clone())
+                .or_default()
+                .entry(time.date_naive())
+                .or_default() += 1;
         }
 
         for (tag, diff) in tag_weight_diff {
@@ -684,6 +841,40 @@ impl storage::Interaction for Storage {
             }
         }
 
+        Ok(not_found)
+    }
+
+    async fn update_impressions(
+        &self,
+        user_id: &UserId,
+        impressions: Vec<SnippetOrDocumentId>,
+        mut update_logic: impl for<'a, 'b> FnMut(
+            &'a mut Vec<Coi>,
+            &'b NormalizedEmbedding,
+        ) -> Option<Coi>,
+    ) -> Result<(), Error> {
+        let impressions = impressions
+            .into_iter()
+            .map(|id| match id {
+                SnippetOrDocumentId::SnippetId(id) => id,
+                SnippetOrDocumentId::DocumentId(id) => SnippetId::new(id, 0),
+            })
+            .collect_vec();
+        let snippets = self
+            .get_snippets_for_interaction(impressions.iter())
+            .await?;
+        let mut interests = self.interests.write().await;
+        let interests = interests.entry(user_id.clone()).or_default();
+        for snippet in &snippets {
+            update_logic(interests, &snippet.embedding);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_history(&self, user_id: &UserId) -> Result<(), Error> {
+        self.interactions.write().await.remove(user_id);
+
         Ok(())
     }
 }
@@ -714,19 +905,23 @@ impl Storage {
             &*self.documents.read().await,
             &*self.interests.read().await,
             &*self.interactions.read().await,
+            &*self.interaction_counts.read().await,
             &*self.users.read().await,
             &*self.tags.read().await,
         ))
     }
 
     pub(crate) fn deserialize(bytes: &[u8]) -> Result<Self, bincode::Error> {
-        deserialize(bytes).map(|(documents, interests, interactions, users, tags)| Self {
-            documents: RwLock::new(documents),
-            interests: RwLock::new(interests),
-            interactions: RwLock::new(interactions),
-            users: RwLock::new(users),
-            tags: RwLock::new(tags),
-        })
+        deserialize(bytes).map(
+            |(documents, interests, interactions, interaction_counts, users, tags)| Self {
+                documents: RwLock::new(documents),
+                interests: RwLock::new(interests),
+                interactions: RwLock::new(interactions),
+                interaction_counts: RwLock::new(interaction_counts),
+                users: RwLock::new(users),
+                tags: RwLock::new(tags),
+            },
+        )
     }
 }
 
@@ -786,6 +981,7 @@ mod tests {
                 include_properties: false,
                 include_snippet: false,
                 filter: None,
+                published_after: None,
                 with_raw_scores: false,
             },
         )
@@ -810,6 +1006,7 @@ mod tests {
                 include_properties: false,
                 include_snippet: false,
                 filter: None,
+                published_after: None,
                 with_raw_scores: false,
             },
         )
@@ -849,8 +1046,9 @@ mod tests {
         storage::Interaction::update_interactions(
             &storage,
             &user_id,
-            vec![SnippetOrDocumentId::DocumentId(
-                doc_id.document_id().clone(),
+            vec![(
+                SnippetOrDocumentId::DocumentId(doc_id.document_id().clone()),
+                InteractionType::default(),
             )],
             true,
             Utc::now(),
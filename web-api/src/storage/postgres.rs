@@ -21,15 +21,15 @@ use std::{
 };
 
 use async_trait::async_trait;
-pub(crate) use client::{Database, DatabaseBuilder};
+pub(crate) use client::{Database, DatabaseBuilder, PoolMetrics};
 use either::Either;
-use futures_util::{future, TryStreamExt};
+use futures_util::{future, Future, TryStreamExt};
 use itertools::Itertools;
 use serde_json::Value;
 use sqlx::{
     postgres::PgRow,
     types::{
-        chrono::{DateTime, Utc},
+        chrono::{DateTime, NaiveDate, Utc},
         Json,
     },
     Executor,
@@ -38,11 +38,13 @@ use sqlx::{
     QueryBuilder,
     Row,
     Transaction,
+    Type,
 };
 use tracing::{info, instrument};
 use xayn_ai_bert::NormalizedEmbedding;
 use xayn_ai_coi::{Coi, CoiId, CoiStats};
-use xayn_web_api_shared::elastic::ScoreMap;
+use xayn_web_api_db_ctrl::tenant::Tenant;
+use xayn_web_api_shared::{elastic::ScoreMap, request::TenantId};
 
 use super::{
     property_filter::{
@@ -52,12 +54,17 @@ use super::{
         IndexedPropertyType,
     },
     utils::{Chunks, IterAsTuple, SqlBitCastU32},
+    ConsistencyReport,
+    IdempotencyState,
     InteractionUpdateContext,
     TagWeights,
 };
 use crate::{
     backoffice::IngestionConfig,
+    error::common::InternalError,
     models::{
+        BoostRule,
+        BoostRuleId,
         DocumentContent,
         DocumentDevData,
         DocumentForIngestion,
@@ -66,16 +73,25 @@ use crate::{
         DocumentProperty,
         DocumentPropertyId,
         DocumentSnippet,
+        DocumentSource,
         DocumentTag,
         DocumentTags,
         ExcerptedDocument,
+        IdempotencyKey,
+        IndexedDocument,
+        InteractionCount,
+        InteractionType,
         PersonalizedDocument,
+        PinnedDocument,
+        PropertyValueCount,
         RawScores,
         Sha256Hash,
         SnippetForInteraction,
         SnippetId,
         SnippetOrDocumentId,
+        SourcePreferences,
         UserId,
+        UserInteraction,
     },
     storage::{self, utils::SqlxPushTupleExt, KnnSearchParams, Storage, Warning},
     Error,
@@ -105,7 +121,7 @@ impl Database {
     pub(super) async fn insert_documents(
         &self,
         documents: &[DocumentForIngestion],
-    ) -> Result<(), Error> {
+    ) -> Result<DateTime<Utc>, Error> {
         let mut tx = self.begin().await?;
 
         let mut builder = QueryBuilder::new(
@@ -193,6 +209,125 @@ impl Database {
                 .await?;
         }
 
+        // Hint: documents stay in the outbox until elastic is confirmed to be in sync
+        let marked =
+            Self::add_to_outbox(&mut tx, documents.iter().map(|document| &document.id)).await?;
+
+        tx.commit().await?;
+
+        Ok(marked)
+    }
+
+    /// Marks the documents as out of sync with elastic until they are removed from the outbox.
+    ///
+    /// Returns the time of the marking, which is the start of the transaction.
+    async fn add_to_outbox(
+        tx: &mut Transaction<'_, Postgres>,
+        ids: impl IntoIterator<Item = &DocumentId>,
+    ) -> Result<DateTime<Utc>, Error> {
+        let mut builder = QueryBuilder::new("INSERT INTO elastic_outbox (document_id) ");
+        let mut chunks = Chunks::new(Self::BIND_LIMIT, ids);
+        while let Some(chunk) = chunks.next() {
+            builder
+                .reset()
+                .push_values(chunk, |mut builder, id| {
+                    builder.push_bind(id);
+                })
+                .push(" ON CONFLICT (document_id) DO UPDATE SET time_stamp = now();")
+                .build()
+                .persistent(false)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let (marked,) = sqlx::query_as::<_, (DateTime<Utc>,)>("SELECT now();")
+            .fetch_one(&mut *tx)
+            .await?;
+
+        Ok(marked)
+    }
+
+    async fn get_pending(
+        tx: &mut Transaction<'_, Postgres>,
+        before: DateTime<Utc>,
+        count: usize,
+    ) -> Result<Vec<DocumentId>, Error> {
+        sqlx::query_as::<_, (DocumentId,)>(
+            "SELECT document_id
+            FROM elastic_outbox
+            WHERE time_stamp <= $1
+            ORDER BY time_stamp, document_id
+            LIMIT $2;",
+        )
+        .bind(before)
+        .bind(i64::try_from(count).unwrap_or(i64::MAX))
+        .fetch(tx)
+        .map_ok(|(id,)| id)
+        .try_collect()
+        .await
+        .map_err(Into::into)
+    }
+
+    async fn get_pending_of(
+        tx: &mut Transaction<'_, Postgres>,
+        ids: impl IntoIterator<Item = &DocumentId>,
+    ) -> Result<HashSet<DocumentId>, Error> {
+        let mut builder =
+            QueryBuilder::new("SELECT document_id FROM elastic_outbox WHERE document_id IN ");
+        let mut chunks = IterAsTuple::chunks(Self::BIND_LIMIT, ids);
+        let mut pending = HashSet::new();
+        while let Some(ids) = chunks.next() {
+            pending.extend(
+                builder
+                    .reset()
+                    .push_tuple(ids)
+                    .push(";")
+                    .build()
+                    .persistent(false)
+                    .try_map(|row| DocumentId::from_row(&row))
+                    .fetch_all(&mut *tx)
+                    .await?,
+            );
+        }
+
+        Ok(pending)
+    }
+
+    async fn count_candidates(tx: &mut Transaction<'_, Postgres>) -> Result<usize, Error> {
+        let (count,) = sqlx::query_as::<_, (i64,)>(
+            "SELECT COUNT(*)
+            FROM document
+            WHERE is_candidate;",
+        )
+        .fetch_one(tx)
+        .await?;
+
+        Ok(usize::try_from(count).unwrap_or(usize::MAX))
+    }
+
+    /// Removes the documents which have been marked until the given time from the outbox.
+    ///
+    /// Documents which have been marked again in the meantime stay pending.
+    async fn remove_from_outbox(
+        &self,
+        ids: impl IntoIterator<Item = &DocumentId>,
+        marked: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let mut tx = self.begin().await?;
+
+        let mut builder = QueryBuilder::new("DELETE FROM elastic_outbox WHERE time_stamp <= ");
+        let mut chunks = IterAsTuple::chunks(Self::BIND_LIMIT - 1, ids);
+        while let Some(ids) = chunks.next() {
+            builder
+                .reset()
+                .push_bind(marked)
+                .push(" AND document_id IN ")
+                .push_tuple(ids)
+                .build()
+                .persistent(false)
+                .execute(&mut tx)
+                .await?;
+        }
+
         tx.commit().await?;
 
         Ok(())
@@ -417,6 +552,35 @@ impl Database {
         Ok(documents)
     }
 
+    async fn list_excerpted(
+        tx: &mut Transaction<'_, Postgres>,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<Vec<ExcerptedDocument>, Error> {
+        sqlx::query(
+            "SELECT document_id, original_sha256, preprocessing_step, properties, tags, is_candidate
+            FROM document
+            WHERE $1::TEXT IS NULL OR document_id > $1
+            ORDER BY document_id
+            LIMIT $2;",
+        )
+        .bind(after)
+        .bind(i64::try_from(count).unwrap_or(i64::MAX))
+        .try_map(|row: PgRow| {
+            Ok(ExcerptedDocument {
+                id: row.try_get("document_id")?,
+                original_sha256: row.try_get("original_sha256")?,
+                preprocessing_step: row.try_get("preprocessing_step")?,
+                properties: row.try_get::<Json<_>, _>("properties")?.0,
+                tags: row.try_get("tags")?,
+                is_candidate: row.try_get("is_candidate")?,
+            })
+        })
+        .fetch_all(tx)
+        .await
+        .map_err(Into::into)
+    }
+
     async fn get_embedding(
         tx: &mut Transaction<'_, Postgres>,
         id: &SnippetId,
@@ -429,6 +593,211 @@ impl Database {
             .map_err(Into::into)
     }
 
+    async fn get_documents_after(
+        &self,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<Vec<DocumentForIngestion>, Error> {
+        let mut tx = self.begin().await?;
+        let documents = Self::list_excerpted(&mut tx, after, count).await?;
+        let documents = Self::load_snippets(&mut tx, documents).await?;
+        tx.commit().await?;
+
+        Ok(documents)
+    }
+
+    /// Gets up to `count` documents with snippets which haven't been staged yet.
+    async fn get_unstaged_documents(
+        &self,
+        count: usize,
+    ) -> Result<Vec<DocumentForIngestion>, Error> {
+        let mut tx = self.begin().await?;
+        let ids = sqlx::query_as::<_, (DocumentId,)>(
+            "SELECT DISTINCT s.document_id
+            FROM snippet s
+            LEFT JOIN reembedded_snippet r
+            ON s.document_id = r.document_id AND s.sub_id = r.sub_id
+            WHERE r.document_id IS NULL
+            ORDER BY s.document_id
+            LIMIT $1;",
+        )
+        .bind(i64::try_from(count).unwrap_or(i64::MAX))
+        .fetch(&mut tx)
+        .map_ok(|(id,)| id)
+        .try_collect::<Vec<_>>()
+        .await?;
+        let documents = Self::get_excerpted(&mut tx, &ids).await?;
+        let documents = Self::load_snippets(&mut tx, documents).await?;
+        tx.commit().await?;
+
+        Ok(documents)
+    }
+
+    /// Loads the snippets of the excerpted documents.
+    async fn load_snippets(
+        tx: &mut Transaction<'_, Postgres>,
+        documents: Vec<ExcerptedDocument>,
+    ) -> Result<Vec<DocumentForIngestion>, Error> {
+        let mut builder = QueryBuilder::new(
+            "SELECT document_id, sub_id, snippet, embedding
+            FROM snippet
+            WHERE document_id IN ",
+        );
+        let mut snippets = HashMap::<_, Vec<_>>::new();
+        let mut chunks = IterAsTuple::chunks(
+            Self::BIND_LIMIT,
+            documents.iter().map(|document| &document.id),
+        );
+        while let Some(ids) = chunks.next() {
+            builder
+                .reset()
+                .push_tuple(ids)
+                .build_query_as::<SqlSnippet>()
+                .fetch(&mut *tx)
+                .try_for_each(|snippet| {
+                    snippets.entry(snippet.document_id).or_default().push((
+                        u32::from(snippet.sub_id),
+                        DocumentContent {
+                            snippet: snippet.snippet,
+                            embedding: snippet.embedding,
+                        },
+                    ));
+                    future::ok(())
+                })
+                .await?;
+        }
+
+        let documents = documents
+            .into_iter()
+            .map(|document| DocumentForIngestion {
+                snippets: snippets
+                    .remove(&document.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .sorted_by_key(|(sub_id, _)| *sub_id)
+                    .map(|(_, content)| content)
+                    .collect(),
+                id: document.id,
+                original_sha256: document.original_sha256,
+                preprocessing_step: document.preprocessing_step,
+                properties: document.properties,
+                tags: document.tags,
+                is_candidate: document.is_candidate,
+            })
+            .collect();
+
+        Ok(documents)
+    }
+
+    async fn stage_snippets(&self, documents: &[DocumentForIngestion]) -> Result<(), Error> {
+        let mut tx = self.begin().await?;
+
+        let mut snippets = Chunks::new(
+            Self::BIND_LIMIT / 4,
+            documents.iter().flat_map(|document| {
+                document.snippets.iter().enumerate().map(
+                    |(sub_id, DocumentContent { snippet, embedding })| {
+                        (
+                            &document.id,
+                            #[allow(clippy::cast_possible_truncation)]
+                            SqlBitCastU32::from(sub_id as u32),
+                            snippet,
+                            embedding,
+                        )
+                    },
+                )
+            }),
+        );
+        let mut builder = QueryBuilder::new(
+            "INSERT INTO reembedded_snippet (
+                document_id,
+                sub_id,
+                snippet,
+                embedding
+            ) ",
+        );
+        while let Some(chunk) = snippets.next() {
+            builder
+                .reset()
+                .push_values(
+                    chunk,
+                    |mut builder, (document_id, sub_id, snippet, embedding)| {
+                        builder
+                            .push_bind(document_id)
+                            .push_bind(sub_id)
+                            .push_bind(snippet)
+                            .push_bind(embedding);
+                    },
+                )
+                .push(
+                    " ON CONFLICT (document_id, sub_id) DO UPDATE SET
+                    snippet = EXCLUDED.snippet,
+                    embedding = EXCLUDED.embedding;",
+                )
+                .build()
+                .execute(&mut tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn discard_staged_snippets(&self) -> Result<(), Error> {
+        sqlx::query("DELETE FROM reembedded_snippet;")
+            .execute(self)
+            .await?;
+        Ok(())
+    }
+
+    async fn apply_staged_snippets(
+        &self,
+        tenant_id: &TenantId,
+        es_index_name: String,
+    ) -> Result<u64, Error> {
+        let mut tx = self.begin().await?;
+
+        // Hint: snippets of documents which have been ingested since the last staging would be
+        // missing in the new index and keep their embeddings of the previous model
+        let (unstaged,) = sqlx::query_as::<_, (i64,)>(
+            "SELECT COUNT(*)
+            FROM snippet s
+            LEFT JOIN reembedded_snippet r
+            ON s.document_id = r.document_id AND s.sub_id = r.sub_id
+            WHERE r.document_id IS NULL;",
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        if unstaged > 0 {
+            return Err(InternalError::from_message(format!(
+                "{unstaged} snippets haven't been re-embedded"
+            ))
+            .into());
+        }
+
+        sqlx::query(
+            "UPDATE snippet s
+            SET snippet = r.snippet, embedding = r.embedding
+            FROM reembedded_snippet r
+            WHERE s.document_id = r.document_id AND s.sub_id = r.sub_id;",
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query("DELETE FROM reembedded_snippet;")
+            .execute(&mut tx)
+            .await?;
+        let deleted_cois = sqlx::query("DELETE FROM center_of_interest;")
+            .execute(&mut tx)
+            .await?
+            .rows_affected();
+        // Hint: the management schema isn't accessible with the role of the tenant
+        sqlx::query("RESET ROLE;").execute(&mut tx).await?;
+        Tenant::change_es_index(&mut tx, tenant_id, es_index_name).await?;
+
+        tx.commit().await?;
+        Ok(deleted_cois)
+    }
+
     async fn set_candidates(
         &self,
         ids: impl IntoIterator<Item = &DocumentId>,
@@ -437,6 +806,7 @@ impl Database {
             HashSet<DocumentId>,
             Vec<DocumentForIngestion>,
             Warning<DocumentId>,
+            DateTime<Utc>,
         ),
         Error,
     > {
@@ -482,6 +852,13 @@ impl Database {
         let needs_ingestion =
             Self::set_is_candidate_and_return_for_ingestion(&mut tx, ingestable.iter().copied())
                 .await?;
+        let marked = Self::add_to_outbox(
+            &mut tx,
+            removed
+                .iter()
+                .chain(needs_ingestion.iter().map(|document| &document.id)),
+        )
+        .await?;
 
         tx.commit().await?;
 
@@ -494,7 +871,7 @@ impl Database {
             })
             .unwrap_or_default();
 
-        Ok((removed, needs_ingestion, failed))
+        Ok((removed, needs_ingestion, failed, marked))
     }
 
     async fn set_is_candidate_and_return_for_ingestion(
@@ -574,7 +951,14 @@ impl Database {
     async fn add_candidates(
         &self,
         ids: impl IntoIterator<Item = &DocumentId>,
-    ) -> Result<(Vec<DocumentForIngestion>, Warning<DocumentId>), Error> {
+    ) -> Result<
+        (
+            Vec<DocumentForIngestion>,
+            Warning<DocumentId>,
+            DateTime<Utc>,
+        ),
+        Error,
+    > {
         let mut tx = self.begin().await?;
 
         let mut ingestable = ids.into_iter().collect::<HashSet<_>>();
@@ -603,6 +987,9 @@ impl Database {
         let needs_ingestion =
             Self::set_is_candidate_and_return_for_ingestion(&mut tx, ingestable.iter().copied())
                 .await?;
+        let marked =
+            Self::add_to_outbox(&mut tx, needs_ingestion.iter().map(|document| &document.id))
+                .await?;
 
         tx.commit().await?;
 
@@ -615,13 +1002,13 @@ impl Database {
             })
             .unwrap_or_default();
 
-        Ok((needs_ingestion, failed))
+        Ok((needs_ingestion, failed, marked))
     }
 
     async fn remove_candidates(
         &self,
         ids: impl IntoIterator<Item = &DocumentId>,
-    ) -> Result<(Vec<DocumentId>, Warning<DocumentId>), Error> {
+    ) -> Result<(Vec<DocumentId>, Warning<DocumentId>, DateTime<Utc>), Error> {
         let mut tx = self.begin().await?;
 
         let mut removable = ids.into_iter().collect::<HashSet<_>>();
@@ -666,6 +1053,7 @@ impl Database {
                     .await?,
             );
         }
+        let marked = Self::add_to_outbox(&mut tx, &removed).await?;
 
         tx.commit().await?;
 
@@ -678,7 +1066,7 @@ impl Database {
             })
             .unwrap_or_default();
 
-        Ok((removed, failed))
+        Ok((removed, failed, marked))
     }
 
     async fn acquire_user_coi_lock(
@@ -784,33 +1172,89 @@ impl Database {
         Ok(())
     }
 
+    async fn update_coi_last_views(
+        tx: &mut Transaction<'_, Postgres>,
+        cois: &HashMap<CoiId, Coi>,
+    ) -> Result<(), Error> {
+        for coi in cois.values() {
+            sqlx::query(
+                "UPDATE center_of_interest
+                SET last_view = $1
+                WHERE coi_id = $2;",
+            )
+            .bind(coi.stats.last_view)
+            .bind(coi.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Ok(())
+    }
+
     async fn upsert_interactions(
         tx: &mut Transaction<'_, Postgres>,
         user_id: &UserId,
         time: DateTime<Utc>,
-        interactions: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = &SnippetId>>,
+        interactions: &HashMap<&SnippetId, (InteractionType, CoiId)>,
     ) -> Result<(), Error> {
-        let mut interactions = Chunks::new(Database::BIND_LIMIT / 4, interactions);
+        let mut interactions = Chunks::new(Database::BIND_LIMIT / 6, interactions);
 
         //FIXME micro benchmark and chunking+persist abstraction
         let persist = interactions.element_count() < 10;
 
         let mut builder = QueryBuilder::new(
-            "INSERT INTO interaction (document_id, sub_id, user_id, time_stamp) ",
+            "INSERT INTO interaction
+                (document_id, sub_id, user_id, time_stamp, interaction_type, coi_id) ",
         );
         while let Some(chunk) = interactions.next() {
             builder
                 .reset()
-                .push_values(chunk, |mut builder, snippet_id| {
+                .push_values(
+                    chunk,
+                    |mut builder, (snippet_id, (interaction_type, coi_id))| {
+                        builder
+                            .push_bind(snippet_id.document_id())
+                            .push_bind(SqlBitCastU32::from(snippet_id.sub_id()))
+                            .push_bind(user_id)
+                            .push_bind(time)
+                            .push_bind(*interaction_type)
+                            .push_bind(*coi_id);
+                    },
+                )
+                .push(" ON CONFLICT DO NOTHING;")
+                .build()
+                .persistent(persist)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn upsert_interaction_counts(
+        tx: &mut Transaction<'_, Postgres>,
+        day: NaiveDate,
+        counts: &HashMap<&SnippetId, i32>,
+    ) -> Result<(), Error> {
+        // sorted to acquire the row locks in a consistent order for concurrent updates
+        let mut counts = Chunks::new(Database::BIND_LIMIT / 4, counts.iter().sorted());
+        let mut builder =
+            QueryBuilder::new("INSERT INTO interaction_count (document_id, sub_id, day, count) ");
+        while let Some(chunk) = counts.next() {
+            builder
+                .reset()
+                .push_values(chunk, |mut builder, (snippet_id, count)| {
                     builder
                         .push_bind(snippet_id.document_id())
                         .push_bind(SqlBitCastU32::from(snippet_id.sub_id()))
-                        .push_bind(user_id)
-                        .push_bind(time);
+                        .push_bind(day)
+                        .push_bind(*count);
                 })
-                .push(" ON CONFLICT DO NOTHING;")
+                .push(
+                    " ON CONFLICT (document_id, sub_id, day) DO UPDATE
+                    SET count = interaction_count.count + EXCLUDED.count;",
+                )
                 .build()
-                .persistent(persist)
                 .execute(&mut *tx)
                 .await?;
         }
@@ -962,7 +1406,7 @@ impl storage::Document for Storage {
         &self,
         documents: Vec<DocumentForIngestion>,
     ) -> Result<Warning<DocumentId>, Error> {
-        self.postgres.insert_documents(&documents).await?;
+        let marked = self.postgres.insert_documents(&documents).await?;
         let (candidates, noncandidates) = documents
             .into_iter()
             .partition_map::<Vec<_>, Vec<_>, _, _, _>(|document| {
@@ -974,6 +1418,16 @@ impl storage::Document for Storage {
             });
         let failed_documents = self.elastic.upsert_documents(&candidates).await?;
         self.elastic.delete_by_parents(&noncandidates).await?;
+        self.postgres
+            .remove_from_outbox(
+                candidates
+                    .iter()
+                    .map(|document| &document.id)
+                    .filter(|id| !failed_documents.contains(id))
+                    .chain(&noncandidates),
+                marked,
+            )
+            .await?;
 
         Ok(failed_documents)
     }
@@ -987,7 +1441,35 @@ impl storage::Document for Storage {
 
         Ok(failed_documents)
     }
-}
+
+    async fn list_indexed(
+        &self,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<Vec<IndexedDocument>, Error> {
+        self.elastic.list_documents(after, count).await
+    }
+}
+
+impl Storage {
+    /// Awaits the elastic update of a candidate which has been added to the outbox.
+    ///
+    /// The candidate is removed from the outbox once the update succeeded, otherwise it stays
+    /// pending until it is reconciled.
+    async fn sync_candidate(
+        &self,
+        id: &DocumentId,
+        marked: DateTime<Utc>,
+        update: impl Future<Output = Result<Option<()>, Error>>,
+    ) -> Result<Option<()>, Error> {
+        let updated = update.await?;
+        if updated.is_some() {
+            self.postgres.remove_from_outbox([id], marked).await?;
+        }
+
+        Ok(updated)
+    }
+}
 
 #[async_trait(?Send)]
 impl storage::DocumentCandidate for Storage {
@@ -1002,9 +1484,20 @@ impl storage::DocumentCandidate for Storage {
         &self,
         ids: impl IntoIterator<Item = &DocumentId>,
     ) -> Result<Warning<DocumentId>, Error> {
-        let (removed, ingested, mut failed) = self.postgres.set_candidates(ids).await?;
+        let (removed, ingested, mut failed, marked) = self.postgres.set_candidates(ids).await?;
         self.elastic.delete_by_parents(&removed).await?;
-        failed.extend(self.elastic.freshly_insert_documents(&ingested).await?);
+        let failed_documents = self.elastic.freshly_insert_documents(&ingested).await?;
+        self.postgres
+            .remove_from_outbox(
+                ingested
+                    .iter()
+                    .map(|document| &document.id)
+                    .filter(|id| !failed_documents.contains(id))
+                    .chain(&removed),
+                marked,
+            )
+            .await?;
+        failed.extend(failed_documents);
 
         Ok(failed)
     }
@@ -1013,8 +1506,18 @@ impl storage::DocumentCandidate for Storage {
         &self,
         ids: impl IntoIterator<Item = &DocumentId>,
     ) -> Result<Warning<DocumentId>, Error> {
-        let (ingested, mut failed) = self.postgres.add_candidates(ids).await?;
-        failed.extend(self.elastic.freshly_insert_documents(&ingested).await?);
+        let (ingested, mut failed, marked) = self.postgres.add_candidates(ids).await?;
+        let failed_documents = self.elastic.freshly_insert_documents(&ingested).await?;
+        self.postgres
+            .remove_from_outbox(
+                ingested
+                    .iter()
+                    .map(|document| &document.id)
+                    .filter(|id| !failed_documents.contains(id)),
+                marked,
+            )
+            .await?;
+        failed.extend(failed_documents);
 
         Ok(failed)
     }
@@ -1023,8 +1526,10 @@ impl storage::DocumentCandidate for Storage {
         &self,
         ids: impl IntoIterator<Item = &DocumentId>,
     ) -> Result<Warning<DocumentId>, Error> {
-        let (removed, failed) = self.postgres.remove_candidates(ids).await?;
+        let (removed, failed, marked) = self.postgres.remove_candidates(ids).await?;
         self.elastic.delete_by_parents(&removed).await?;
+        self.postgres.remove_from_outbox(&removed, marked).await?;
+
         Ok(failed)
     }
 }
@@ -1071,21 +1576,25 @@ impl storage::DocumentProperties for Storage {
         .bind(id)
         .fetch_optional(&mut tx)
         .await?;
-        let inserted = if let Some((is_candidate,)) = inserted {
-            if is_candidate {
-                self.elastic
-                    .insert_document_properties(id, properties)
-                    .await?
-            } else {
-                Some(())
-            }
-        } else {
-            None
+        let marked = match inserted {
+            Some((true,)) => Some(Database::add_to_outbox(&mut tx, [id]).await?),
+            _ => None,
         };
 
         tx.commit().await?;
 
-        Ok(inserted)
+        match (inserted, marked) {
+            (Some(_), Some(marked)) => {
+                self.sync_candidate(
+                    id,
+                    marked,
+                    self.elastic.insert_document_properties(id, properties),
+                )
+                .await
+            }
+            (Some(_), None) => Ok(Some(())),
+            (None, _) => Ok(None),
+        }
     }
 
     async fn delete(&self, id: &DocumentId) -> Result<Option<()>, Error> {
@@ -1105,19 +1614,21 @@ impl storage::DocumentProperties for Storage {
         .bind(id)
         .fetch_optional(&mut tx)
         .await?;
-        let deleted = if let Some((is_candidate,)) = deleted {
-            if is_candidate {
-                self.elastic.delete_document_properties(id).await?
-            } else {
-                Some(())
-            }
-        } else {
-            None
+        let marked = match deleted {
+            Some((true,)) => Some(Database::add_to_outbox(&mut tx, [id]).await?),
+            _ => None,
         };
 
         tx.commit().await?;
 
-        Ok(deleted)
+        match (deleted, marked) {
+            (Some(_), Some(marked)) => {
+                self.sync_candidate(id, marked, self.elastic.delete_document_properties(id))
+                    .await
+            }
+            (Some(_), None) => Ok(Some(())),
+            (None, _) => Ok(None),
+        }
     }
 }
 
@@ -1176,21 +1687,26 @@ impl storage::DocumentProperty for Storage {
         .bind(document_id)
         .fetch_optional(&mut tx)
         .await?;
-        let inserted = if let Some((is_candidate,)) = inserted {
-            if is_candidate {
-                self.elastic
-                    .insert_document_property(document_id, property_id, property)
-                    .await?
-            } else {
-                Some(())
-            }
-        } else {
-            None
+        let marked = match inserted {
+            Some((true,)) => Some(Database::add_to_outbox(&mut tx, [document_id]).await?),
+            _ => None,
         };
 
         tx.commit().await?;
 
-        Ok(inserted)
+        match (inserted, marked) {
+            (Some(_), Some(marked)) => {
+                self.sync_candidate(
+                    document_id,
+                    marked,
+                    self.elastic
+                        .insert_document_property(document_id, property_id, property),
+                )
+                .await
+            }
+            (Some(_), None) => Ok(Some(())),
+            (None, _) => Ok(None),
+        }
     }
 
     async fn delete(
@@ -1215,43 +1731,49 @@ impl storage::DocumentProperty for Storage {
         .bind(document_id)
         .fetch_optional(&mut tx)
         .await?;
-        let deleted = if let Some((is_candidate,)) = deleted {
-            if is_candidate {
-                self.elastic
-                    .delete_document_property(document_id, property_id)
-                    .await?
-                    .map(|()| Some(()))
-            } else {
-                Some(Some(()))
-            }
-        } else {
-            Database::document_exists(&mut tx, document_id)
-                .await?
-                .then_some(None)
+        let marked = match deleted {
+            Some((true,)) => Some(Database::add_to_outbox(&mut tx, [document_id]).await?),
+            _ => None,
         };
+        let exists = deleted.is_some() || Database::document_exists(&mut tx, document_id).await?;
 
         tx.commit().await?;
 
-        Ok(deleted)
+        match (deleted, marked) {
+            (Some(_), Some(marked)) => Ok(self
+                .sync_candidate(
+                    document_id,
+                    marked,
+                    self.elastic
+                        .delete_document_property(document_id, property_id),
+                )
+                .await?
+                .map(|()| Some(()))),
+            (Some(_), None) => Ok(Some(Some(()))),
+            (None, _) => Ok(exists.then_some(None)),
+        }
     }
 }
 
 #[async_trait]
 impl storage::Interest for Storage {
     async fn get(&self, user_id: &UserId) -> Result<Vec<Coi>, Error> {
-        Database::get_user_interests(&self.postgres, user_id).await
+        Database::get_user_interests(&self.postgres.read_only(), user_id).await
     }
 }
 
 #[async_trait(?Send)]
 impl storage::Interaction for Storage {
     async fn get(&self, user_id: &UserId) -> Result<Vec<DocumentId>, Error> {
-        let mut tx = self.postgres.begin().await?;
+        let postgres = self.postgres.read_only();
+        let mut tx = postgres.begin().await?;
 
         let documents = sqlx::query_as::<_, (DocumentId,)>(
-            "SELECT DISTINCT document_id
+            "SELECT document_id
             FROM interaction
-            WHERE user_id = $1;",
+            WHERE user_id = $1
+            GROUP BY document_id
+            ORDER BY MAX(time_stamp) DESC;",
         )
         .bind(user_id)
         .fetch(&mut tx)
@@ -1264,6 +1786,123 @@ impl storage::Interaction for Storage {
         Ok(documents)
     }
 
+    async fn get_history(
+        &self,
+        user_id: &UserId,
+        offset: usize,
+        count: usize,
+    ) -> Result<Vec<UserInteraction>, Error> {
+        let history = sqlx::query(
+            "SELECT document_id, sub_id, interaction_type, time_stamp, coi_id
+            FROM interaction
+            WHERE user_id = $1
+            ORDER BY time_stamp DESC, document_id, sub_id
+            OFFSET $2
+            LIMIT $3;",
+        )
+        .bind(user_id)
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .bind(i64::try_from(count).unwrap_or(i64::MAX))
+        .try_map(|row: PgRow| {
+            let document_id = row.try_get("document_id")?;
+            let sub_id = u32::from(row.try_get::<SqlBitCastU32, _>("sub_id")?);
+            Ok(UserInteraction {
+                snippet_id: SnippetId::new(document_id, sub_id),
+                interaction_type: row.try_get("interaction_type")?,
+                time: row.try_get("time_stamp")?,
+                coi_id: row.try_get("coi_id")?,
+            })
+        })
+        .fetch_all(&self.postgres.read_only())
+        .await?;
+
+        Ok(history)
+    }
+
+    async fn get_trending(
+        &self,
+        time: DateTime<Utc>,
+        half_life: Duration,
+        count: usize,
+    ) -> Result<ScoreMap<SnippetId>, Error> {
+        let trending = sqlx::query(
+            "SELECT
+                c.document_id,
+                c.sub_id,
+                SUM(c.count * EXP(LN(0.5) * ($1 - c.day) / $2))::REAL AS score
+            FROM interaction_count c JOIN document d USING (document_id)
+            WHERE d.is_candidate
+            GROUP BY c.document_id, c.sub_id
+            ORDER BY score DESC, c.document_id, c.sub_id
+            LIMIT $3;",
+        )
+        .bind(time.date_naive())
+        .bind(half_life.as_secs_f64() / (24. * 60. * 60.))
+        .bind(i64::try_from(count).unwrap_or(i64::MAX))
+        .try_map(|row: PgRow| {
+            let document_id = row.try_get("document_id")?;
+            let sub_id = u32::from(row.try_get::<SqlBitCastU32, _>("sub_id")?);
+            Ok((SnippetId::new(document_id, sub_id), row.try_get("score")?))
+        })
+        .fetch_all(&self.postgres)
+        .await?
+        .into_iter()
+        .collect();
+
+        Ok(trending)
+    }
+
+    async fn get_interaction_counts(
+        &self,
+        ids: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = &SnippetId>>,
+        since: NaiveDate,
+    ) -> Result<Vec<InteractionCount>, Error> {
+        let mut builder = QueryBuilder::new(
+            "SELECT document_id, sub_id, day, count
+            FROM interaction_count
+            WHERE day >= ",
+        );
+        let mut chunks = IterAsTuple::chunks(
+            (Database::BIND_LIMIT - 1) / 2,
+            ids.into_iter()
+                .map(|id| (id.document_id(), SqlBitCastU32::from(id.sub_id()))),
+        );
+        let mut counts = Vec::with_capacity(chunks.element_count());
+        while let Some(ids) = chunks.next() {
+            counts.extend(
+                builder
+                    .reset()
+                    .push_bind(since)
+                    .push(" AND (document_id, sub_id) IN ")
+                    .push_nested_tuple(ids)
+                    .build()
+                    .try_map(|row: PgRow| {
+                        let document_id = row.try_get("document_id")?;
+                        let sub_id = u32::from(row.try_get::<SqlBitCastU32, _>("sub_id")?);
+                        Ok(InteractionCount {
+                            snippet_id: SnippetId::new(document_id, sub_id),
+                            day: row.try_get("day")?,
+                            count: u32::try_from(row.try_get::<i32, _>("count")?)
+                                .unwrap_or_default(),
+                        })
+                    })
+                    .fetch_all(&self.postgres)
+                    .await?,
+            );
+        }
+
+        Ok(counts)
+    }
+
+    async fn prune_interaction_counts(&self, before: NaiveDate) -> Result<(), Error> {
+        sqlx::query("DELETE FROM interaction_count WHERE day < $1;")
+            .bind(before)
+            .execute(&self.postgres)
+            .await?;
+
+        Ok(())
+    }
+
     async fn user_seen(&self, id: &UserId, time: DateTime<Utc>) -> Result<(), Error> {
         sqlx::query(
             "INSERT INTO users (user_id, last_seen)
@@ -1282,24 +1921,26 @@ impl storage::Interaction for Storage {
     async fn update_interactions(
         &self,
         user_id: &UserId,
-        interactions: Vec<SnippetOrDocumentId>,
+        interactions: Vec<(SnippetOrDocumentId, InteractionType)>,
         store_user_history: bool,
         time: DateTime<Utc>,
         mut update_logic: impl for<'a, 'b> FnMut(InteractionUpdateContext<'a, 'b>) -> Coi,
-    ) -> Result<(), Error> {
+    ) -> Result<Warning<SnippetId>, Error> {
         let mut tx = self.postgres.begin().await?;
         Database::acquire_user_coi_lock(&mut tx, user_id).await?;
 
         // TODO[pmk/ET-4851] proper support for interaction with multi-snippet documents
         let interactions = interactions
             .into_iter()
-            .map(|id| match id {
-                SnippetOrDocumentId::SnippetId(id) => id,
-                SnippetOrDocumentId::DocumentId(id) => SnippetId::new(id, 0),
+            .map(|(id, interaction_type)| match id {
+                SnippetOrDocumentId::SnippetId(id) => (id, interaction_type),
+                SnippetOrDocumentId::DocumentId(id) => (SnippetId::new(id, 0), interaction_type),
             })
             .collect_vec();
 
-        let snippets = Database::get_snippets_for_interaction(&mut tx, interactions.iter()).await?;
+        let snippets =
+            Database::get_snippets_for_interaction(&mut tx, interactions.iter().map(|(id, _)| id))
+                .await?;
         let snippet_map = snippets
             .iter()
             .map(|document| (&document.id, document))
@@ -1312,30 +1953,80 @@ impl storage::Interaction for Storage {
 
         let mut interests = Database::get_user_interests(&mut tx, user_id).await?;
         let mut updates = HashMap::new();
-        for document_id in interactions {
+        let mut interaction_cois = HashMap::with_capacity(snippet_map.len());
+        let mut interaction_counts = HashMap::with_capacity(snippet_map.len());
+        let mut not_found = Warning::default();
+        for (document_id, interaction_type) in interactions {
             if let Some(document) = snippet_map.get(&document_id) {
+                *interaction_counts.entry(&document.id).or_default() += 1;
                 let updated_coi = update_logic(InteractionUpdateContext {
                     document,
                     tag_weight_diff: &mut tag_weight_diff,
                     interests: &mut interests,
                     time,
                 });
+                interaction_cois.insert(&document.id, (interaction_type, updated_coi.id));
                 // We might update the same coi min `interests` multiple times,
                 // if we do we only want to keep the latest update.
                 updates.insert(updated_coi.id, updated_coi);
             } else {
                 info!(?document_id, "interacted snippet doesn't exist");
+                not_found.push(document_id);
             }
         }
 
         Database::upsert_cois(&mut tx, user_id, time, &updates).await?;
         if store_user_history {
-            Database::upsert_interactions(&mut tx, user_id, time, snippet_map.keys().copied())
-                .await?;
+            Database::upsert_interactions(&mut tx, user_id, time, &interaction_cois).await?;
         }
         Database::upsert_tag_weights(&mut tx, user_id, &tag_weight_diff).await?;
+        Database::upsert_interaction_counts(&mut tx, time.date_naive(), &interaction_counts)
+            .await?;
 
         tx.commit().await?;
+        Ok(not_found)
+    }
+
+    async fn update_impressions(
+        &self,
+        user_id: &UserId,
+        impressions: Vec<SnippetOrDocumentId>,
+        mut update_logic: impl for<'a, 'b> FnMut(
+            &'a mut Vec<Coi>,
+            &'b NormalizedEmbedding,
+        ) -> Option<Coi>,
+    ) -> Result<(), Error> {
+        let mut tx = self.postgres.begin().await?;
+        Database::acquire_user_coi_lock(&mut tx, user_id).await?;
+
+        let impressions = impressions
+            .into_iter()
+            .map(|id| match id {
+                SnippetOrDocumentId::SnippetId(id) => id,
+                SnippetOrDocumentId::DocumentId(id) => SnippetId::new(id, 0),
+            })
+            .collect_vec();
+        let snippets = Database::get_snippets_for_interaction(&mut tx, impressions.iter()).await?;
+
+        let mut interests = Database::get_user_interests(&mut tx, user_id).await?;
+        let mut updates = HashMap::new();
+        for snippet in &snippets {
+            if let Some(updated_coi) = update_logic(&mut interests, &snippet.embedding) {
+                updates.insert(updated_coi.id, updated_coi);
+            }
+        }
+        Database::update_coi_last_views(&mut tx, &updates).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_history(&self, user_id: &UserId) -> Result<(), Error> {
+        sqlx::query("DELETE FROM interaction WHERE user_id = $1;")
+            .bind(user_id)
+            .execute(&self.postgres)
+            .await?;
+
         Ok(())
     }
 }
@@ -1350,7 +2041,8 @@ struct QueriedWeightedTag {
 #[async_trait]
 impl storage::Tag for Storage {
     async fn get(&self, user_id: &UserId) -> Result<TagWeights, Error> {
-        let mut tx = self.postgres.begin().await?;
+        let postgres = self.postgres.read_only();
+        let mut tx = postgres.begin().await?;
 
         let tags = sqlx::query_as::<_, QueriedWeightedTag>(
             "SELECT tag, weight
@@ -1421,6 +2113,419 @@ impl storage::Size for Storage {
     }
 }
 
+#[async_trait(?Send)]
+impl storage::Dismissal for Storage {
+    async fn get(
+        &self,
+        user_id: &UserId,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<DocumentId>, Error> {
+        let documents = sqlx::query_as::<_, (DocumentId,)>(
+            "SELECT document_id
+            FROM dismissed_document
+            WHERE user_id = $1 AND ($2::TIMESTAMPTZ IS NULL OR time_stamp >= $2);",
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(documents.into_iter().map(|(id,)| id).collect())
+    }
+
+    async fn dismiss(
+        &self,
+        user_id: &UserId,
+        ids: &[DocumentId],
+        time: DateTime<Utc>,
+        expiration: Option<DateTime<Utc>>,
+    ) -> Result<(), Error> {
+        let mut tx = self.postgres.begin().await?;
+
+        if let Some(expiration) = expiration {
+            sqlx::query(
+                "DELETE FROM dismissed_document
+                WHERE user_id = $1 AND time_stamp < $2;",
+            )
+            .bind(user_id)
+            .bind(expiration)
+            .execute(&mut tx)
+            .await?;
+        }
+
+        let mut builder = QueryBuilder::new(
+            "INSERT INTO dismissed_document (user_id, document_id, time_stamp) SELECT ",
+        );
+        let mut chunks = IterAsTuple::chunks(Database::BIND_LIMIT - 2, ids);
+        while let Some(ids) = chunks.next() {
+            builder
+                .reset()
+                .push_bind(user_id)
+                .push(", document_id, ")
+                .push_bind(time)
+                .push(" FROM document WHERE document_id IN ")
+                .push_tuple(ids)
+                .push(
+                    " ON CONFLICT (user_id, document_id) DO UPDATE SET
+                    time_stamp = EXCLUDED.time_stamp;",
+                )
+                .build()
+                .persistent(false)
+                .execute(&mut tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl storage::Pin for Storage {
+    async fn get(&self, user_id: &UserId) -> Result<Vec<PinnedDocument>, Error> {
+        let documents = sqlx::query_as::<_, (DocumentId, i32)>(
+            "SELECT document_id, position
+            FROM pinned_document
+            WHERE user_id = $1
+            ORDER BY position, document_id;",
+        )
+        .bind(user_id)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(documents
+            .into_iter()
+            .map(
+                #[allow(clippy::cast_sign_loss)] // the position is checked to be non-negative
+                |(id, position)| PinnedDocument {
+                    id,
+                    position: position as usize,
+                },
+            )
+            .collect())
+    }
+
+    async fn replace(
+        &self,
+        user_id: &UserId,
+        documents: &[PinnedDocument],
+    ) -> Result<Vec<DocumentId>, Error> {
+        let mut tx = self.postgres.begin().await?;
+
+        sqlx::query("DELETE FROM pinned_document WHERE user_id = $1;")
+            .bind(user_id)
+            .execute(&mut tx)
+            .await?;
+
+        if !documents.is_empty() {
+            let pinned = QueryBuilder::new(
+                "INSERT INTO pinned_document (user_id, document_id, position) SELECT ",
+            )
+            .push_bind(user_id)
+            .push(", p.document_id, p.position FROM (")
+            .push_values(documents, |mut builder, document| {
+                builder
+                    .push_bind(&document.id)
+                    .push_bind(i32::try_from(document.position).unwrap_or(i32::MAX));
+            })
+            .push(
+                ") AS p (document_id, position) JOIN document USING (document_id)
+                RETURNING document_id;",
+            )
+            .build_query_as::<(DocumentId,)>()
+            .persistent(false)
+            .fetch_all(&mut tx)
+            .await?;
+
+            let missing = documents
+                .iter()
+                .filter(|document| pinned.iter().all(|(id,)| id != &document.id))
+                .map(|document| document.id.clone())
+                .collect_vec();
+            if !missing.is_empty() {
+                // Hint: the transaction is rolled back on drop
+                return Ok(missing);
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(Vec::new())
+    }
+
+    async fn delete(&self, user_id: &UserId) -> Result<(), Error> {
+        sqlx::query("DELETE FROM pinned_document WHERE user_id = $1;")
+            .bind(user_id)
+            .execute(&self.postgres)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Type)]
+#[sqlx(type_name = "source_preference", rename_all = "snake_case")]
+enum SourcePreference {
+    Trusted,
+    Excluded,
+}
+
+type BoostRuleRow = (
+    BoostRuleId,
+    DocumentPropertyId,
+    Json<DocumentProperty>,
+    f32,
+    Option<DateTime<Utc>>,
+);
+
+fn boost_rule_from_row(
+    (id, property_id, Json(value), weight, expires_at): BoostRuleRow,
+) -> BoostRule {
+    BoostRule {
+        id,
+        property_id,
+        value,
+        weight,
+        expires_at,
+    }
+}
+
+#[async_trait(?Send)]
+impl storage::BoostRules for Storage {
+    async fn get_all(&self) -> Result<Vec<BoostRule>, Error> {
+        let rules = sqlx::query_as::<_, BoostRuleRow>(
+            "SELECT rule_id, property_id, value, weight, expires_at
+            FROM boost_rule
+            ORDER BY rule_id;",
+        )
+        .fetch_all(&self.postgres)
+        .await?;
+
+        Ok(rules.into_iter().map(boost_rule_from_row).collect())
+    }
+
+    async fn get_active(&self, time: DateTime<Utc>) -> Result<Vec<BoostRule>, Error> {
+        let rules = sqlx::query_as::<_, BoostRuleRow>(
+            "SELECT rule_id, property_id, value, weight, expires_at
+            FROM boost_rule
+            WHERE expires_at IS NULL OR expires_at > $1
+            ORDER BY rule_id;",
+        )
+        .bind(time)
+        .fetch_all(&self.postgres.read_only())
+        .await?;
+
+        Ok(rules.into_iter().map(boost_rule_from_row).collect())
+    }
+
+    async fn put(&self, rule: &BoostRule) -> Result<(), Error> {
+        sqlx::query(
+            "INSERT INTO boost_rule (rule_id, property_id, value, weight, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (rule_id) DO UPDATE SET
+                property_id = EXCLUDED.property_id,
+                value = EXCLUDED.value,
+                weight = EXCLUDED.weight,
+                expires_at = EXCLUDED.expires_at;",
+        )
+        .bind(&rule.id)
+        .bind(&rule.property_id)
+        .bind(Json(&rule.value))
+        .bind(rule.weight)
+        .bind(rule.expires_at)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &BoostRuleId) -> Result<Option<()>, Error> {
+        let deleted = sqlx::query("DELETE FROM boost_rule WHERE rule_id = $1;")
+            .bind(id)
+            .execute(&self.postgres)
+            .await?
+            .rows_affected();
+
+        Ok((deleted > 0).then_some(()))
+    }
+}
+
+#[async_trait(?Send)]
+impl storage::Source for Storage {
+    async fn get(&self, user_id: &UserId) -> Result<SourcePreferences, Error> {
+        let sources = sqlx::query_as::<_, (DocumentSource, SourcePreference)>(
+            "SELECT source, preference
+            FROM user_source
+            WHERE user_id = $1
+            ORDER BY source;",
+        )
+        .bind(user_id)
+        .fetch_all(&self.postgres)
+        .await?;
+
+        let (trusted, excluded) =
+            sources
+                .into_iter()
+                .partition_map(|(source, preference)| match preference {
+                    SourcePreference::Trusted => Either::Left(source),
+                    SourcePreference::Excluded => Either::Right(source),
+                });
+
+        Ok(SourcePreferences { trusted, excluded })
+    }
+
+    async fn put(&self, user_id: &UserId, sources: &SourcePreferences) -> Result<(), Error> {
+        let mut tx = self.postgres.begin().await?;
+
+        sqlx::query("DELETE FROM user_source WHERE user_id = $1;")
+            .bind(user_id)
+            .execute(&mut tx)
+            .await?;
+
+        let sources = sources
+            .trusted
+            .iter()
+            .map(|source| (source, SourcePreference::Trusted))
+            .chain(
+                sources
+                    .excluded
+                    .iter()
+                    .map(|source| (source, SourcePreference::Excluded)),
+            )
+            .collect_vec();
+        if !sources.is_empty() {
+            QueryBuilder::new("INSERT INTO user_source (user_id, source, preference) ")
+                .push_values(sources, |mut builder, (source, preference)| {
+                    builder
+                        .push_bind(user_id)
+                        .push_bind(source)
+                        .push_bind(preference);
+                })
+                .push(" ON CONFLICT (user_id, source) DO NOTHING;")
+                .build()
+                .persistent(false)
+                .execute(&mut tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, user_id: &UserId) -> Result<(), Error> {
+        sqlx::query("DELETE FROM user_source WHERE user_id = $1;")
+            .bind(user_id)
+            .execute(&self.postgres)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl storage::Idempotency for Storage {
+    async fn reserve(
+        &self,
+        key: &IdempotencyKey,
+        fingerprint: &Sha256Hash,
+        time: DateTime<Utc>,
+        lease: DateTime<Utc>,
+        expiration: DateTime<Utc>,
+    ) -> Result<IdempotencyState, Error> {
+        let mut tx = self.postgres.begin().await?;
+
+        // Hint: an expired key is reused and an unfinished request whose lease ran out, eg due to
+        //       a crash, is taken over by a retry with the same body
+        let reserved = sqlx::query(
+            "INSERT INTO idempotency_key (key, fingerprint, created_at, locked_until)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (key) DO UPDATE
+            SET fingerprint = EXCLUDED.fingerprint,
+                created_at = EXCLUDED.created_at,
+                locked_until = EXCLUDED.locked_until,
+                status = NULL,
+                response = NULL
+            WHERE idempotency_key.created_at < $5
+                OR (
+                    idempotency_key.status IS NULL
+                    AND idempotency_key.locked_until < EXCLUDED.created_at
+                    AND idempotency_key.fingerprint = EXCLUDED.fingerprint
+                );",
+        )
+        .bind(key)
+        .bind(fingerprint)
+        .bind(time)
+        .bind(lease)
+        .bind(expiration)
+        .execute(&mut tx)
+        .await?
+        .rows_affected()
+            > 0;
+        let state = if reserved {
+            IdempotencyState::Reserved
+        } else {
+            let (stored_fingerprint, status, response) =
+                sqlx::query_as::<_, (Sha256Hash, Option<i16>, Option<Vec<u8>>)>(
+                    "SELECT fingerprint, status, response
+                    FROM idempotency_key
+                    WHERE key = $1;",
+                )
+                .bind(key)
+                .fetch_one(&mut tx)
+                .await?;
+            if &stored_fingerprint != fingerprint {
+                IdempotencyState::Mismatch
+            } else if let Some(status) = status {
+                IdempotencyState::Completed {
+                    status: u16::try_from(status).unwrap_or_default(),
+                    body: response.unwrap_or_default(),
+                }
+            } else {
+                IdempotencyState::InProgress
+            }
+        };
+
+        tx.commit().await?;
+
+        Ok(state)
+    }
+
+    async fn complete(&self, key: &IdempotencyKey, status: u16, body: &[u8]) -> Result<(), Error> {
+        sqlx::query(
+            "UPDATE idempotency_key
+            SET status = $2, response = $3
+            WHERE key = $1;",
+        )
+        .bind(key)
+        .bind(i16::try_from(status).unwrap_or_default())
+        .bind(body)
+        .execute(&self.postgres)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn release(&self, key: &IdempotencyKey) -> Result<(), Error> {
+        sqlx::query("DELETE FROM idempotency_key WHERE key = $1;")
+            .bind(key)
+            .execute(&self.postgres)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn prune(&self, expiration: DateTime<Utc>) -> Result<(), Error> {
+        sqlx::query("DELETE FROM idempotency_key WHERE created_at < $1;")
+            .bind(expiration)
+            .execute(&self.postgres)
+            .await?;
+
+        Ok(())
+    }
+}
+
 #[async_trait(?Send)]
 impl storage::IndexedProperties for Storage {
     async fn load_schema(&self) -> Result<IndexedPropertiesSchema, Error> {
@@ -1447,6 +2552,162 @@ impl storage::IndexedProperties for Storage {
     }
 }
 
+#[async_trait(?Send)]
+impl storage::Facets for Storage {
+    async fn count_by_property(
+        &self,
+        property_id: &DocumentPropertyId,
+        property_type: IndexedPropertyType,
+        count: usize,
+    ) -> Result<Vec<PropertyValueCount>, Error> {
+        self.elastic
+            .count_by_property(property_id, property_type, count)
+            .await
+    }
+}
+
+#[async_trait(?Send)]
+impl storage::Reembedding for Storage {
+    async fn get_after(
+        &self,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<Vec<DocumentForIngestion>, Error> {
+        self.postgres.get_documents_after(after, count).await
+    }
+
+    async fn copy_schema(&self, ingestion_config: &IngestionConfig) -> Result<(), Error> {
+        let mut tx = self.postgres.begin().await?;
+        let schema = Database::load_schema(&mut tx).await?;
+        tx.commit().await?;
+        let update = schema.into_iter().collect::<HashMap<_, _>>().into();
+        self.elastic
+            .extend_mapping(&update, &ingestion_config.index_update)
+            .await
+    }
+
+    async fn stage(
+        &self,
+        documents: Vec<DocumentForIngestion>,
+    ) -> Result<Warning<DocumentId>, Error> {
+        self.postgres.stage_snippets(&documents).await?;
+        let candidates = documents
+            .into_iter()
+            .filter(|document| document.is_candidate)
+            .collect_vec();
+
+        self.elastic.upsert_documents(&candidates).await
+    }
+
+    async fn get_unstaged(&self, count: usize) -> Result<Vec<DocumentForIngestion>, Error> {
+        self.postgres.get_unstaged_documents(count).await
+    }
+
+    async fn discard(&self) -> Result<(), Error> {
+        self.postgres.discard_staged_snippets().await
+    }
+
+    async fn apply(&self) -> Result<u64, Error> {
+        self.postgres
+            .apply_staged_snippets(&self.tenant.tenant_id, self.tenant.es_index_name.clone())
+            .await
+    }
+}
+
+#[async_trait(?Send)]
+impl storage::Consistency for Storage {
+    async fn check(
+        &self,
+        after: Option<&DocumentId>,
+        count: usize,
+    ) -> Result<ConsistencyReport, Error> {
+        let mut tx = self.postgres.begin().await?;
+        let pending = Database::get_pending(&mut tx, Utc::now(), count).await?;
+        let documents = Database::list_excerpted(&mut tx, after, count).await?;
+        let candidates = Database::count_candidates(&mut tx).await?;
+        tx.commit().await?;
+
+        let ids = documents.iter().map(|document| &document.id).collect_vec();
+        let indexed = self.elastic.get_indexed(&ids).await?;
+        let (missing, unexpected) = documents
+            .iter()
+            .filter(|document| document.is_candidate != indexed.contains(&document.id))
+            .partition_map(|document| {
+                if document.is_candidate {
+                    Either::Left(document.id.clone())
+                } else {
+                    Either::Right(document.id.clone())
+                }
+            });
+
+        Ok(ConsistencyReport {
+            pending,
+            missing,
+            unexpected,
+            last: documents.last().map(|document| document.id.clone()),
+            candidates,
+            indexed: self.elastic.count_indexed().await?,
+        })
+    }
+
+    async fn get_sync_state(
+        &self,
+        ids: &[&DocumentId],
+    ) -> Result<(HashSet<DocumentId>, HashSet<DocumentId>), Error> {
+        let mut tx = self.postgres.begin().await?;
+        let pending = Database::get_pending_of(&mut tx, ids.iter().copied()).await?;
+        tx.commit().await?;
+        let indexed = self.elastic.get_indexed(ids).await?;
+
+        Ok((pending, indexed))
+    }
+
+    async fn mark_pending(&self, ids: impl IntoIterator<Item = &DocumentId>) -> Result<(), Error> {
+        let mut tx = self.postgres.begin().await?;
+        Database::add_to_outbox(&mut tx, ids).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn apply_pending(
+        &self,
+        before: DateTime<Utc>,
+        count: usize,
+    ) -> Result<Warning<DocumentId>, Error> {
+        let mut tx = self.postgres.begin().await?;
+        let pending = Database::get_pending(&mut tx, before, count).await?;
+        let documents = Database::get_excerpted(&mut tx, pending.iter()).await?;
+        let documents = Database::load_snippets(&mut tx, documents).await?;
+        tx.commit().await?;
+
+        let (candidates, noncandidates) = documents
+            .into_iter()
+            .partition_map::<Vec<_>, Vec<_>, _, _, _>(|document| {
+                if document.is_candidate {
+                    Either::Left(document)
+                } else {
+                    Either::Right(document.id)
+                }
+            });
+        let failed_documents = self.elastic.upsert_documents(&candidates).await?;
+        self.elastic.delete_by_parents(&noncandidates).await?;
+        self.postgres
+            .remove_from_outbox(
+                candidates
+                    .iter()
+                    .map(|document| &document.id)
+                    .filter(|id| !failed_documents.contains(id))
+                    .chain(&noncandidates),
+                // Hint: documents which have been marked again since they were read are newer
+                before,
+            )
+            .await?;
+
+        Ok(failed_documents)
+    }
+}
+
 impl Database {
     async fn load_schema(
         tx: &mut Transaction<'_, Postgres>,
@@ -15,9 +15,10 @@
 use async_stream::try_stream;
 use either::Either;
 use futures_util::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt, TryStreamExt};
+use serde::Serialize;
 use sqlx::{
     pool::{PoolConnection, PoolOptions},
-    postgres::{PgQueryResult, PgRow, PgStatement, PgTypeInfo},
+    postgres::{PgConnectOptions, PgQueryResult, PgRow, PgStatement, PgTypeInfo},
     Acquire,
     Describe,
     Execute,
@@ -38,17 +39,23 @@ use crate::{Error, SetupError};
 #[derive(Clone)]
 pub(crate) struct DatabaseBuilder {
     pool: Pool<Postgres>,
+    replica: Option<Pool<Postgres>>,
     legacy_tenant: Option<TenantId>,
+    max_pool_size: u32,
 }
 
 impl DatabaseBuilder {
     pub(crate) async fn close(&self) {
         self.pool.close().await;
+        if let Some(replica) = &self.replica {
+            replica.close().await;
+        }
     }
 
     pub(crate) fn build_for(&self, tenant: &Tenant) -> Database {
         Database {
             pool: self.pool.clone(),
+            replica: self.replica.as_ref().unwrap_or(&self.pool).clone(),
             tenant_db_name: QuotedIdentifier::db_name_for_tenant_id(&tenant.tenant_id),
         }
     }
@@ -57,6 +64,14 @@ impl DatabaseBuilder {
         self.legacy_tenant.as_ref()
     }
 
+    pub(crate) fn pool_metrics(&self) -> PoolMetrics {
+        PoolMetrics {
+            size: self.pool.size(),
+            idle: self.pool.num_idle(),
+            max_size: self.max_pool_size,
+        }
+    }
+
     /// Get a db connection for the `MT_USER`.
     ///
     /// This connection should be able to read the `management.tenant` table but might not
@@ -66,9 +81,22 @@ impl DatabaseBuilder {
     }
 }
 
+/// The utilization of the connection pool.
+#[derive(Debug, Serialize)]
+pub(crate) struct PoolMetrics {
+    /// Number of open connections.
+    size: u32,
+    /// Number of open connections which are not in use.
+    idle: usize,
+    /// Maximum number of connections.
+    max_size: u32,
+}
+
 #[derive(Debug)]
 pub(crate) struct Database {
     pool: Pool<Postgres>,
+    /// The read replica, this is the same as `pool` if no replica is configured.
+    replica: Pool<Postgres>,
     #[allow(dead_code)]
     tenant_db_name: QuotedIdentifier,
 }
@@ -79,11 +107,37 @@ impl Database {
         config: &Config,
         legacy_tenant: Option<TenantId>,
     ) -> Result<DatabaseBuilder, SetupError> {
-        let options = config.to_connection_options()?;
         info!("starting postgres setup");
-        let pool = PoolOptions::new()
+        let pool = Self::connect(config, config.to_connection_options()?).await?;
+        let replica = if let Some(options) = config.to_replica_connection_options() {
+            info!("connecting to postgres read replica");
+            Some(Self::connect(config, options?).await?)
+        } else {
+            None
+        };
+
+        Ok(DatabaseBuilder {
+            pool,
+            replica,
+            legacy_tenant,
+            max_pool_size: u32::from(config.max_pool_size),
+        })
+    }
+
+    async fn connect(
+        config: &Config,
+        mut options: PgConnectOptions,
+    ) -> Result<Pool<Postgres>, sqlx::Error> {
+        if !config.statement_timeout.is_zero() {
+            options =
+                options.options([("statement_timeout", config.statement_timeout.as_millis())]);
+        }
+
+        PoolOptions::new()
             .min_connections(u32::from(config.min_pool_size))
             .max_connections(u32::from(config.max_pool_size))
+            .acquire_timeout(config.acquire_timeout)
+            .idle_timeout((!config.idle_timeout.is_zero()).then_some(config.idle_timeout))
             .after_release(|conn, _metadata| {
                 async {
                     sqlx::query("RESET ROLE;").execute(conn).await?;
@@ -92,12 +146,18 @@ impl Database {
                 .boxed()
             })
             .connect_with(options)
-            .await?;
+            .await
+    }
 
-        Ok(DatabaseBuilder {
-            pool,
-            legacy_tenant,
-        })
+    /// Returns a database which sends queries to the read replica, if one is configured.
+    ///
+    /// Only use this for read-only queries which can tolerate a slight replication lag.
+    pub(crate) fn read_only(&self) -> Self {
+        Self {
+            pool: self.replica.clone(),
+            replica: self.replica.clone(),
+            tenant_db_name: self.tenant_db_name.clone(),
+        }
     }
 
     async fn set_role(
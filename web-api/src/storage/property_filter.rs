@@ -39,7 +39,7 @@ pub(crate) enum IncompatibleUpdate {
 }
 
 //Hint: Currently the API and internal definition match so we use the same type.
-#[derive(Debug, Clone, Default, Deref, IntoIterator, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Deref, IntoIterator, From, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct IndexedPropertiesSchemaUpdate {
     #[into_iterator(owned, ref)]
@@ -32,5 +32,6 @@ impl Application for WebApi {
 
     fn configure_ops_service(config: &mut ServiceConfig) {
         crate::backoffice::routes::configure_ops_service(config);
+        crate::app::configure_ops_service(config);
     }
 }